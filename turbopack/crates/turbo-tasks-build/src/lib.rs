@@ -11,8 +11,8 @@ use anyhow::{Context, Result};
 use glob::glob;
 use quote::ToTokens;
 use syn::{
-    parse_quote, Attribute, Ident, Item, ItemEnum, ItemFn, ItemImpl, ItemMacro, ItemMod,
-    ItemStruct, ItemTrait, TraitItem, TraitItemMethod,
+    parse::ParseStream, parse_quote, Attribute, Ident, Item, ItemEnum, ItemFn, ItemImpl, ItemMacro,
+    ItemMod, ItemStruct, ItemTrait, TraitItem, TraitItemMethod,
 };
 use turbo_tasks_macros_shared::{
     get_impl_function_ident, get_native_function_ident, get_path_ident,
@@ -249,7 +249,13 @@ impl<'a> RegisterContext<'a> {
     fn process_enum_inner(&mut self, enum_item: &ItemEnum) -> Result<()> {
         if has_turbo_attribute(&enum_item.attrs, "value") {
             self.add_value(&enum_item.ident);
-            self.add_value_debug_impl(&enum_item.ident);
+            // monomorphized values don't get a default `ValueDebug` impl
+            if !has_value_argument(&enum_item.attrs, "monomorphize") {
+                self.add_value_debug_impl(&enum_item.ident);
+            }
+            if has_value_argument(&enum_item.attrs, "map_get") {
+                self.add_value_map_get_impl(&enum_item.ident)?;
+            }
         }
         Ok(())
     }
@@ -287,9 +293,11 @@ impl<'a> RegisterContext<'a> {
 
             for item in &impl_item.items {
                 if let syn::ImplItem::Method(method_item) = item {
-                    // TODO: if method_item.attrs.iter().any(|a|
-                    // is_attribute(a,
-                    // "function")) {
+                    // Methods without `#[turbo_tasks::function]` are plain methods in inherent
+                    // impls, which are passed through unchanged.
+                    if !has_turbo_attribute(&method_item.attrs, "function") {
+                        continue;
+                    }
                     let method_ident = &method_item.sig.ident;
                     let function_type_ident = if let Some(trait_ident) = &trait_ident {
                         get_trait_impl_function_ident(&struct_ident, trait_ident, method_ident)
@@ -303,7 +311,9 @@ impl<'a> RegisterContext<'a> {
                         self.get_global_name(&[&struct_ident, method_ident])
                     };
 
-                    self.register(function_type_ident, global_name)?;
+                    self.with_cfg_attrs(&method_item.attrs, move |this| {
+                        this.register(function_type_ident, global_name)
+                    })?;
                 }
             }
         }
@@ -353,7 +363,13 @@ impl<'a> RegisterContext<'a> {
     fn process_struct_inner(&mut self, struct_item: &ItemStruct) -> Result<()> {
         if has_turbo_attribute(&struct_item.attrs, "value") {
             self.add_value(&struct_item.ident);
-            self.add_value_debug_impl(&struct_item.ident);
+            // monomorphized values don't get a default `ValueDebug` impl
+            if !has_value_argument(&struct_item.attrs, "monomorphize") {
+                self.add_value_debug_impl(&struct_item.ident);
+            }
+            if has_value_argument(&struct_item.attrs, "map_get") {
+                self.add_value_map_get_impl(&struct_item.ident)?;
+            }
         }
         Ok(())
     }
@@ -374,17 +390,18 @@ impl<'a> RegisterContext<'a> {
                 if let TraitItem::Method(TraitItemMethod {
                     default: Some(_),
                     sig,
+                    attrs,
                     ..
                 }) = item
                 {
                     let method_ident = &sig.ident;
                     let function_type_ident =
                         get_trait_default_impl_function_ident(trait_ident, method_ident);
+                    let global_name = self.get_global_name(&[trait_ident, method_ident]);
 
-                    self.register(
-                        function_type_ident,
-                        self.get_global_name(&[trait_ident, method_ident]),
-                    )?;
+                    self.with_cfg_attrs(attrs, move |this| {
+                        this.register(function_type_ident, global_name)
+                    })?;
                 }
             }
 
@@ -478,6 +495,15 @@ impl<'a> RegisterContext<'a> {
         );
     }
 
+    fn add_value_map_get_impl(&mut self, ident: &Ident) -> std::fmt::Result {
+        // register the `get` function generated by proc macro for `map_get`
+        let method_ident = Ident::new("get", ident.span());
+        self.register(
+            get_impl_function_ident(ident, &method_ident),
+            self.get_global_name(&[ident, &method_ident]),
+        )
+    }
+
     fn add_value_default_impl(&mut self, ident: &Ident) {
         // register default ValueDefault impl generated by proc macro
         self.register_default_impl(ident).unwrap();
@@ -582,6 +608,28 @@ fn has_turbo_attribute(attrs: &[Attribute], name: &str) -> bool {
     attrs.iter().any(|a| is_turbo_attribute(a, name))
 }
 
+/// Whether the `#[turbo_tasks::value]` attribute has the argument `name`, e.g. `monomorphize` in
+/// `#[turbo_tasks::value(monomorphize = [...])]`.
+fn has_value_argument(attrs: &[Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|a| is_turbo_attribute(a, "value"))
+        .any(|attr| {
+            attr.parse_args_with(|input: ParseStream| {
+                input.step(|cursor| {
+                    let mut rest = *cursor;
+                    let mut found = false;
+                    while let Some((token, next)) = rest.token_tree() {
+                        found |= token.to_string() == name;
+                        rest = next;
+                    }
+                    Ok((found, rest))
+                })
+            })
+            .unwrap_or(false)
+        })
+}
+
 fn is_turbo_attribute(attr: &Attribute, name: &str) -> bool {
     let path = &attr.path;
     if path.leading_colon.is_some() {
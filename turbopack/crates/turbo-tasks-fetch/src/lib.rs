@@ -2,7 +2,10 @@
 #![feature(arbitrary_self_types)]
 
 use anyhow::Result;
-use turbo_tasks::{RcStr, Vc};
+use turbo_tasks::{
+    io_enforcement::{check_io, IoKind},
+    RcStr, Vc,
+};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::issue::{Issue, IssueSeverity, IssueStage, OptionStyledString, StyledString};
 
@@ -70,6 +73,7 @@ pub async fn fetch(
         builder = builder.header("User-Agent", user_agent.as_str());
     }
 
+    check_io(IoKind::Network, "Network request");
     let response = builder.send().await.and_then(|r| r.error_for_status());
     match response {
         Ok(response) => {
@@ -2,6 +2,7 @@ use std::{future::Future, io, io::ErrorKind, path::Path, thread::sleep, time::Du
 
 use futures_retry::{ErrorHandler, FutureRetry, RetryPolicy};
 use tokio::task::spawn_blocking;
+use turbo_tasks::io_enforcement::{check_io, IoKind};
 
 const MAX_RETRY_ATTEMPTS: usize = 10;
 
@@ -10,6 +11,7 @@ where
     F: FnMut() -> Fut + Unpin,
     Fut: Future<Output = io::Result<R>> + 'a,
 {
+    check_io(IoKind::Filesystem, "Filesystem access");
     match FutureRetry::new(
         func,
         FsRetryHandler {
@@ -32,6 +34,7 @@ where
     F: Fn(&Path) -> io::Result<R> + Send + 'static,
     R: Send + 'static,
 {
+    check_io(IoKind::Filesystem, "Filesystem access");
     let path = path.as_ref().to_owned();
 
     let current_span = tracing::Span::current();
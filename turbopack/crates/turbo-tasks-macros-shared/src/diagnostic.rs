@@ -0,0 +1,40 @@
+use proc_macro2::Span;
+use proc_macro_error::{Diagnostic, Level};
+
+/// Reports diagnostics through `proc_macro_error`, which emits them with `proc_macro::Diagnostic`
+/// on nightly, and falls back to `compile_error!` invocations at the same spans on stable.
+///
+/// This mirrors the `proc_macro::Span` API, e.g. `span.error("message").help("hint").emit()`.
+/// Diagnostics can only be emitted from within a `#[proc_macro_error]` entry point.
+pub trait SpanDiagnosticExt {
+    fn error(self, message: impl Into<String>) -> SpanDiagnostic;
+    fn warning(self, message: impl Into<String>) -> SpanDiagnostic;
+}
+
+impl SpanDiagnosticExt for Span {
+    fn error(self, message: impl Into<String>) -> SpanDiagnostic {
+        SpanDiagnostic(Diagnostic::spanned(self, Level::Error, message.into()))
+    }
+
+    fn warning(self, message: impl Into<String>) -> SpanDiagnostic {
+        SpanDiagnostic(Diagnostic::spanned(self, Level::Warning, message.into()))
+    }
+}
+
+/// A diagnostic created with [`SpanDiagnosticExt`]. Nothing is reported until it's emitted.
+#[must_use = "diagnostics are only reported when they're emitted"]
+pub struct SpanDiagnostic(Diagnostic);
+
+impl SpanDiagnostic {
+    pub fn help(self, message: impl Into<String>) -> Self {
+        SpanDiagnostic(self.0.help(message.into()))
+    }
+
+    pub fn note(self, message: impl Into<String>) -> Self {
+        SpanDiagnostic(self.0.note(message.into()))
+    }
+
+    pub fn emit(self) {
+        self.0.emit()
+    }
+}
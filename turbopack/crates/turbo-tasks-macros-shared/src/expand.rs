@@ -5,6 +5,8 @@ use syn::{
     FieldsUnnamed,
 };
 
+use crate::SpanDiagnosticExt;
+
 /// Handles the expansion of a struct/enum into a match statement that accesses
 /// every field for procedural code generation.
 ///
@@ -92,11 +94,7 @@ pub fn match_expansion<
             }
         }
         _ => {
-            derive_input
-                .span()
-                .unwrap()
-                .error("unsupported syntax")
-                .emit();
+            derive_input.span().error("unsupported syntax").emit();
 
             quote! {}
         }
@@ -1,5 +1,10 @@
 use quote::ToTokens;
-use syn::{spanned::Spanned, GenericArgument, Ident, Path, PathArguments, Type, TypeParamBound};
+use syn::{
+    ext::IdentExt, spanned::Spanned, GenericArgument, Ident, Path, PathArguments, Type,
+    TypeParamBound,
+};
+
+use crate::SpanDiagnosticExt;
 
 pub fn get_register_value_type_ident(struct_ident: &Ident) -> Ident {
     Ident::new(
@@ -17,21 +22,120 @@ pub fn get_register_trait_methods_ident(trait_ident: &Ident, struct_ident: &Iden
 
 pub fn get_native_function_ident(ident: &Ident) -> Ident {
     Ident::new(
-        &format!("{}_FUNCTION", ident.to_string().to_uppercase()),
+        &format!("{}_FUNCTION", ident.unraw().to_string().to_uppercase()),
         ident.span(),
     )
 }
 
 pub fn get_native_function_id_ident(ident: &Ident) -> Ident {
     Ident::new(
-        &format!("{}_FUNCTION_ID", ident.to_string().to_uppercase()),
+        &format!("{}_FUNCTION_ID", ident.unraw().to_string().to_uppercase()),
         ident.span(),
     )
 }
 
+/// The ident of the `&str` const describing the exposed signature of the function registered as
+/// `native_function_ident`.
+pub fn get_function_signature_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_SIGNATURE"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `&[&str]` const listing the parameter names of the function registered as
+/// `native_function_ident`.
+pub fn get_function_parameter_names_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_PARAMETER_NAMES"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `usize` const holding the number of arguments (excluding `self`) of the
+/// function registered as `native_function_ident`.
+pub fn get_function_arg_count_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_ARG_COUNT"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `&str` const describing the context (e.g. a `#[turbo_tasks::value_impl]`
+/// inherent method) in which the function registered as `native_function_ident` is defined.
+pub fn get_function_definition_context_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_DEFINITION_CONTEXT"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `&str` const describing the exposed return type of the function registered as
+/// `native_function_ident`.
+pub fn get_function_return_type_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_RETURN_TYPE"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `&str` const holding the `module_path!()` of the function registered as
+/// `native_function_ident`.
+pub fn get_function_module_path_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_MODULE_PATH"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `AtomicU64` static counting the calls of the function registered as
+/// `native_function_ident`.
+pub fn get_function_call_count_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_CALL_COUNT"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `AtomicU64` static holding the duration of the last execution (in
+/// microseconds) of the function registered as `native_function_ident`.
+pub fn get_function_last_duration_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_LAST_DURATION"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `bool` const telling whether the function registered as
+/// `native_function_ident` uses local cells.
+pub fn get_function_local_cells_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_LOCAL_CELLS"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `bool` const telling whether the function registered as
+/// `native_function_ident` is a method (takes `self`).
+pub fn get_function_is_method_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_IS_METHOD"),
+        native_function_ident.span(),
+    )
+}
+
+/// The ident of the `bool` const telling whether the function registered as
+/// `native_function_ident` has a receiver (takes `self`).
+pub fn get_function_has_receiver_ident(native_function_ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("{native_function_ident}_HAS_RECEIVER"),
+        native_function_ident.span(),
+    )
+}
+
 pub fn get_trait_type_ident(ident: &Ident) -> Ident {
     Ident::new(
-        &format!("{}_TRAIT_TYPE", ident.to_string().to_uppercase()),
+        &format!("{}_TRAIT_TYPE", ident.unraw().to_string().to_uppercase()),
         ident.span(),
     )
 }
@@ -40,8 +144,8 @@ pub fn get_impl_function_ident(struct_ident: &Ident, ident: &Ident) -> Ident {
     Ident::new(
         &format!(
             "{}_IMPL_{}_FUNCTION",
-            struct_ident.to_string().to_uppercase(),
-            ident.to_string().to_uppercase()
+            struct_ident.unraw().to_string().to_uppercase(),
+            ident.unraw().to_string().to_uppercase()
         ),
         ident.span(),
     )
@@ -51,8 +155,8 @@ pub fn get_inherent_impl_function_ident(ty_ident: &Ident, fn_ident: &Ident) -> I
     Ident::new(
         &format!(
             "{}_IMPL_{}_FUNCTION",
-            ty_ident.to_string().to_uppercase(),
-            fn_ident.to_string().to_uppercase()
+            ty_ident.unraw().to_string().to_uppercase(),
+            fn_ident.unraw().to_string().to_uppercase()
         ),
         fn_ident.span(),
     )
@@ -62,8 +166,8 @@ pub fn get_inherent_impl_function_id_ident(ty_ident: &Ident, fn_ident: &Ident) -
     Ident::new(
         &format!(
             "{}_IMPL_{}_FUNCTION_ID",
-            ty_ident.to_string().to_uppercase(),
-            fn_ident.to_string().to_uppercase()
+            ty_ident.unraw().to_string().to_uppercase(),
+            fn_ident.unraw().to_string().to_uppercase()
         ),
         fn_ident.span(),
     )
@@ -77,9 +181,9 @@ pub fn get_trait_impl_function_ident(
     Ident::new(
         &format!(
             "{}_IMPL_TRAIT_{}_{}_FUNCTION",
-            struct_ident.to_string().to_uppercase(),
-            trait_ident.to_string().to_uppercase(),
-            ident.to_string().to_uppercase()
+            struct_ident.unraw().to_string().to_uppercase(),
+            trait_ident.unraw().to_string().to_uppercase(),
+            ident.unraw().to_string().to_uppercase()
         ),
         ident.span(),
     )
@@ -93,9 +197,9 @@ pub fn get_trait_impl_function_id_ident(
     Ident::new(
         &format!(
             "{}_IMPL_TRAIT_{}_{}_FUNCTION_ID",
-            struct_ident.to_string().to_uppercase(),
-            trait_ident.to_string().to_uppercase(),
-            ident.to_string().to_uppercase()
+            struct_ident.unraw().to_string().to_uppercase(),
+            trait_ident.unraw().to_string().to_uppercase(),
+            ident.unraw().to_string().to_uppercase()
         ),
         ident.span(),
     )
@@ -103,7 +207,7 @@ pub fn get_trait_impl_function_id_ident(
 
 pub fn get_internal_trait_impl_function_ident(trait_ident: &Ident, ident: &Ident) -> Ident {
     Ident::new(
-        &format!("__trait_call_{trait_ident}_{ident}"),
+        &format!("__trait_call_{}_{}", trait_ident.unraw(), ident.unraw()),
         trait_ident.span(),
     )
 }
@@ -139,7 +243,6 @@ pub fn get_path_ident(path: &Path) -> Ident {
                                 }
                             } else {
                                 arg.span()
-                                    .unwrap()
                                     .error(
                                         "#[turbo_tasks::value_impl] does not support this type \
                                          argument",
@@ -149,7 +252,6 @@ pub fn get_path_ident(path: &Path) -> Ident {
                         }
                         _ => arg
                             .span()
-                            .unwrap()
                             .error("#[turbo_tasks::value_impl] does not support this type argument")
                             .emit(),
                     }
@@ -159,7 +261,6 @@ pub fn get_path_ident(path: &Path) -> Ident {
             _ => {
                 segment
                     .span()
-                    .unwrap()
                     .error("#[turbo_tasks::value_impl] does not support this type argument")
                     .emit();
             }
@@ -175,7 +276,6 @@ pub fn get_type_ident(ty: &Type) -> Option<Ident> {
         Type::Tuple(tuple) => Some(Ident::new("unit", tuple.span())),
         _ => {
             ty.span()
-                .unwrap()
                 .error(format!(
                     "#[turbo_tasks::value_impl] does not support the type {}, expected T or \
                      Box<dyn Trait>",
@@ -195,12 +295,18 @@ pub fn get_trait_ref_ident(ident: &Ident) -> Ident {
     Ident::new(&(ident.to_string() + "TraitRef"), ident.span())
 }
 
+/// The ident of the trait holding the associated consts of a value trait. It's separate from the
+/// value trait, as consts would make `dyn Trait` impossible.
+pub fn get_trait_consts_ident(ident: &Ident) -> Ident {
+    Ident::new(&(ident.to_string() + "Consts"), ident.span())
+}
+
 pub fn get_trait_default_impl_function_ident(trait_ident: &Ident, ident: &Ident) -> Ident {
     Ident::new(
         &format!(
             "{}_DEFAULT_IMPL_{}_FUNCTION",
-            trait_ident.to_string().to_uppercase(),
-            ident.to_string().to_uppercase()
+            trait_ident.unraw().to_string().to_uppercase(),
+            ident.unraw().to_string().to_uppercase()
         ),
         ident.span(),
     )
@@ -208,7 +314,7 @@ pub fn get_trait_default_impl_function_ident(trait_ident: &Ident, ident: &Ident)
 
 pub fn get_trait_type_id_ident(ident: &Ident) -> Ident {
     Ident::new(
-        &format!("{}_TRAIT_TYPE_ID", ident.to_string().to_uppercase()),
+        &format!("{}_TRAIT_TYPE_ID", ident.unraw().to_string().to_uppercase()),
         ident.span(),
     )
 }
@@ -217,8 +323,8 @@ pub fn get_trait_default_impl_function_id_ident(trait_ident: &Ident, ident: &Ide
     Ident::new(
         &format!(
             "{}_DEFAULT_IMPL_{}_FUNCTION_ID",
-            trait_ident.to_string().to_uppercase(),
-            ident.to_string().to_uppercase()
+            trait_ident.unraw().to_string().to_uppercase(),
+            ident.unraw().to_string().to_uppercase()
         ),
         ident.span(),
     )
@@ -226,21 +332,24 @@ pub fn get_trait_default_impl_function_id_ident(trait_ident: &Ident, ident: &Ide
 
 pub fn get_value_type_ident(ident: &Ident) -> Ident {
     Ident::new(
-        &format!("{}_VALUE_TYPE", ident.to_string().to_uppercase()),
+        &format!("{}_VALUE_TYPE", ident.unraw().to_string().to_uppercase()),
         ident.span(),
     )
 }
 
 pub fn get_value_type_id_ident(ident: &Ident) -> Ident {
     Ident::new(
-        &format!("{}_VALUE_TYPE_ID", ident.to_string().to_uppercase()),
+        &format!("{}_VALUE_TYPE_ID", ident.unraw().to_string().to_uppercase()),
         ident.span(),
     )
 }
 
 pub fn get_value_type_init_ident(ident: &Ident) -> Ident {
     Ident::new(
-        &format!("{}_VALUE_TYPE_INIT", ident.to_string().to_uppercase()),
+        &format!(
+            "{}_VALUE_TYPE_INIT",
+            ident.unraw().to_string().to_uppercase()
+        ),
         ident.span(),
     )
 }
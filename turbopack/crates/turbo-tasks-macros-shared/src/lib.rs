@@ -1,12 +1,11 @@
-#![feature(proc_macro_diagnostic)]
-#![feature(box_patterns)]
-
+mod diagnostic;
 mod expand;
 mod generic_type_input;
 mod ident;
 mod primitive_input;
 mod value_trait_arguments;
 
+pub use diagnostic::{SpanDiagnostic, SpanDiagnosticExt};
 pub use expand::*;
 pub use generic_type_input::GenericTypeInput;
 pub use ident::*;
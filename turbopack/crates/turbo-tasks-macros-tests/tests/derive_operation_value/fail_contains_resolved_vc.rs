@@ -0,0 +1,10 @@
+#![allow(dead_code)]
+
+use turbo_tasks::{OperationValue, ResolvedVc};
+
+#[derive(OperationValue)]
+struct ContainsResolvedVc {
+    a: ResolvedVc<i32>,
+}
+
+fn main() {}
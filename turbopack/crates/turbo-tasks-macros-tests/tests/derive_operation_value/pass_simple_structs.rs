@@ -0,0 +1,23 @@
+#![allow(dead_code)]
+
+use std::marker::PhantomData;
+
+use turbo_tasks::{OperationValue, OperationVc};
+
+#[derive(OperationValue)]
+struct UnitStruct;
+
+#[derive(OperationValue)]
+struct ContainsSimpleValuesNamed {
+    a: i32,
+    b: String,
+    c: (),
+    d: (u8, u8, (u8, u8, u8)),
+    e: [u8; 8],
+    f: PhantomData<u8>,
+}
+
+#[derive(OperationValue)]
+struct ContainsOperationVc(OperationVc<u32>, Vec<OperationVc<String>>);
+
+fn main() {}
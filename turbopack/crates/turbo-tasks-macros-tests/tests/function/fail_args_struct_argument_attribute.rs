@@ -0,0 +1,11 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::{RcStr, Vc};
+
+#[turbo_tasks::function(args_struct)]
+fn label(#[turbo_tasks(untracked)] name: RcStr, count: u32) -> Vc<RcStr> {
+    Vc::cell(format!("{name} ({count})").into())
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct ExampleStruct;
+
+#[turbo_tasks::value_impl]
+impl ExampleStruct {
+    #[turbo_tasks::function(args_struct)]
+    fn sum(a: u32, b: u32) -> Vc<u32> {
+        Vc::cell(a + b)
+    }
+}
+
+fn main() {}
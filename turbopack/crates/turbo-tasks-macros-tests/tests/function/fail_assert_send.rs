@@ -0,0 +1,15 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function(assert_send)]
+async fn non_send_body(value: Vc<u32>) -> anyhow::Result<Vc<u32>> {
+    let shared = Rc::new(*value.await?);
+    let value = *value.await?;
+    Ok(Vc::cell(*shared + value))
+}
+
+fn main() {}
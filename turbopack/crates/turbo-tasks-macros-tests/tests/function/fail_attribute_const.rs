@@ -0,0 +1,7 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+#[turbo_tasks::function]
+const VALUE: u32 = 42;
+
+fn main() {}
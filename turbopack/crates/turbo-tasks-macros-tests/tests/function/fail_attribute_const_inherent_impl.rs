@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+#[turbo_tasks::value]
+struct ExampleStruct;
+
+#[turbo_tasks::value_impl]
+impl ExampleStruct {
+    #[turbo_tasks::function]
+    const VALUE: u32 = 42;
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#![feature(arbitrary_self_types)]
+#![deny(deprecated)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function]
+fn new_value(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[turbo_tasks::function(deprecated, replacement = new_value)]
+fn old_value(value: u32) -> Vc<u32> {}
+
+fn main() {
+    let _ = old_value(1);
+}
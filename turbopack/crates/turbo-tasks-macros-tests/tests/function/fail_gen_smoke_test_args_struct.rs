@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+fn register() {}
+
+#[turbo_tasks::function(args_struct, gen_smoke_test = register)]
+fn sum(a: u32, b: u32) -> Vc<u32> {
+    Vc::cell(a + b)
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+fn register() {}
+
+#[turbo_tasks::function(no_cache, gen_smoke_test = register)]
+fn random(seed: u32) -> Vc<u32> {
+    Vc::cell(seed)
+}
+
+fn main() {}
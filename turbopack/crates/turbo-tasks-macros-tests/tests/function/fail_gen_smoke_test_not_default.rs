@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+fn register() {}
+
+#[turbo_tasks::function(gen_smoke_test = register)]
+fn double(value: Vc<u32>) -> Vc<u32> {
+    value
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::{TaskInput, Vc};
+
+#[turbo_tasks::function]
+fn generic<T: TaskInput>(value: T) -> Vc<u32> {
+    Vc::cell(0)
+}
+
+fn main() {}
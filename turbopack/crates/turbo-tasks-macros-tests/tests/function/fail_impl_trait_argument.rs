@@ -0,0 +1,11 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function]
+fn displayed_len(value: impl std::fmt::Display) -> Vc<u32> {
+    Vc::cell(value.to_string().len() as u32)
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use turbo_tasks::{RcStr, ResolvedVc, Vc};
+
+#[turbo_tasks::function]
+fn count(values: HashMap<RcStr, ResolvedVc<u32>>) -> Vc<u32> {
+    Vc::cell(values.len() as u32)
+}
+
+fn main() {}
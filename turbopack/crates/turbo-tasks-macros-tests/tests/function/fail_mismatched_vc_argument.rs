@@ -0,0 +1,16 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::{ResolvedVc, Vc};
+
+#[turbo_tasks::function]
+fn double(value: ResolvedVc<u32>) -> Vc<u32> {
+    *value
+}
+
+#[turbo_tasks::function]
+fn double_str(value: ResolvedVc<String>) -> Vc<u32> {
+    double(*value)
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![feature(arbitrary_self_types)]
+
+use turbo_tasks::{Vc, VcValueType};
+
+struct NotAValue;
+
+#[turbo_tasks::function(monomorphize = [u32, NotAValue])]
+fn identity<T: VcValueType>(value: Vc<T>) -> Vc<T> {
+    value
+}
+
+fn main() {}
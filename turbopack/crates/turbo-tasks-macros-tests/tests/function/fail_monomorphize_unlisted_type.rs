@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::{RcStr, Vc, VcValueType};
+
+#[turbo_tasks::function(monomorphize = [u32, RcStr])]
+fn identity<T: VcValueType>(value: Vc<T>) -> Vc<T> {
+    value
+}
+
+fn main() {
+    let _: fn(Vc<bool>) -> Vc<bool> = identity::<bool>;
+}
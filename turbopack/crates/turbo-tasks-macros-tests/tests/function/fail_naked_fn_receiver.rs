@@ -0,0 +1,11 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function]
+fn not_a_method(&self) -> Vc<u32> {
+    Vc::cell(0)
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct ExampleStruct;
+
+#[turbo_tasks::value_trait]
+trait ExampleTrait {
+    fn random(self: Vc<Self>) -> Vc<u32>;
+}
+
+#[turbo_tasks::value_impl]
+impl ExampleTrait for ExampleStruct {
+    #[turbo_tasks::function(no_cache)]
+    fn random(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(42)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function(operation)]
+async fn optional_operation() -> Result<Option<Vc<u32>>> {
+    Ok(Some(Vc::cell(42)))
+}
+
+fn main() {}
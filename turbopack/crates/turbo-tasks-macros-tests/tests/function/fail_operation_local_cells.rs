@@ -0,0 +1,11 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function(operation, local_cells)]
+fn conflicting_arguments() -> Vc<u32> {
+    Vc::cell(42)
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct Counter(u32);
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    #[turbo_tasks::function(operation)]
+    fn increment(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(0)
+    }
+}
+
+fn main() {}
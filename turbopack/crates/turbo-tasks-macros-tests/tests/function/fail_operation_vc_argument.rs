@@ -0,0 +1,12 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function(operation)]
+fn multiply(value: Vc<u32>, factor: u32) -> Vc<u32> {
+    let _ = (value, factor);
+    Vc::cell(0)
+}
+
+fn main() {}
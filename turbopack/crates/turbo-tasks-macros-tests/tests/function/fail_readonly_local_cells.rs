@@ -0,0 +1,11 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function(readonly, local_cells)]
+fn conflicting_arguments(value: Vc<u32>) -> Vc<u32> {
+    value
+}
+
+fn main() {}
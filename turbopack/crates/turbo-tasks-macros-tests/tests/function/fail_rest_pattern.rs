@@ -0,0 +1,10 @@
+#![feature(arbitrary_self_types)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function]
+fn first((first, ..): (u32, u32, u32)) -> Vc<u32> {
+    Vc::cell(first)
+}
+
+fn main() {}
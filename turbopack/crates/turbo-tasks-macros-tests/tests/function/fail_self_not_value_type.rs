@@ -0,0 +1,16 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+struct NotAValue;
+
+#[turbo_tasks::value_impl]
+impl NotAValue {
+    #[turbo_tasks::function]
+    fn method(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(0)
+    }
+}
+
+fn main() {}
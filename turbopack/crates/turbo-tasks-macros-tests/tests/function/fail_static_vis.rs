@@ -0,0 +1,11 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function(static_vis = "pub(super)")]
+fn restricted() -> Vc<u32> {
+    Vc::cell(1)
+}
+
+fn main() {}
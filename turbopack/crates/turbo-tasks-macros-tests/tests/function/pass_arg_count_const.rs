@@ -0,0 +1,25 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct Counter(u32);
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    #[turbo_tasks::function]
+    fn add(&self, amount: u32) -> Vc<u32> {
+        Vc::cell(self.0 + amount)
+    }
+}
+
+#[turbo_tasks::function]
+fn multiply(a: u32, b: u32) -> Vc<u32> {
+    Vc::cell(a * b)
+}
+
+const _: () = assert!(COUNTER_IMPL_ADD_FUNCTION_ARG_COUNT == 1);
+const _: () = assert!(MULTIPLY_FUNCTION_ARG_COUNT == 2);
+
+fn main() {}
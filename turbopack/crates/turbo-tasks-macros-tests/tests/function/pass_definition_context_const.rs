@@ -0,0 +1,55 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct Counter(u32);
+
+#[turbo_tasks::value_trait]
+trait Describe {
+    fn describe(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(0)
+    }
+    fn value(self: Vc<Self>) -> Vc<u32>;
+}
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    #[turbo_tasks::function]
+    fn add(&self, amount: u32) -> Vc<u32> {
+        Vc::cell(self.0 + amount)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Describe for Counter {
+    #[turbo_tasks::function]
+    fn value(&self) -> Vc<u32> {
+        Vc::cell(self.0)
+    }
+}
+
+#[turbo_tasks::function]
+fn multiply(a: u32, b: u32) -> Vc<u32> {
+    Vc::cell(a * b)
+}
+
+fn main() {
+    assert_eq!(
+        MULTIPLY_FUNCTION_DEFINITION_CONTEXT,
+        "#[turbo_tasks::function] naked functions"
+    );
+    assert_eq!(
+        COUNTER_IMPL_ADD_FUNCTION_DEFINITION_CONTEXT,
+        "#[turbo_tasks::value_impl] inherent methods"
+    );
+    assert_eq!(
+        COUNTER_IMPL_TRAIT_DESCRIBE_VALUE_FUNCTION_DEFINITION_CONTEXT,
+        "#[turbo_tasks::value_impl] trait methods"
+    );
+    assert_eq!(
+        DESCRIBE_DEFAULT_IMPL_DESCRIBE_FUNCTION_DEFINITION_CONTEXT,
+        "#[turbo_tasks::value_trait] methods"
+    );
+}
@@ -0,0 +1,35 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value_trait]
+trait Greeting {}
+
+#[turbo_tasks::value]
+struct English;
+
+#[turbo_tasks::value_impl]
+impl Greeting for English {}
+
+/// A simplified alias for the trait-object return type, shown in the rustdocs.
+type DynGreeting = Vc<Box<dyn Greeting>>;
+
+#[turbo_tasks::function(doc_return = DynGreeting)]
+fn greeting() -> Vc<Box<dyn Greeting>> {
+    Vc::upcast(English.cell())
+}
+
+#[turbo_tasks::value_impl]
+impl English {
+    #[turbo_tasks::function(doc_return = DynGreeting)]
+    fn as_greeting(self: Vc<Self>) -> Vc<Box<dyn Greeting>> {
+        Vc::upcast(self)
+    }
+}
+
+fn main() {
+    // outside of rustdoc, the exposed functions keep their real return types
+    let _: fn() -> Vc<Box<dyn Greeting>> = greeting;
+    let _: fn(Vc<English>) -> Vc<Box<dyn Greeting>> = English::as_greeting;
+}
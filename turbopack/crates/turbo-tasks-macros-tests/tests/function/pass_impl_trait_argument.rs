@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::{RcStr, Vc};
+
+#[turbo_tasks::function]
+fn total_len(prefix: impl Into<RcStr>, values: impl AsRef<[u32]>) -> Vc<usize> {
+    let prefix: RcStr = prefix.into();
+    let values: &[u32] = values.as_ref();
+    Vc::cell(prefix.len() + values.len())
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct Counter(u32);
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    #[turbo_tasks::function(local_cells)]
+    fn local(&self) -> Vc<u32> {
+        Vc::cell(self.0)
+    }
+
+    #[turbo_tasks::function]
+    fn zero() -> Vc<Counter> {
+        Counter(0).cell()
+    }
+}
+
+#[turbo_tasks::function]
+fn double(value: u32) -> Vc<u32> {
+    Vc::cell(value * 2)
+}
+
+const _: () = assert!(COUNTER_IMPL_LOCAL_FUNCTION_LOCAL_CELLS);
+const _: () = assert!(COUNTER_IMPL_LOCAL_FUNCTION_IS_METHOD);
+// an associated function without a receiver isn't a method
+const _: () = assert!(!COUNTER_IMPL_ZERO_FUNCTION_IS_METHOD);
+const _: () = assert!(!DOUBLE_FUNCTION_LOCAL_CELLS);
+const _: () = assert!(!DOUBLE_FUNCTION_IS_METHOD);
+
+const _: () = assert!(COUNTER_IMPL_LOCAL_FUNCTION_HAS_RECEIVER);
+const _: () = assert!(!COUNTER_IMPL_ZERO_FUNCTION_HAS_RECEIVER);
+const _: () = assert!(!DOUBLE_FUNCTION_HAS_RECEIVER);
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::{RcStr, Vc, VcValueType};
+
+#[turbo_tasks::function(monomorphize = [u32, RcStr])]
+fn identity<T: VcValueType>(value: Vc<T>) -> Vc<T> {
+    value
+}
+
+fn main() {
+    let _: fn(Vc<u32>) -> Vc<u32> = identity::<u32>;
+    let _: fn(Vc<RcStr>) -> Vc<RcStr> = identity::<RcStr>;
+}
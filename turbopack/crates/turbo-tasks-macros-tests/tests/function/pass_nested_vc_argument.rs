@@ -0,0 +1,22 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use anyhow::Result;
+use turbo_tasks::{ResolvedVc, Vc};
+
+#[turbo_tasks::function]
+async fn count(
+    values: Vc<Vec<Vc<u32>>>,
+    extra: Option<ResolvedVc<Vec<Vc<u32>>>>,
+) -> Result<Vc<u32>> {
+    let mut count = values.await?.len();
+    if let Some(extra) = extra {
+        count += extra.await?.len();
+    }
+    Ok(Vc::cell(count as u32))
+}
+
+fn main() {
+    // only the `ResolvedVc` is rewritten, the contents of a `Vc` are left untouched
+    let _: fn(Vc<Vec<Vc<u32>>>, Option<Vc<Vec<Vc<u32>>>>) -> Vc<u32> = count;
+}
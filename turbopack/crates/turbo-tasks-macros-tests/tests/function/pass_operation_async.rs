@@ -0,0 +1,14 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function(operation)]
+async fn async_operation() -> Result<Vc<u32>> {
+    Ok(Vc::cell(42))
+}
+
+fn main() {
+    let _: turbo_tasks::OperationVc<u32> = async_operation();
+}
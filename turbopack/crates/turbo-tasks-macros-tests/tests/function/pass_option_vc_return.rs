@@ -0,0 +1,26 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function]
+fn maybe_value(present: bool) -> Option<Vc<u32>> {
+    present.then(|| Vc::cell(42))
+}
+
+#[turbo_tasks::function]
+async fn maybe_value_result(present: bool) -> anyhow::Result<Option<Vc<u32>>> {
+    Ok(present.then(|| Vc::cell(42)))
+}
+
+#[turbo_tasks::function]
+fn maybe_value_qualified(present: bool) -> anyhow::Result<Option<turbo_tasks::Vc<u32>>> {
+    Ok(present.then(|| Vc::cell(42)))
+}
+
+fn main() {
+    // the `Result` is unwrapped first, then the optional `Vc` is returned in a `Vc`
+    let _: fn(bool) -> Vc<Option<Vc<u32>>> = maybe_value;
+    let _: fn(bool) -> Vc<Option<Vc<u32>>> = maybe_value_result;
+    let _: fn(bool) -> Vc<Option<Vc<u32>>> = maybe_value_qualified;
+}
@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use turbo_tasks::{Value, Vc};
+
+#[turbo_tasks::function(portable_inputs)]
+fn path_argument(path: Value<PathBuf>) -> Vc<u32> {
+    Vc::cell(path.into_value().components().count() as u32)
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use turbo_tasks::{Value, Vc};
+
+#[turbo_tasks::function(portable_inputs)]
+fn boxed_path_argument(path: Value<Box<Path>>) -> Vc<u32> {
+    Vc::cell(path.into_value().components().count() as u32)
+}
+
+#[turbo_tasks::function(portable_inputs, fs)]
+fn boxed_path_argument_fs(path: Value<Box<Path>>) -> Vc<bool> {
+    Vc::cell(path.into_value().exists())
+}
+
+fn main() {}
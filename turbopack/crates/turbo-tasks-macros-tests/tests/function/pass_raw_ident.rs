@@ -0,0 +1,28 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::function]
+fn r#type(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[turbo_tasks::value]
+struct Keyword(u32);
+
+#[turbo_tasks::value_impl]
+impl Keyword {
+    #[turbo_tasks::function]
+    fn r#match(&self) -> Vc<u32> {
+        Vc::cell(self.0)
+    }
+}
+
+// the `r#` is stripped from the generated idents
+const _: () = assert!(!TYPE_FUNCTION_IS_METHOD);
+const _: () = assert!(KEYWORD_IMPL_MATCH_FUNCTION_IS_METHOD);
+
+fn main() {
+    let _: fn(u32) -> Vc<u32> = r#type;
+}
@@ -0,0 +1,17 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct Foo(u32);
+
+#[turbo_tasks::function]
+async fn make_foo(value: u32) -> Result<Vc<Foo>> {
+    Ok(Foo(value).cell())
+}
+
+fn main() {
+    assert_eq!(MAKE_FOO_FUNCTION_RETURN_TYPE, "Vc<Foo>");
+}
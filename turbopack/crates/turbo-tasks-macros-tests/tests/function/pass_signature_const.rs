@@ -0,0 +1,33 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use anyhow::Result;
+use turbo_tasks::{RcStr, Vc};
+
+#[turbo_tasks::function]
+async fn my_task(value: u32, name: RcStr) -> Result<Vc<u32>> {
+    Ok(Vc::cell(value + name.len() as u32))
+}
+
+#[turbo_tasks::value]
+struct Counter(u32);
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    #[turbo_tasks::function]
+    fn add(self: Vc<Self>, amount: u32) -> Vc<u32> {
+        let _ = self;
+        Vc::cell(amount)
+    }
+}
+
+fn main() {
+    assert_eq!(
+        MY_TASK_FUNCTION_SIGNATURE,
+        "fn my_task(value: u32, name: RcStr) -> Vc<u32>"
+    );
+    assert_eq!(
+        COUNTER_IMPL_ADD_FUNCTION_SIGNATURE,
+        "fn add(self: Vc<Self>, amount: u32) -> Vc<u32>"
+    );
+}
@@ -0,0 +1,31 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value]
+struct Counter(u32);
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    #[turbo_tasks::function]
+    fn get(&self) -> Vc<u32>
+    where
+        Self: Sized,
+    {
+        Vc::cell(self.0)
+    }
+}
+
+#[turbo_tasks::function]
+fn double(value: u32) -> Vc<u32>
+where
+    u32: Copy,
+{
+    Vc::cell(value * 2)
+}
+
+fn main() {
+    let _: fn(Vc<Counter>) -> Vc<u32> = Counter::get;
+    let _: fn(u32) -> Vc<u32> = double;
+}
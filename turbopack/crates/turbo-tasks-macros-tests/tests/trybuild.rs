@@ -5,6 +5,13 @@ fn derive_resolved_value() {
     t.compile_fail("tests/derive_resolved_value/fail_*.rs");
 }
 
+#[test]
+fn derive_operation_value() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/derive_operation_value/pass_*.rs");
+    t.compile_fail("tests/derive_operation_value/fail_*.rs");
+}
+
 #[test]
 fn function() {
     let t = trybuild::TestCases::new();
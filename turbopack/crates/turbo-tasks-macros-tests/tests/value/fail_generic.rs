@@ -0,0 +1,8 @@
+#![feature(arbitrary_self_types)]
+
+#[turbo_tasks::value]
+struct MyValue<T> {
+    value: T,
+}
+
+fn main() {}
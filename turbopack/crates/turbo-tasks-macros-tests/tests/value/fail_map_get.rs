@@ -0,0 +1,6 @@
+#![feature(arbitrary_self_types)]
+
+#[turbo_tasks::value(transparent, map_get)]
+struct MyValue(Vec<u32>);
+
+fn main() {}
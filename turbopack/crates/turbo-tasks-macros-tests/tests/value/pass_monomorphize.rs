@@ -0,0 +1,17 @@
+#![feature(arbitrary_self_types)]
+
+use turbo_tasks::{RcStr, Vc};
+
+#[turbo_tasks::value(monomorphize = [u32, RcStr])]
+struct MyValue<T> {
+    value: T,
+}
+
+fn expects_vc_value_type<T: turbo_tasks::VcValueType>(_vc: Vc<T>) {}
+
+fn main() {
+    let _ = |number: Vc<MyValue<u32>>, text: Vc<MyValue<RcStr>>| {
+        expects_vc_value_type(number);
+        expects_vc_value_type(text);
+    };
+}
@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value_trait]
+trait Duplicate {
+    fn duplicate(self: Vc<Self>) -> Vc<Self> {
+        self
+    }
+}
+
+fn main() {}
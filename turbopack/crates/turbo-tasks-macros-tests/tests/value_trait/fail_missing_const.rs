@@ -0,0 +1,24 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value_trait]
+trait Shape {
+    const SIDES: u32;
+
+    fn sides(self: Vc<Self>) -> Vc<u32>;
+}
+
+#[turbo_tasks::value]
+struct Circle;
+
+#[turbo_tasks::value_impl]
+impl Shape for Circle {
+    #[turbo_tasks::function]
+    fn sides(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(0)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value(transparent)]
+struct IntegersVec(Vec<Vc<u32>>);
+
+#[turbo_tasks::value_trait(resolved)]
+trait ExampleTrait {
+    fn return_contains_unresolved_vc(self: Vc<Self>) -> Vc<IntegersVec>;
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![feature(arbitrary_self_types)]
+#![allow(dead_code)]
+
+use turbo_tasks::Vc;
+
+#[turbo_tasks::value_trait]
+trait NoDefault {
+    fn create() -> Vc<u32>;
+}
+
+fn main() {
+    let _ = <Box<dyn NoDefault>>::create();
+}
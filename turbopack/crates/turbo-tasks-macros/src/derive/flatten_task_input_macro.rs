@@ -0,0 +1,54 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DataStruct, DeriveInput, Fields};
+use turbo_tasks_macros_shared::{generate_exhaustive_destructuring, SpanDiagnosticExt};
+
+pub fn derive_flatten_task_input(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    let ident = &derive_input.ident;
+
+    let Data::Struct(DataStruct { fields, .. }) = &derive_input.data else {
+        derive_input
+            .span()
+            .error("the FlattenTaskInput derive macro only supports structs")
+            .emit();
+        return quote! {}.into();
+    };
+
+    if !derive_input.generics.params.is_empty() {
+        // NOTE: the fields would need bounds on the generic parameters, which would have to be
+        // inferred or configured.
+        derive_input
+            .generics
+            .span()
+            .error("the FlattenTaskInput derive macro does not support generic parameters yet")
+            .emit();
+        return quote! {}.into();
+    }
+
+    let field_types = fields.iter().map(|field| &field.ty);
+    let (capture, field_idents) = generate_exhaustive_destructuring(fields.iter());
+    let construct = match fields {
+        Fields::Named(_) => quote! { #ident #capture },
+        Fields::Unnamed(_) => quote! { #ident(#(#field_idents),*) },
+        Fields::Unit => quote! { #ident },
+    };
+
+    quote! {
+        impl turbo_tasks::task::FlattenTaskInput for #ident {
+            type Fields = (#(#field_types,)*);
+
+            #[allow(non_snake_case)]
+            fn into_fields(self) -> Self::Fields {
+                let #ident #capture = self;
+                (#(#field_idents,)*)
+            }
+
+            #[allow(non_snake_case)]
+            fn from_fields((#(#field_idents,)*): Self::Fields) -> Self {
+                #construct
+            }
+        }
+    }
+    .into()
+}
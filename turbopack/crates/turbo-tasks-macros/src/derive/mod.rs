@@ -1,4 +1,6 @@
 mod deterministic_hash_macro;
+mod flatten_task_input_macro;
+mod operation_value_macro;
 mod resolved_value_macro;
 mod task_input_macro;
 mod trace_raw_vcs_macro;
@@ -6,10 +8,13 @@ mod value_debug_format_macro;
 mod value_debug_macro;
 
 pub use deterministic_hash_macro::derive_deterministic_hash;
+pub use flatten_task_input_macro::derive_flatten_task_input;
+pub use operation_value_macro::derive_operation_value;
 pub use resolved_value_macro::derive_resolved_value;
 use syn::{spanned::Spanned, Attribute, Meta, MetaList, NestedMeta};
 pub use task_input_macro::derive_task_input;
 pub use trace_raw_vcs_macro::derive_trace_raw_vcs;
+use turbo_tasks_macros_shared::SpanDiagnosticExt;
 pub use value_debug_format_macro::derive_value_debug_format;
 pub use value_debug_macro::derive_value_debug;
 
@@ -36,7 +41,7 @@ impl From<&[Attribute]> for FieldAttributes {
             }
             if let Ok(Meta::List(MetaList { nested, .. })) = attr
                 .parse_meta()
-                .map_err(|err| err.span().unwrap().error(err.to_string()).emit())
+                .map_err(|err| err.span().error(err.to_string()).emit())
             {
                 for meta in nested {
                     if let NestedMeta::Meta(Meta::Path(path)) = &meta {
@@ -45,13 +50,11 @@ impl From<&[Attribute]> for FieldAttributes {
                             Some("debug_ignore") => result.debug_ignore = true,
                             _ => path
                                 .span()
-                                .unwrap()
                                 .error("expected `trace_ignore` or `debug_ignore`")
                                 .emit(),
                         }
                     } else {
                         meta.span()
-                            .unwrap()
                             .error("expected `trace_ignore` or `debug_ignore`")
                             .emit();
                     }
@@ -0,0 +1,49 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Generics};
+
+use super::resolved_value_macro::iter_data_fields;
+
+pub fn derive_operation_value(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    let ident = &derive_input.ident;
+
+    let assertions = assert_fields_impl_operation_value(&derive_input.generics, &derive_input.data);
+
+    let (impl_generics, ty_generics, where_clause) = derive_input.generics.split_for_impl();
+    quote! {
+        unsafe impl #impl_generics turbo_tasks::OperationValue
+            for #ident #ty_generics #where_clause {}
+        #assertions
+    }
+    .into()
+}
+
+fn assert_fields_impl_operation_value(generics: &Generics, data: &Data) -> TokenStream2 {
+    // see `assert_fields_impl_resolved_value`
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let field_types: Vec<_> = iter_data_fields(data).map(|field| &field.ty).collect();
+    let assertion_calls = field_types.iter().map(|ty| {
+        quote_spanned! {
+            // attribute type assertion errors to the line where the field is defined
+            ty.span() =>
+            // this call is only valid if ty is an OperationValue
+            Self::assert_impl_operation_value::<#ty>();
+        }
+    });
+    quote! {
+        const _: fn() = || {
+            struct DeriveOperationValueAssertion #impl_generics (#(#field_types),*) #where_clause;
+
+            impl #impl_generics DeriveOperationValueAssertion #ty_generics #where_clause {
+                fn assert_impl_operation_value<
+                    ExpectedOperationValue: turbo_tasks::OperationValue + ?Sized
+                >() {}
+                fn field_types() {
+                    #(#assertion_calls)*
+                }
+            }
+        };
+    }
+}
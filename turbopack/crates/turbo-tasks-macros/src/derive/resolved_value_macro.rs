@@ -19,7 +19,7 @@ pub fn derive_resolved_value(input: TokenStream) -> TokenStream {
     .into()
 }
 
-fn iter_data_fields(data: &Data) -> impl Iterator<Item = &syn::Field> {
+pub(super) fn iter_data_fields(data: &Data) -> impl Iterator<Item = &syn::Field> {
     match data {
         Data::Struct(ds) => Either::Left(ds.fields.iter()),
         Data::Enum(de) => Either::Right(Either::Left(de.variants.iter().flat_map(|v| &v.fields))),
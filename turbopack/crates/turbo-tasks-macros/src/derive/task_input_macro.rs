@@ -1,53 +1,60 @@
 use proc_macro::TokenStream;
+use proc_macro2::Span;
 use quote::quote;
-use syn::{parse_macro_input, spanned::Spanned, DeriveInput};
-use turbo_tasks_macros_shared::{generate_exhaustive_destructuring, match_expansion};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, visit::Visit, Data, DataEnum, DataStruct,
+    DeriveInput, GenericArgument, PathArguments, Type, TypePath,
+};
+use turbo_tasks_macros_shared::{
+    generate_exhaustive_destructuring, match_expansion, SpanDiagnosticExt,
+};
 
 pub fn derive_task_input(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     let ident = &derive_input.ident;
-    let generics = &derive_input.generics;
+    let mut generics = derive_input.generics.clone();
 
-    if let Some(where_clause) = &generics.where_clause {
-        // NOTE(alexkirsz) We could support where clauses and generic parameters bounds
-        // in the future, but for simplicity's sake, we don't support them yet.
-        where_clause
-            .span()
-            .unwrap()
-            .error("the TaskInput derive macro does not support where clauses yet")
-            .emit();
-    }
-
-    for param in &generics.params {
+    for param in &derive_input.generics.params {
         match param {
             syn::GenericParam::Type(param) => {
-                if !param.bounds.is_empty() {
-                    // NOTE(alexkirsz) See where clause above.
-                    param
-                        .span()
-                        .unwrap()
-                        .error(
-                            "the TaskInput derive macro does not support generic parameters \
-                             bounds yet",
-                        )
-                        .emit();
-                }
+                let param_ident = &param.ident;
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote! { #param_ident: turbo_tasks::TaskInput });
             }
             syn::GenericParam::Lifetime(param) => {
                 param
                     .span()
-                    .unwrap()
                     .error("the TaskInput derive macro does not support generic lifetimes")
                     .emit();
             }
-            syn::GenericParam::Const(param) => {
-                // NOTE(alexkirsz) Ditto: not supported yet for simplicity's sake.
-                param
-                    .span()
-                    .unwrap()
-                    .error("the TaskInput derive macro does not support const generics yet")
-                    .emit();
-            }
+            syn::GenericParam::Const(_) => {}
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let fields: Vec<_> = match &derive_input.data {
+        Data::Struct(DataStruct { fields, .. }) => fields.iter().collect(),
+        Data::Enum(DataEnum { variants, .. }) => variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter())
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+    for field in fields {
+        let mut visitor = BoxedTraitObjectVisitor::default();
+        visitor.visit_type(&field.ty);
+        for span in visitor.spans {
+            span.error(
+                "the TaskInput derive macro does not support `Box<dyn Trait>`, because trait \
+                 objects can't be cloned, compared or deserialized",
+            )
+            .help(
+                "use a `ResolvedVc<Box<dyn Trait>>` of a #[turbo_tasks::value_trait] instead, \
+                 which refers to the value through its registered value type",
+            )
+            .emit();
         }
     }
 
@@ -136,24 +143,9 @@ pub fn derive_task_input(input: TokenStream) -> TokenStream {
         &|ident| quote! {Ok(#ident)},
     );
 
-    let generic_params: Vec<_> = generics
-        .params
-        .iter()
-        .filter_map(|param| match param {
-            syn::GenericParam::Type(param) => Some(param),
-            _ => {
-                // We already report an error for this above.
-                None
-            }
-        })
-        .collect();
-
     quote! {
         #[turbo_tasks::macro_helpers::async_trait]
-        impl #generics turbo_tasks::TaskInput for #ident #generics
-        where
-            #(#generic_params: turbo_tasks::TaskInput,)*
-        {
+        impl #impl_generics turbo_tasks::TaskInput for #ident #ty_generics #where_clause {
             #[allow(non_snake_case)]
             #[allow(unreachable_code)] // This can occur for enums with no variants.
             fn is_resolved(&self) -> bool {
@@ -181,6 +173,45 @@ pub fn derive_task_input(input: TokenStream) -> TokenStream {
                 }
             }
         }
+
+        // Derived inputs are passed to `#[turbo_tasks::function]`s unchanged. This allows them to
+        // be used inside of containers (e.g. tuples) that also contain `ResolvedVc`s.
+        impl #impl_generics turbo_tasks::task::FromTaskInput for #ident #ty_generics #where_clause {
+            type TaskInput = Self;
+
+            fn from_task_input(from: Self) -> Self {
+                from
+            }
+        }
     }
     .into()
 }
+
+/// Collects the spans of `Box<dyn Trait>` types, which can't implement `TaskInput`. Trait objects
+/// behind a `Vc` (e.g. `ResolvedVc<Box<dyn Trait>>`) are fine.
+#[derive(Default)]
+struct BoxedTraitObjectVisitor {
+    spans: Vec<Span>,
+}
+
+impl<'ast> Visit<'ast> for BoxedTraitObjectVisitor {
+    fn visit_type_path(&mut self, node: &'ast TypePath) {
+        if let Some(segment) = node.path.segments.last() {
+            if segment.ident == "Vc"
+                || segment.ident == "ResolvedVc"
+                || segment.ident == "OperationVc"
+            {
+                return;
+            }
+            if segment.ident == "Box" {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(GenericArgument::Type(Type::TraitObject(_))) = args.args.first() {
+                        self.spans.push(node.span());
+                    }
+                }
+            }
+        }
+
+        syn::visit::visit_type_path(self, node);
+    }
+}
@@ -1,37 +1,176 @@
-use std::collections::HashSet;
+use std::{borrow::Cow, collections::HashSet};
 
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{quote, quote_spanned, ToTokens};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
+    ext::IdentExt,
     parenthesized,
     parse::{Parse, ParseStream},
     parse_quote,
     punctuated::{Pair, Punctuated},
     spanned::Spanned,
     token::Paren,
-    AngleBracketedGenericArguments, Block, Expr, ExprPath, FnArg, GenericArgument, Meta, Pat,
-    PatIdent, PatType, Path, PathArguments, PathSegment, Receiver, ReturnType, Signature, Token,
-    Type, TypeGroup, TypePath, TypeTuple,
+    AngleBracketedGenericArguments, Attribute, Binding, Block, Expr, ExprArray, ExprLit, ExprPath,
+    FnArg, GenericArgument, Item, Lit, LitInt, LitStr, Meta, MetaNameValue, Pat, PatBox, PatIdent,
+    PatReference, PatStruct, PatTuple, PatTupleStruct, PatType, Path, PathArguments, PathSegment,
+    Receiver, ReturnType, Signature, Token, TraitBound, TraitBoundModifier, Type, TypeArray,
+    TypeGroup, TypeImplTrait, TypeParam, TypeParamBound, TypeParen, TypePath, TypeReference,
+    TypeSlice, TypeTuple, VisRestricted, Visibility, WherePredicate,
+};
+use turbo_tasks_macros_shared::{
+    get_function_call_count_ident, get_function_last_duration_ident, get_native_function_ident,
+    SpanDiagnosticExt,
 };
 
 #[derive(Debug)]
 pub struct TurboFn {
-    // signature: Signature,
-    // block: Block,
+    orig_signature: Signature,
     ident: Ident,
     output: Type,
     this: Option<Input>,
     inputs: Vec<Input>,
     /// Should we check that the return type contains a `ResolvedValue`?
     resolved: Option<Span>,
+    /// The span of the receiver of a method in an inherent impl. `Self` must be a value type for
+    /// the call to be dispatched, which is checked at this span.
+    value_receiver: Option<Span>,
     /// Should this function use `TaskPersistence::LocalCells`?
     local_cells: bool,
+    /// A function that derives the name of the call's span from its inputs.
+    name_fn: Option<ExprPath>,
+    /// The name of the native function in traces, e.g. `"resolve/esm"`, instead of the name
+    /// derived from the function's path.
+    name: Option<LitStr>,
+    /// The level of the call's span.
+    trace_level: Option<TraceLevel>,
+    /// A custom [`std::hash::Hasher`] used to hash the inputs for the task cache key.
+    hasher: Option<Type>,
+    /// Should the exposed function be marked as `#[deprecated]`, optionally with a note?
+    deprecated: Option<Option<LitStr>>,
+    /// A function with the same signature that an elided (empty) body forwards to.
+    replacement: Option<ExprPath>,
+    /// A return type shown in rustdoc in place of the exposed function's real return type.
+    doc_return: Option<Type>,
+    /// Should arguments be converted with `FromTaskInput` directly, instead of through the
+    /// `AutoFromTaskInput` specialization?
+    direct_from_task_input: bool,
+    /// Should `Vc` inputs be resolved concurrently?
+    parallel_inputs: bool,
+    /// Should input resolution stop when the task is cancelled?
+    cancellable: bool,
+    /// Should input resolution and the body be timed and reported to the profile hook?
+    profile: bool,
+    /// Should the body be re-run when it returns an error?
+    retry: Option<RetryArguments>,
+    /// Should we assert that the future of the async body is `Send`? Errors are reported to this
+    /// span.
+    assert_send: Option<Span>,
+    /// A function whose tasks should preferably be scheduled before this function's tasks.
+    schedule_after: Option<ExprPath>,
+    /// The generic type parameter of the function and the types it's instantiated with.
+    monomorphize: Option<Monomorphize>,
+    /// A threshold in microseconds. A warning is logged when the body takes longer than this.
+    warn_after: Option<u64>,
+    /// A function registering the tested functions, if a smoke test calling the function with
+    /// default inputs should be generated. Errors about the inputs not implementing `Default` are
+    /// reported to their types.
+    gen_smoke_test: Option<ExprPath>,
+    /// Should the calls of the exposed function be counted?
+    count_calls: bool,
+    /// Should an `<name>_invalidator()` function be generated?
+    manual_invalidation: bool,
+    /// Should an `<name>_untracked()` function be generated?
+    also_untracked: bool,
+    /// Should the duration of the last execution be recorded?
+    track_duration: bool,
+    /// The visibility of the generated statics and metadata constants, `pub(crate)` if unset.
+    static_vis: Option<Visibility>,
+    /// The module path recorded for the function, `module_path!()` if unset.
+    module_path: Option<LitStr>,
+    /// A function producing the result when the body returns an error.
+    fallback: Option<ExprPath>,
+    /// The version of the function's logic, bumped to invalidate persisted results.
+    version: u32,
+    /// Is the function a pure reader that doesn't create cells?
+    readonly: bool,
+    /// The name of a concurrency group shared with other functions.
+    concurrency_group: Option<LitStr>,
+    /// Should the future of the async body be constructed (and boxed) only when it's first polled?
+    lazy_body: bool,
+    /// Should the exposed function be async and resolve the returned `Vc`?
+    resolve_return: bool,
+    /// Should every call run the body again? The exposed function calls the inline function
+    /// directly then, without creating a task.
+    no_cache: bool,
+    /// Should the function's tasks start executing when they're created?
+    eager: bool,
+    /// The scheduling priority of the function's tasks.
+    priority: TaskPriority,
+    /// Is the function an operation? The exposed function returns an `OperationVc` then, and all
+    /// inputs must be `OperationValue`s.
+    operation: bool,
+    /// Warnings about filesystem path arguments, reported by [`TurboFn::emit_warnings`].
+    path_argument_warnings: Vec<PathArgumentWarning>,
+    /// Does the function directly access the filesystem (`fs`)?
+    io_fs: bool,
+    /// Does the function directly access the network (`network`)?
+    io_network: bool,
+    /// The context in which the function is defined.
+    definition_context: DefinitionContext,
+}
+
+/// The generic type parameter of a `monomorphize`d function and the types it's instantiated with.
+/// Every instantiation is registered as a separate native function.
+#[derive(Debug)]
+pub struct Monomorphize {
+    pub param: TypeParam,
+    pub types: Vec<Type>,
 }
 
 #[derive(Debug)]
 pub struct Input {
     pub ident: Ident,
     pub ty: Type,
+    /// Is the argument marked `#[turbo_tasks(flatten)]`? Its fields are then passed as separate
+    /// task inputs, see `turbo_tasks::task::FlattenTaskInput`.
+    pub flatten: bool,
+    /// Is the argument marked `#[turbo_tasks(map_value)]`? It's then a map-like type (e.g.
+    /// `IndexMap<K, V>`) that's stored as a `Vec` of its entries sorted by key, with the value
+    /// type expanded like any other argument type.
+    pub map_value: bool,
+    /// Is the argument marked `#[turbo_tasks(span)]`? It's then recorded as a field of the
+    /// dispatch span, at the given level (e.g. `#[turbo_tasks(span = "debug")]`) or at the level
+    /// of the span.
+    pub span: Option<Option<TraceLevel>>,
+    /// The expression of `#[turbo_tasks(trace_as = expr)]`, which is recorded as the argument's
+    /// span field instead of the argument itself. The argument is in scope as a reference.
+    pub trace_as: Option<Expr>,
+    /// Is the argument marked `#[turbo_tasks(untracked)]`? It's then stored as an
+    /// `UntrackedTaskInput`, which isn't part of the task's cache key.
+    pub untracked: bool,
+    /// Is the argument marked `#[turbo_tasks(debug_skip)]`? It's then stored as a
+    /// `DebugSkippedTaskInput`, which is left out of the `Debug` output of the task's inputs.
+    pub debug_skip: bool,
+    /// The projection of `#[turbo_tasks(key(...))]`. The argument is then stored as a
+    /// `KeyedTaskInput`, of which only the projection is part of the task's cache key.
+    pub key: Option<Expr>,
+}
+
+impl Input {
+    /// Does the argument's task input wrap its value, see `TurboFn::task_input_type`?
+    fn is_wrapped(&self) -> bool {
+        self.untracked || self.debug_skip || self.key.is_some()
+    }
+
+    /// The `.0` fields that unwrap the task input of a `#[turbo_tasks(untracked)]`,
+    /// `#[turbo_tasks(debug_skip)]` or `#[turbo_tasks(key(...))]` argument, see
+    /// `TurboFn::task_input_type`.
+    fn unwrap_fields(&self) -> impl Iterator<Item = syn::Index> + Clone {
+        let count = usize::from(self.untracked)
+            + usize::from(self.debug_skip)
+            + usize::from(self.key.is_some());
+        (0..count).map(|_| syn::Index::from(0))
+    }
 }
 
 impl TurboFn {
@@ -40,33 +179,329 @@ impl TurboFn {
         definition_context: DefinitionContext,
         args: FunctionArguments,
     ) -> Option<TurboFn> {
-        if !original_signature.generics.params.is_empty() {
+        let monomorphize = match (&args.monomorphize, &definition_context) {
+            (Some((span, _)), DefinitionContext::NakedFn)
+                if args.parallel_inputs.is_some()
+                    || args.profile.is_some()
+                    || args.cancellable.is_some() =>
+            {
+                span.error(
+                    "\"monomorphize\" cannot be used together with \"parallel_inputs\", \
+                     \"profile\" or \"cancellable\"",
+                )
+                .emit();
+                return None;
+            }
+            (Some((span, types)), DefinitionContext::NakedFn) => {
+                let mut type_params = original_signature.generics.type_params();
+                let (Some(param), None, 1) = (
+                    type_params.next(),
+                    type_params.next(),
+                    original_signature.generics.params.len(),
+                ) else {
+                    span.error(
+                        "\"monomorphize\" requires the function to have exactly one generic \
+                         parameter, which must be a type parameter",
+                    )
+                    .emit();
+                    return None;
+                };
+                Some(Monomorphize {
+                    param: param.clone(),
+                    types: types.clone(),
+                })
+            }
+            (Some((span, _)), _) => {
+                span.error(format!(
+                    "{} do not support \"monomorphize\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => None,
+        };
+
+        let gen_smoke_test = match (args.gen_smoke_test, &definition_context) {
+            (Some((span, _)), DefinitionContext::NakedFn) if monomorphize.is_some() => {
+                span.error("\"gen_smoke_test\" cannot be used together with \"monomorphize\"")
+                    .emit();
+                return None;
+            }
+            // With `no_cache`, the exposed function returns a future instead of a `Vc`, and with
+            // `args_struct` it accepts the packed struct instead of the `Default` arguments
+            (Some((span, _)), DefinitionContext::NakedFn)
+                if args.no_cache.is_some() || args.args_struct.is_some() =>
+            {
+                span.error(
+                    "\"gen_smoke_test\" cannot be used together with \"no_cache\" or \
+                     \"args_struct\"",
+                )
+                .emit();
+                return None;
+            }
+            (Some((_, register)), DefinitionContext::NakedFn) => Some(register),
+            (Some((span, _)), _) => {
+                span.error(format!(
+                    "{} do not support \"gen_smoke_test\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => None,
+        };
+
+        let count_calls = match (args.count_calls, &definition_context) {
+            (Some(_), DefinitionContext::NakedFn) => true,
+            (Some(span), _) => {
+                span.error(format!(
+                    "{} do not support \"count_calls\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => false,
+        };
+
+        let manual_invalidation = match (args.manual_invalidation, &definition_context) {
+            (Some(span), DefinitionContext::NakedFn)
+                if monomorphize.is_some() || args.no_cache.is_some() =>
+            {
+                span.error(
+                    "\"invalidation = manual\" cannot be used together with \"monomorphize\" or \
+                     \"no_cache\"",
+                )
+                .emit();
+                return None;
+            }
+            (Some(_), DefinitionContext::NakedFn) => true,
+            (Some(span), _) => {
+                span.error(format!(
+                    "{} do not support \"invalidation = manual\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => false,
+        };
+
+        let also_untracked = match (args.also_untracked, &definition_context) {
+            (Some(span), DefinitionContext::NakedFn)
+                if monomorphize.is_some() || args.no_cache.is_some() =>
+            {
+                span.error(
+                    "\"also_untracked\" cannot be used together with \"monomorphize\" or \
+                     \"no_cache\"",
+                )
+                .emit();
+                return None;
+            }
+            (Some(_), DefinitionContext::NakedFn) => true,
+            (Some(span), _) => {
+                span.error(format!(
+                    "{} do not support \"also_untracked\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => false,
+        };
+
+        // The arguments of a naked function are packed into a struct by `function_macro` before
+        // the signature gets here
+        if let (Some(span), false) = (
+            args.args_struct,
+            definition_context == DefinitionContext::NakedFn,
+        ) {
+            span.error(format!(
+                "{} do not support \"args_struct\"",
+                definition_context.function_type(),
+            ))
+            .emit();
+            return None;
+        }
+
+        let track_duration = match (args.track_duration, &definition_context) {
+            (Some(_), DefinitionContext::NakedFn) => true,
+            (Some(span), _) => {
+                span.error(format!(
+                    "{} do not support \"track_duration\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => false,
+        };
+
+        let static_vis = match (args.static_vis, &definition_context) {
+            (Some((_, vis)), DefinitionContext::NakedFn) => Some(vis),
+            (Some((span, _)), _) => {
+                span.error(format!(
+                    "{} do not support \"static_vis\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => None,
+        };
+
+        let module_path = match (args.module_path, &definition_context) {
+            (Some(path), DefinitionContext::NakedFn) => Some(path),
+            (Some(path), _) => {
+                path.span()
+                    .error(format!(
+                        "{} do not support \"module_path\"",
+                        definition_context.function_type(),
+                    ))
+                    .emit();
+                return None;
+            }
+            (None, _) => None,
+        };
+
+        let resolve_return = match (args.resolve_return, &definition_context) {
+            (Some(_), DefinitionContext::NakedFn | DefinitionContext::ValueInherentImpl) => true,
+            (Some(span), _) => {
+                span.error(format!(
+                    "{} do not support \"resolve_return\", because the exposed function must \
+                     match the trait's signature and can't be async",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => false,
+        };
+
+        let no_cache = match (args.no_cache, &definition_context) {
+            (Some(span), DefinitionContext::NakedFn)
+                if monomorphize.is_some() || args.operation.is_some() =>
+            {
+                span.error(
+                    "\"no_cache\" cannot be used together with \"monomorphize\" or \"operation\"",
+                )
+                .emit();
+                return None;
+            }
+            (Some(_), DefinitionContext::NakedFn) => true,
+            (Some(span), _) => {
+                span.error(format!(
+                    "{} do not support \"no_cache\"",
+                    definition_context.function_type(),
+                ))
+                .emit();
+                return None;
+            }
+            (None, _) => false,
+        };
+
+        if monomorphize.is_none() && !original_signature.generics.params.is_empty() {
+            let diagnostic = original_signature.generics.span().error(format!(
+                "{} do not support generic parameters",
+                definition_context.function_type(),
+            ));
+            match definition_context {
+                DefinitionContext::NakedFn => diagnostic
+                    .help(
+                        "list the types to register the function for, e.g. \
+                         #[turbo_tasks::function(monomorphize = [Foo, Bar])]",
+                    )
+                    .emit(),
+                _ => diagnostic.emit(),
+            }
+            return None;
+        }
+
+        if let (Some(_), DefinitionContext::ValueTraitImpl { .. }) =
+            (&args.deprecated, &definition_context)
+        {
             original_signature
-                .generics
                 .span()
-                .unwrap()
                 .error(format!(
-                    "{} do not support generic parameters",
+                    "{} cannot be deprecated, deprecate the trait method instead",
                     definition_context.function_type(),
                 ))
                 .emit();
             return None;
         }
 
-        if original_signature.generics.where_clause.is_some() {
-            original_signature
-                .generics
-                .where_clause
+        if let (Some(doc_return), DefinitionContext::ValueTraitImpl { .. }) =
+            (&args.doc_return, &definition_context)
+        {
+            doc_return
                 .span()
-                .unwrap()
                 .error(format!(
-                    "{} do not support where clauses",
+                    "{} must use the return type of the trait method, \"doc_return\" is not \
+                     supported",
+                    definition_context.function_type(),
+                ))
+                .emit();
+            return None;
+        }
+
+        let operation = match (args.operation, &definition_context) {
+            (Some(_), DefinitionContext::NakedFn | DefinitionContext::ValueInherentImpl) => {
+                if let Some(receiver) = original_signature.receiver() {
+                    // `self` is a `Vc`, which isn't an `OperationValue`
+                    receiver
+                        .span()
+                        .error(
+                            "\"operation\" functions cannot take `self`, because a `Vc` can be \
+                             read without depending on the operation that created it",
+                        )
+                        .help("remove \"operation\" or the `self` argument")
+                        .emit();
+                    return None;
+                }
+                true
+            }
+            (Some(span), _) => {
+                span.error(format!(
+                    "{} do not support \"operation\", because the exposed function must return \
+                     the trait method's `Vc`",
                     definition_context.function_type(),
                 ))
                 .emit();
+                return None;
+            }
+            (None, _) => false,
+        };
+
+        let variadic_span = original_signature.variadic.as_ref().map(Spanned::span);
+        let rest_pattern_span = original_signature
+            .inputs
+            .iter()
+            .find_map(|input| match input {
+                FnArg::Typed(typed) => find_rest_pattern(&typed.pat),
+                FnArg::Receiver(_) => None,
+            });
+        if let Some(span) = variadic_span.or(rest_pattern_span) {
+            span.error(format!(
+                "{} have a fixed number of arguments, rest and slice patterns are not supported",
+                definition_context.function_type(),
+            ))
+            .help(
+                "bind each argument to a name with a concrete owned type, e.g. `values: Vec<u32>`",
+            )
+            .emit();
             return None;
         }
 
+        // Arguments bound by a pattern get a generated name, which must not clash with any other
+        // name bound in the signature.
+        let mut bound_idents = HashSet::new();
+        for input in &original_signature.inputs {
+            if let FnArg::Typed(typed) = input {
+                collect_bound_idents(&typed.pat, &mut bound_idents);
+            }
+        }
+
         let mut raw_inputs = original_signature.inputs.iter();
         let mut this = None;
         let mut inputs = Vec::with_capacity(raw_inputs.len());
@@ -84,7 +519,6 @@ impl TurboFn {
                     if !attrs.is_empty() {
                         receiver
                             .span()
-                            .unwrap()
                             .error(format!(
                                 "{} do not support attributes on arguments",
                                 definition_context.function_type(),
@@ -98,14 +532,16 @@ impl TurboFn {
                     // other forms of self.
 
                     let definition_context = match &definition_context {
-                        DefinitionContext::NakedFn { .. } => return None,
+                        DefinitionContext::NakedFn { .. } => {
+                            emit_naked_fn_receiver_error(receiver.span(), &definition_context);
+                            return None;
+                        }
                         _ => &definition_context,
                     };
 
                     if !attrs.is_empty() {
                         receiver
                             .span()
-                            .unwrap()
                             .error(format!(
                                 "{} do not support attributes on self",
                                 definition_context.function_type(),
@@ -117,7 +553,6 @@ impl TurboFn {
                     if mutability.is_some() {
                         receiver
                             .span()
-                            .unwrap()
                             .error(format!(
                                 "{} cannot take self by mutable reference, use &self or self: \
                                  Vc<Self> instead",
@@ -131,7 +566,6 @@ impl TurboFn {
                         None => {
                             receiver
                                 .span()
-                                .unwrap()
                                 .error(format!(
                                     "{} cannot take self by value, use &self or self: Vc<Self> \
                                      instead",
@@ -143,7 +577,6 @@ impl TurboFn {
                         Some((_, Some(lifetime))) => {
                             lifetime
                                 .span()
-                                .unwrap()
                                 .error(format!(
                                     "{} cannot take self by reference with a custom lifetime, use \
                                      &self or self: Vc<Self> instead",
@@ -158,39 +591,53 @@ impl TurboFn {
                     this = Some(Input {
                         ident: Ident::new("self", self_token.span()),
                         ty: parse_quote! { turbo_tasks::Vc<Self> },
+                        flatten: false,
+                        map_value: false,
+                        span: None,
+                        trace_as: None,
+                        untracked: false,
+                        debug_skip: false,
+                        key: None,
                     });
                 }
                 FnArg::Typed(typed) => {
-                    if !typed.attrs.is_empty() {
-                        typed
-                            .span()
-                            .unwrap()
-                            .error(format!(
-                                "{} does not support attributes on arguments",
-                                definition_context.function_type(),
-                            ))
-                            .emit();
-                        return None;
-                    }
+                    let ArgumentAttributes {
+                        flatten,
+                        map_value,
+                        span,
+                        trace_as,
+                        untracked,
+                        debug_skip,
+                        key,
+                    } = ArgumentAttributes::parse(typed, &definition_context)?;
 
                     if let Pat::Ident(ident) = &*typed.pat {
                         if ident.ident == "self" {
                             if let DefinitionContext::NakedFn { .. } = definition_context {
-                                // The function is not associated. The compiler will emit an error
-                                // on its own.
+                                emit_naked_fn_receiver_error(typed.span(), &definition_context);
                                 return None;
                             };
 
                             // We don't validate that the user provided a valid
-                            // `turbo_tasks::Vc<Self>` here.
+                            // `turbo_tasks::Vc<Self>` or `turbo_tasks::ResolvedVc<Self>` here.
                             // We'll rely on the compiler to emit an error
-                            // if the user provided an invalid receiver type
+                            // if the user provided an invalid receiver type.
+                            //
+                            // Either way the exposed function takes a `Vc<Self>`, which is
+                            // resolved before the inline function is called.
 
                             let ident = ident.ident.clone();
 
                             this = Some(Input {
                                 ident,
                                 ty: parse_quote! { turbo_tasks::Vc<Self> },
+                                flatten: false,
+                                map_value: false,
+                                span: None,
+                                trace_as: None,
+                                untracked: false,
+                                debug_skip: false,
+                                key: None,
                             });
                         } else {
                             match definition_context {
@@ -200,7 +647,6 @@ impl TurboFn {
                                 | DefinitionContext::ValueTrait { .. } => {
                                     typed
                                         .span()
-                                        .unwrap()
                                         .error(format!(
                                             "{} must accept &self or self: Vc<Self> as the first \
                                              argument",
@@ -215,15 +661,31 @@ impl TurboFn {
                             inputs.push(Input {
                                 ident,
                                 ty: (*typed.ty).clone(),
+                                flatten,
+                                map_value,
+                                span,
+                                trace_as,
+                                untracked,
+                                debug_skip,
+                                key,
                             });
                         }
                     } else {
-                        // We can't support destructuring patterns (or other kinds of patterns).
-                        let ident = Ident::new("arg1", typed.pat.span());
+                        // Destructuring patterns are bound to a generated name in the signature,
+                        // and re-destructured at the start of the inline
+                        // function.
+                        let ident = pattern_argument_ident(&typed.pat, 1, &bound_idents);
 
                         inputs.push(Input {
                             ident,
                             ty: (*typed.ty).clone(),
+                            flatten,
+                            map_value,
+                            span,
+                            trace_as,
+                            untracked,
+                            debug_skip,
+                            key,
                         });
                     }
                 }
@@ -240,29 +702,233 @@ impl TurboFn {
                     let ident = if let Pat::Ident(ident) = &*typed.pat {
                         ident.ident.clone()
                     } else {
-                        Ident::new(&format!("arg{}", i + 2), typed.pat.span())
+                        pattern_argument_ident(&typed.pat, i + 2, &bound_idents)
                     };
 
+                    let ArgumentAttributes {
+                        flatten,
+                        map_value,
+                        span,
+                        trace_as,
+                        untracked,
+                        debug_skip,
+                        key,
+                    } = ArgumentAttributes::parse(typed, &definition_context)?;
                     inputs.push(Input {
                         ident,
                         ty: (*typed.ty).clone(),
+                        flatten,
+                        map_value,
+                        span,
+                        trace_as,
+                        untracked,
+                        debug_skip,
+                        key,
                     });
                 }
             }
         }
 
+        for input in &inputs {
+            if let (Type::ImplTrait(impl_trait), None) =
+                (&input.ty, ImplTraitArgument::parse(&input.ty))
+            {
+                impl_trait
+                    .span()
+                    .error(format!(
+                        "{} only support `impl Into<T>`, `impl AsRef<T>` and `impl \
+                         IntoIterator<Item = T>` argument types",
+                        definition_context.function_type(),
+                    ))
+                    .help("use a concrete type, which must implement `TaskInput`")
+                    .emit();
+                return None;
+            }
+        }
+
+        for input in &inputs {
+            let Some(key) = &input.key else {
+                continue;
+            };
+            if definition_context != DefinitionContext::NakedFn || monomorphize.is_some() {
+                key.span()
+                    .error(format!(
+                        "{} do not support #[turbo_tasks(key(...))]",
+                        if monomorphize.is_some() {
+                            "monomorphized functions"
+                        } else {
+                            definition_context.function_type()
+                        },
+                    ))
+                    .emit();
+                return None;
+            }
+        }
+
+        for input in &inputs {
+            if input.map_value || input.flatten {
+                continue;
+            }
+            if let Some(map_name) = unhashable_map_type_name(&input.ty) {
+                input
+                    .ty
+                    .span()
+                    .error(format!(
+                        "{} can't take a `{map_name}` argument, as it doesn't implement `Hash`, \
+                         which task inputs require",
+                        definition_context.function_type(),
+                    ))
+                    .help(
+                        "mark the argument with #[turbo_tasks(map_value)] to pass its entries as \
+                         a task input, or use a `BTreeMap`",
+                    )
+                    .emit();
+                return None;
+            }
+        }
+
+        let path_argument_warnings = if args.portable_inputs.is_some() {
+            let suggest_fs = !args.io_markers.contains(&IoMarker::Filesystem);
+            inputs
+                .iter()
+                .flat_map(|input| find_path_types(&input.ty))
+                .map(|span| PathArgumentWarning { span, suggest_fs })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         let output = return_type_to_type(&original_signature.output);
 
+        let assert_send = match (args.assert_send, &original_signature.asyncness) {
+            (Some(_), Some(asyncness)) => Some(asyncness.span()),
+            (Some(span), None) => {
+                span.error("\"assert_send\" can only be used with async functions")
+                    .emit();
+                None
+            }
+            (None, _) => None,
+        };
+
+        let lazy_body = match (args.lazy_body, &original_signature.asyncness) {
+            (Some(_), Some(_)) => true,
+            (Some(span), None) => {
+                span.error(
+                    "\"lazy_body\" can only be used with async functions, the body of a sync \
+                     function only runs when its task is polled",
+                )
+                .emit();
+                false
+            }
+            (None, _) => false,
+        };
+
+        // The future of an async operation must produce the `Vc<T>` that's exposed as an
+        // `OperationVc<T>`. Other outputs (e.g. `Option<Vc<T>>`) are wrapped in another `Vc` after
+        // the future completes, so the operation's output couldn't be tracked.
+        if let (Some(_), Some(_)) = (args.operation, original_signature.asyncness) {
+            let vc_output = result_ok_type(&output).unwrap_or(&output);
+            if !is_vc_type(vc_output) {
+                vc_output
+                    .span()
+                    .error(
+                        "async \"operation\" functions must return `Vc<T>` or `Result<Vc<T>>`, \
+                         which is exposed as an `OperationVc<T>`",
+                    )
+                    .help("move the operation into a separate function returning `Vc<T>`")
+                    .emit();
+                return None;
+            }
+        }
+
+        if let Some(retry) = &args.retry {
+            if !is_result_type(&output) {
+                retry
+                    .span
+                    .error(format!(
+                        "{} must return a `Result` to use \"retry\"",
+                        definition_context.function_type(),
+                    ))
+                    .emit();
+                return None;
+            }
+        }
+
+        if let Some(fallback) = &args.fallback {
+            if !is_result_type(&output) {
+                fallback
+                    .span()
+                    .error(format!(
+                        "{} must return a `Result` to use \"fallback\"",
+                        definition_context.function_type(),
+                    ))
+                    .emit();
+                return None;
+            }
+        }
+
+        let value_receiver = match (&this, &definition_context) {
+            (Some(this), DefinitionContext::ValueInherentImpl) => Some(this.ident.span()),
+            _ => None,
+        };
+
         Some(TurboFn {
+            orig_signature: original_signature.clone(),
             ident: original_signature.ident.clone(),
             output,
             this,
             inputs,
             resolved: args.resolved,
+            value_receiver,
             local_cells: args.local_cells.is_some(),
+            name_fn: args.name_fn,
+            name: args.name,
+            trace_level: args.trace_level,
+            hasher: args.hasher,
+            deprecated: args.deprecated,
+            replacement: args.replacement,
+            doc_return: args.doc_return,
+            direct_from_task_input: args.direct_from_task_input.is_some(),
+            parallel_inputs: args.parallel_inputs.is_some(),
+            cancellable: args.cancellable.is_some(),
+            profile: args.profile.is_some(),
+            retry: args.retry,
+            assert_send,
+            schedule_after: args.schedule_after,
+            monomorphize,
+            warn_after: args.warn_after.map(|(_, micros)| micros),
+            gen_smoke_test,
+            count_calls,
+            manual_invalidation,
+            also_untracked,
+            track_duration,
+            static_vis,
+            module_path,
+            fallback: args.fallback,
+            version: args.version.unwrap_or_default(),
+            readonly: args.readonly.is_some(),
+            concurrency_group: args.concurrency_group,
+            lazy_body,
+            resolve_return,
+            no_cache,
+            eager: args.eager.is_some(),
+            priority: args.priority.unwrap_or(TaskPriority::Normal),
+            operation,
+            path_argument_warnings,
+            io_fs: args.io_markers.contains(&IoMarker::Filesystem),
+            io_network: args.io_markers.contains(&IoMarker::Network),
+            definition_context,
         })
     }
 
+    /// Emits the warnings found while parsing the function, e.g. about the arguments of
+    /// `portable_inputs` functions.
+    pub fn emit_warnings(&self) {
+        for warning in &self.path_argument_warnings {
+            warning.emit();
+        }
+    }
+
     /// The signature of the exposed function. This is the original signature
     /// converted to a standard turbo_tasks function signature.
     pub fn signature(&self) -> Signature {
@@ -274,7 +940,23 @@ impl TurboFn {
             .map(|input| {
                 FnArg::Typed(PatType {
                     attrs: Default::default(),
-                    ty: Box::new(input.ty.clone()),
+                    ty: Box::new(
+                        if input.flatten
+                            || is_cow_str_type(&input.ty)
+                            || ImplTraitArgument::parse(&input.ty).is_some()
+                        {
+                            // the fields are taken apart (or the `Cow` or `impl Trait` is
+                            // converted) in the exposed function's block
+                            input.ty.clone()
+                        } else if owned_borrowed_type(&input.ty).is_some() {
+                            // the reference is converted in the exposed function's block
+                            exposed_borrowed_type(&input.ty)
+                        } else if input.map_value {
+                            self.exposed_map_type(&input.ty)
+                        } else {
+                            self.exposed_input_type(&input.ty).into_owned()
+                        },
+                    ),
                     pat: Box::new(Pat::Ident(PatIdent {
                         attrs: Default::default(),
                         by_ref: None,
@@ -290,81 +972,957 @@ impl TurboFn {
         let ident = &self.ident;
         let orig_output = &self.output;
         let new_output = expand_vc_return_type(orig_output);
+        let operation_output = operation_vc_type(&new_output);
 
-        parse_quote! {
-            fn #ident(#exposed_inputs) -> #new_output
+        let mut signature: Signature = match &self.monomorphize {
+            // the instantiation is picked by the `TypeId` of the parameter, which must be `'static`
+            Some(Monomorphize { param, .. }) => {
+                let param_ident = &param.ident;
+                parse_quote! {
+                    fn #ident<#param>(#exposed_inputs) -> #new_output
+                    where
+                        #param_ident: 'static
+                }
+            }
+            None => parse_quote! {
+                fn #ident(#exposed_inputs) -> #new_output
+            },
+        };
+        // The inline function keeps the original where clause, the exposed function needs it too
+        if let Some(where_clause) = &self.orig_signature.generics.where_clause {
+            signature
+                .generics
+                .make_where_clause()
+                .predicates
+                .extend(where_clause.predicates.iter().cloned());
         }
-    }
-
-    pub fn trait_signature(&self) -> Signature {
-        let signature = self.signature();
-
-        parse_quote! {
-            #signature where Self: Sized
+        if self.operation {
+            signature.output = parse_quote! { -> #operation_output };
         }
+        if self.resolve_return || self.no_cache {
+            signature.asyncness = Some(Default::default());
+            signature.output = parse_quote! { -> turbo_tasks::Result<#new_output> };
+        }
+        signature
     }
 
-    fn input_idents(&self) -> impl Iterator<Item = &Ident> {
-        self.inputs.iter().map(|Input { ident, .. }| ident)
+    /// The signature of the exposed function as it should be shown by rustdoc, if a `doc_return`
+    /// type is set. This is useful for trait-object returns, where the expanded return type (e.g.
+    /// `Vc<Box<dyn Trait>>`) is more verbose than an alias for it.
+    ///
+    /// The macros emit the real definition under `#[cfg(not(doc))]` and a stub with this signature
+    /// under `#[cfg(doc)]`.
+    pub fn doc_signature(&self) -> Option<Signature> {
+        let doc_return = self.doc_return.as_ref()?;
+        let mut signature = self.signature();
+        signature.output = parse_quote! { -> #doc_return };
+        Some(signature)
     }
 
-    pub fn input_types(&self) -> Vec<&Type> {
-        self.inputs.iter().map(|Input { ty, .. }| ty).collect()
-    }
+    /// The signature and block of the inline function. The inline function is the function with
+    /// minimal changes to the original that's registered with, and called by, the turbo-tasks
+    /// runtime.
+    ///
+    /// It accepts the same argument types as the exposed function (see
+    /// [`Self::exposed_input_type`]) and converts them back to the argument types of the original
+    /// signature before running `orig_block`.
+    ///
+    /// If a `replacement` is set and `orig_block` is empty, the block forwards all arguments
+    /// (including `self`) to the replacement instead. They're passed as the exposed function
+    /// accepts them (see [`Self::exposed_value`]), as they're passed to the replacement's exposed
+    /// function.
+    pub fn inline_signature_and_block(
+        &self,
+        inline_ident: &Ident,
+        orig_block: &Block,
+    ) -> (Signature, Block) {
+        let mut inline_signature = self.orig_signature.clone();
+        inline_signature.ident = inline_ident.clone();
 
-    pub fn persistence(&self) -> impl ToTokens {
-        if self.local_cells {
-            quote! {
-                turbo_tasks::TaskPersistence::LocalCells
+        let mut inputs = self.inputs.iter();
+        let mut unwraps = Vec::new();
+        let mut conversions = Vec::with_capacity(self.inputs.len());
+        for arg in inline_signature.inputs.iter_mut() {
+            let FnArg::Typed(pat_type) = arg else {
+                continue;
+            };
+            if matches!(&*pat_type.pat, Pat::Ident(PatIdent { ident, .. }) if ident == "self") {
+                continue;
             }
-        } else {
-            quote! {
-                turbo_tasks::macro_helpers::get_non_local_persistence_from_inputs(&*inputs)
+            let Some(input) = inputs.next() else {
+                break;
+            };
+            let Input {
+                ident, ty, flatten, ..
+            } = input;
+            pat_type
+                .attrs
+                .retain(|attr| !attr.path.is_ident("turbo_tasks"));
+            let orig_pat = std::mem::replace(
+                &mut *pat_type.pat,
+                Pat::Ident(PatIdent {
+                    attrs: Default::default(),
+                    by_ref: None,
+                    mutability: None,
+                    ident: ident.clone(),
+                    subpat: None,
+                }),
+            );
+            pat_type.ty = Box::new(self.task_input_type(input).into_owned());
+            if input.is_wrapped() {
+                let unwrap_fields = input.unwrap_fields();
+                unwraps.push(quote! {
+                    let #ident = #ident #(.#unwrap_fields)*;
+                });
             }
+            conversions.push(if *flatten {
+                quote! {
+                    let #orig_pat =
+                        <#ty as turbo_tasks::task::FlattenTaskInput>::from_fields(#ident);
+                }
+            } else if input.map_value {
+                let (_, value_ty) = map_key_value_types(ty).expect("validated when parsing");
+                let from_task_input = self.from_task_input_path(value_ty);
+                quote! {
+                    let #orig_pat: #ty = #ident
+                        .into_iter()
+                        .map(|(key, value)| (key, #from_task_input(value)))
+                        .collect();
+                }
+            } else if is_cow_str_type(ty) {
+                quote! {
+                    let #orig_pat = <#ty as ::std::convert::From<
+                        turbo_tasks::task::CowStrTaskInput,
+                    >>::from(#ident);
+                }
+            } else if let Some(owned) = owned_borrowed_type(ty) {
+                // re-borrows the owned `RcStr` or `Vec<T>`, which lives until the end of the body
+                let reborrow = quote! {
+                    let #orig_pat: #ty = &*#ident;
+                };
+                if let Cow::Borrowed(_) = expand_task_input_type(&owned) {
+                    reborrow
+                } else {
+                    // e.g. a `Vec<Vc<T>>` for a `&[ResolvedVc<T>]`
+                    let from_task_input = self.from_task_input_path(&owned);
+                    quote! {
+                        let #ident: #owned = #from_task_input(#ident);
+                        #reborrow
+                    }
+                }
+            } else if let Some(impl_trait) = ImplTraitArgument::parse(ty) {
+                impl_trait.inline_conversion(&orig_pat, ident)
+            } else {
+                let from_task_input = self.from_task_input_path(ty);
+                quote! {
+                    let #orig_pat = #from_task_input(#ident);
+                }
+            });
         }
-    }
 
-    pub fn persistence_with_this(&self) -> impl ToTokens {
-        if self.local_cells {
-            quote! {
-                turbo_tasks::TaskPersistence::LocalCells
-            }
-        } else {
-            quote! {
-                turbo_tasks::macro_helpers::get_non_local_persistence_from_inputs_and_this(this, &*inputs)
+        let forwarding_block;
+        let orig_block = match &self.replacement {
+            Some(replacement) if orig_block.stmts.is_empty() => {
+                let this = self.this.iter().map(|Input { ident, .. }| ident);
+                let args = self.inputs.iter().map(|input| self.exposed_value(input));
+                forwarding_block = parse_quote! {
+                    {
+                        #replacement(#(#this,)* #(#args),*)
+                    }
+                };
+                // the task inputs are forwarded instead of the original arguments
+                conversions.clear();
+                &forwarding_block
             }
-        }
-    }
+            _ => orig_block,
+        };
+        let conversions: Vec<_> = unwraps.into_iter().chain(conversions).collect();
 
-    fn converted_this(&self) -> Option<Expr> {
-        self.this.as_ref().map(|Input { ty: _, ident }| {
-            parse_quote! {
-                turbo_tasks::Vc::into_raw(#ident)
-            }
-        })
-    }
+        let profile_guard = self.profile_guard(quote! { Body }, "body");
+        let warn_after_guard = self.warn_after_guard();
+        let last_duration_guard = self.last_duration_guard();
 
-    fn get_assertions(&self) -> TokenStream {
-        if let Some(span) = self.resolved {
-            let return_type = &self.output;
-            quote_spanned! {
-                span =>
-                {
-                    turbo_tasks::macro_helpers::assert_returns_resolved_value::<#return_type, _>()
+        let body = match &self.retry {
+            None => quote! {
+                #(#conversions)*
+                #orig_block
+            },
+            Some(RetryArguments { count, on, .. }) => {
+                let output = &self.output;
+                let input_idents: Vec<_> = self.input_idents().collect();
+                let attempt = if self.orig_signature.asyncness.is_some() {
+                    quote! {
+                        turbo_tasks::macro_helpers::typed_future::<#output, _>(async move {
+                            #(#conversions)*
+                            #orig_block
+                        })
+                        .await
+                    }
+                } else {
+                    quote! {
+                        (move || -> #output {
+                            #(#conversions)*
+                            #orig_block
+                        })()
+                    }
+                };
+                let (err, should_retry) = match on {
+                    Some(on) => (quote! { err }, quote! { #on(&err) }),
+                    None => (quote! { _ }, quote! { true }),
+                };
+                quote! {
+                    let mut retries: u32 = 0;
+                    loop {
+                        let result = {
+                            #(let #input_idents = ::std::clone::Clone::clone(&#input_idents);)*
+                            #attempt
+                        };
+                        match result {
+                            Err(#err) if retries < #count && #should_retry => {
+                                retries += 1;
+                            }
+                            result => break result,
+                        }
+                    }
                 }
             }
-        } else {
-            quote! {}
+        };
+
+        let body = match &self.fallback {
+            Some(fallback) => {
+                let output = &self.output;
+                let result = if self.orig_signature.asyncness.is_some() {
+                    quote! {
+                        turbo_tasks::macro_helpers::typed_future::<#output, _>(async move {
+                            #body
+                        })
+                        .await
+                    }
+                } else {
+                    quote! {
+                        (move || -> #output {
+                            #body
+                        })()
+                    }
+                };
+                let function_name = self.ident.to_string();
+                quote! {
+                    match #result {
+                        Err(err) => {
+                            turbo_tasks::macro_helpers::tracing::warn!(
+                                function = #function_name,
+                                error = ?err,
+                                "{} failed, using its fallback value",
+                                #function_name,
+                            );
+                            Ok(#fallback())
+                        }
+                        result => result,
+                    }
+                }
+            }
+            None => body,
+        };
+
+        let body = match self.assert_send {
+            Some(span) => {
+                let output = &self.output;
+                quote_spanned! {
+                    span =>
+                    turbo_tasks::macro_helpers::assert_send_future::<#output, _>(async move {
+                        #body
+                    })
+                    .await
+                }
+            }
+            None => body,
+        };
+
+        let body = if self.lazy_body {
+            let output = &self.output;
+            quote! {
+                turbo_tasks::macro_helpers::lazy_body::<#output, _, _>(move || async move {
+                    #body
+                })
+                .await
+            }
+        } else {
+            body
+        };
+
+        // A function marked with every kind of IO can't perform unmarked IO, so it doesn't need
+        // its markers in scope. Other functions only have them in scope in debug builds, see
+        // `turbo_tasks::io_markers_scope!`.
+        let body = if self.io_fs && self.io_network {
+            body
+        } else {
+            let output = &self.output;
+            let ident = &self.ident;
+            let io_fs = self.io_fs;
+            let io_network = self.io_network;
+            let name = quote! { concat!(module_path!(), "::", stringify!(#ident)) };
+            let markers = quote! {
+                turbo_tasks::IoMarkers {
+                    fs: #io_fs,
+                    network: #io_network,
+                }
+            };
+            let asyncness = self.orig_signature.asyncness;
+            quote! {
+                turbo_tasks::io_markers_scope!(#name, #markers, #output, #asyncness {
+                    #body
+                })
+            }
+        };
+
+        let inline_block = parse_quote! {
+            {
+                #profile_guard
+                #warn_after_guard
+                #last_duration_guard
+                #[allow(unused_imports)]
+                use turbo_tasks::macro_helpers::AutoFromTaskInputIdentity as _;
+                #body
+            }
+        };
+
+        (inline_signature, inline_block)
+    }
+
+    /// The `#[deprecated]` attribute for the exposed function, if any. Without an explicit note,
+    /// the note points at the `replacement`.
+    pub fn deprecated_attribute(&self) -> Option<TokenStream> {
+        let note = self.deprecated.as_ref()?;
+        let note = note.clone().or_else(|| {
+            self.replacement.as_ref().map(|replacement| {
+                LitStr::new(
+                    &format!(
+                        "use `{}` instead",
+                        replacement.to_token_stream().to_string().replace(' ', "")
+                    ),
+                    replacement.span(),
+                )
+            })
+        });
+        Some(match note {
+            Some(note) => quote! { #[deprecated(note = #note)] },
+            None => quote! { #[deprecated] },
+        })
+    }
+
+    /// A human-readable description of the exposed signature, e.g.
+    /// `fn my_task(value: u32) -> Vc<u32>`.
+    pub fn signature_string(&self) -> String {
+        let Signature {
+            ident,
+            inputs,
+            output,
+            ..
+        } = self.signature();
+        let inputs = inputs
+            .iter()
+            .map(tokens_to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        match output {
+            ReturnType::Default => format!("fn {ident}({inputs})"),
+            ReturnType::Type(_, ty) => format!("fn {ident}({inputs}) -> {}", tokens_to_string(ty)),
+        }
+    }
+
+    /// A human-readable description of the exposed return type, e.g. `Vc<u32>` for a function
+    /// returning `Result<Vc<u32>>`.
+    pub fn return_type_string(&self) -> String {
+        tokens_to_string(expand_vc_return_type(&self.output))
+    }
+
+    /// The span of the first mention of `Self` in the return type, e.g. in `Vc<Self>`.
+    pub fn self_in_return_type(&self) -> Option<Span> {
+        find_types_by_name(&self.output, &["Self"])
+            .into_iter()
+            .next()
+    }
+
+    /// The extra where clause predicate of a value trait's associated function without `self` or
+    /// a default implementation. There's nothing to call for it when `Self` is a trait object, so
+    /// it's limited to value types, which makes calling it on a trait object a compile error.
+    pub fn value_type_bound(&self, has_default: bool) -> Option<WherePredicate> {
+        (self.this.is_none() && !has_default)
+            .then(|| parse_quote! { Self: turbo_tasks::VcValueType })
+    }
+
+    pub fn trait_signature(&self) -> Signature {
+        let signature = self.signature();
+
+        parse_quote! {
+            #signature where Self: Sized
+        }
+    }
+
+    fn input_idents(&self) -> impl Iterator<Item = &Ident> {
+        self.inputs.iter().map(|Input { ident, .. }| ident)
+    }
+
+    pub fn definition_context(&self) -> &DefinitionContext {
+        &self.definition_context
+    }
+
+    /// The number of arguments, excluding `self`.
+    pub fn arg_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// The idents of all parameters in declaration order, starting with `self` for methods.
+    /// Parameters with a pattern other than an ident are named `argN`.
+    pub fn parameter_idents(&self) -> impl Iterator<Item = &Ident> {
+        self.this
+            .iter()
+            .chain(self.inputs.iter())
+            .map(|Input { ident, .. }| ident)
+    }
+
+    /// Converts the (unwrapped) task input of `input` back into the value that the exposed
+    /// function accepts for it. It's the inverse of [`Self::input_values`], e.g. a `Vec<T>` task
+    /// input is passed as a `&[T]` for a `&[T]` argument.
+    fn exposed_value(&self, input: &Input) -> TokenStream {
+        let Input { ident, ty, .. } = input;
+        if input.flatten {
+            quote! { <#ty as turbo_tasks::task::FlattenTaskInput>::from_fields(#ident) }
+        } else if input.map_value {
+            quote! { ::std::iter::IntoIterator::into_iter(#ident).collect() }
+        } else if is_cow_str_type(ty) {
+            quote! {
+                <#ty as ::std::convert::From<turbo_tasks::task::CowStrTaskInput>>::from(#ident)
+            }
+        } else if owned_borrowed_type(ty).is_some() {
+            quote! { &*#ident }
+        } else if let Some(ImplTraitArgument::AsRef(target)) = ImplTraitArgument::parse(ty) {
+            quote! { ::std::borrow::Borrow::<#target>::borrow(&#ident) }
+        } else {
+            quote! { #ident }
+        }
+    }
+
+    /// The type that the exposed function accepts for an argument of type `ty`.
+    ///
+    /// With `direct_from_task_input`, this is exactly `<ty as FromTaskInput>::TaskInput`. Otherwise
+    /// it's approximated by [`expand_task_input_type`].
+    fn exposed_input_type<'a>(&self, ty: &'a Type) -> Cow<'a, Type> {
+        if self.direct_from_task_input {
+            Cow::Owned(parse_quote! {
+                <#ty as turbo_tasks::task::FromTaskInput>::TaskInput
+            })
+        } else {
+            expand_task_input_type(ty)
+        }
+    }
+
+    /// The type that the exposed function accepts for a `#[turbo_tasks(map_value)]` argument of
+    /// type `ty`: the map type with its value type replaced by its [`Self::exposed_input_type`].
+    fn exposed_map_type(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        if let Some(value_ty) = map_value_type_mut(&mut ty) {
+            *value_ty = self.exposed_input_type(value_ty).into_owned();
+        }
+        ty
+    }
+
+    /// The path of the function converting a task input back into an argument of type `ty`.
+    fn from_task_input_path(&self, ty: &Type) -> TokenStream {
+        if self.direct_from_task_input || is_resolved_vc_type(ty) {
+            // `ResolvedVc` inputs were resolved before the task was executed, so they're bound
+            // directly (without resolving them again), skipping the `AutoFromTaskInput`
+            // specialization.
+            quote! { <#ty as turbo_tasks::task::FromTaskInput>::from_task_input }
+        } else if is_arc_type(ty) && matches!(expand_task_input_type(ty), Cow::Borrowed(_)) {
+            // An `Arc` without a `ResolvedVc` inside is stored as-is, so it's shared with the body
+            // instead of cloning its contents for the conversion.
+            quote! { ::std::convert::identity }
+        } else {
+            quote! { turbo_tasks::macro_helpers::AutoFromTaskInput::<#ty>::from_task_input }
+        }
+    }
+
+    /// The type of the task input for `input`. For a `#[turbo_tasks(flatten)]` argument, this is
+    /// the tuple of its fields, and for a `#[turbo_tasks(map_value)]` argument, a `Vec` of its
+    /// entries. A `Cow<'static, str>` argument is stored as a `CowStrTaskInput`, which is cheap to
+    /// clone. Otherwise, it's [`Self::exposed_input_type`].
+    ///
+    /// `#[turbo_tasks(key(...))]`, `#[turbo_tasks(debug_skip)]` and `#[turbo_tasks(untracked)]`
+    /// arguments are wrapped in a `KeyedTaskInput`, `DebugSkippedTaskInput` and
+    /// `UntrackedTaskInput` respectively.
+    fn task_input_type<'a>(&self, input: &'a Input) -> Cow<'a, Type> {
+        let mut ty = self.unwrapped_task_input_type(input);
+        if input.key.is_some() {
+            let key_marker = self.key_marker_ident(input);
+            ty = Cow::Owned(parse_quote! { turbo_tasks::task::KeyedTaskInput<#ty, #key_marker> });
+        }
+        if input.debug_skip {
+            ty = Cow::Owned(parse_quote! { turbo_tasks::task::DebugSkippedTaskInput<#ty> });
+        }
+        if input.untracked {
+            ty = Cow::Owned(parse_quote! { turbo_tasks::task::UntrackedTaskInput<#ty> });
+        }
+        ty
+    }
+
+    /// The type of the task input for `input`, without the wrappers of
+    /// [`Self::task_input_type`].
+    fn unwrapped_task_input_type<'a>(&self, input: &'a Input) -> Cow<'a, Type> {
+        if input.flatten {
+            let ty = &input.ty;
+            Cow::Owned(parse_quote! {
+                <#ty as turbo_tasks::task::FlattenTaskInput>::Fields
+            })
+        } else if input.map_value {
+            let (key_ty, value_ty) =
+                map_key_value_types(&input.ty).expect("validated when parsing");
+            let value_ty = self.exposed_input_type(value_ty);
+            Cow::Owned(parse_quote! { Vec<(#key_ty, #value_ty)> })
+        } else if is_cow_str_type(&input.ty) {
+            Cow::Owned(parse_quote! { turbo_tasks::task::CowStrTaskInput })
+        } else if let Some(owned) = owned_borrowed_type(&input.ty) {
+            Cow::Owned(expand_task_input_type(&owned).into_owned())
+        } else if let Some(impl_trait) = ImplTraitArgument::parse(&input.ty) {
+            Cow::Owned(impl_trait.task_input_type())
+        } else {
+            self.exposed_input_type(&input.ty)
+        }
+    }
+
+    /// The types of the task inputs. See [`Self::task_input_type`].
+    pub fn exposed_input_types(&self) -> Vec<Cow<'_, Type>> {
+        self.inputs
+            .iter()
+            .map(|input| self.task_input_type(input))
+            .collect()
+    }
+
+    /// The argument types (excluding `self`) as they were declared on the original function.
+    ///
+    /// A proc-macro crate can't export this to external tooling, so it's only used to verify the
+    /// expansion in tests.
+    #[cfg(test)]
+    pub fn input_types(&self) -> Vec<&Type> {
+        self.inputs.iter().map(|Input { ty, .. }| ty).collect()
+    }
+
+    /// The argument types (excluding `self`) of the inline signature, i.e. the expanded forms of
+    /// [`Self::input_types`]. See [`Self::task_input_type`].
+    #[cfg(test)]
+    pub fn inline_input_types(&self) -> Vec<Type> {
+        self.exposed_input_types()
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect()
+    }
+
+    /// The marker type implementing `TaskInputKey` for a `#[turbo_tasks(key(...))]` argument, see
+    /// [`Self::key_definitions`].
+    fn key_marker_ident(&self, input: &Input) -> Ident {
+        format_ident!("{}_{}_key", self.ident.unraw(), input.ident.unraw())
+    }
+
+    /// The marker types of the `#[turbo_tasks(key(...))]` arguments, whose `TaskInputKey` impls
+    /// apply the projections to the task inputs.
+    pub fn key_definitions(&self) -> TokenStream {
+        self.inputs
+            .iter()
+            .filter_map(|input| {
+                let key = input.key.as_ref()?;
+                let key_marker = self.key_marker_ident(input);
+                let ty = self.unwrapped_task_input_type(input);
+                Some(quote_spanned! {
+                    key.span() =>
+                    #[doc(hidden)]
+                    #[allow(non_camel_case_types)]
+                    struct #key_marker;
+
+                    impl turbo_tasks::task::TaskInputKey<#ty> for #key_marker {
+                        fn key(value: &#ty) -> impl ::std::hash::Hash + ::std::cmp::Eq {
+                            turbo_tasks::macro_helpers::project_task_input_key(value, #key)
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// The expressions building the task inputs from the arguments of the exposed function.
+    fn input_values(&self) -> impl Iterator<Item = TokenStream> + '_ {
+        self.inputs.iter().map(|input| {
+            let Input {
+                ident,
+                ty,
+                flatten,
+                map_value,
+                untracked,
+                debug_skip,
+                key,
+                ..
+            } = input;
+            let mut value = if *flatten {
+                quote! { <#ty as turbo_tasks::task::FlattenTaskInput>::into_fields(#ident) }
+            } else if *map_value {
+                quote! { turbo_tasks::macro_helpers::sorted_map_entries(#ident) }
+            } else if is_cow_str_type(ty) {
+                quote! { turbo_tasks::task::CowStrTaskInput::from(#ident) }
+            } else if let Some(owned) = owned_borrowed_type(ty) {
+                let owned = expand_task_input_type(&owned);
+                let exposed_ty = exposed_borrowed_type(ty);
+                quote! { <#owned as ::std::convert::From<#exposed_ty>>::from(#ident) }
+            } else if let Some(impl_trait) = ImplTraitArgument::parse(ty) {
+                impl_trait.exposed_conversion(ident)
+            } else {
+                quote! { #ident }
+            };
+            if key.is_some() {
+                let key_marker = self.key_marker_ident(input);
+                value = quote! {
+                    turbo_tasks::task::KeyedTaskInput::<_, #key_marker>::new(#value)
+                };
+            }
+            if *debug_skip {
+                value = quote! { turbo_tasks::task::DebugSkippedTaskInput(#value) };
+            }
+            if *untracked {
+                value = quote! { turbo_tasks::task::UntrackedTaskInput(#value) };
+            }
+            value
+        })
+    }
+
+    /// A custom resolver for the inputs tuple, if one is needed (see
+    /// `turbo_tasks::NativeFunction::with_arg_resolver`).
+    ///
+    /// With `parallel_inputs`, only the inputs that contain a `Vc` are joined. The other inputs
+    /// are cloned as-is if they're already resolved. With `cancellable`, the inputs are resolved
+    /// one at a time (or the `Vc` inputs joined at once), checking for cancellation before each
+    /// step. With `profile`, the resolution is timed.
+    pub fn arg_resolver(&self) -> Option<TokenStream> {
+        if !self.parallel_inputs && !self.profile && !self.cancellable {
+            return None;
+        }
+
+        let input_types = self.exposed_input_types();
+        let profile_guard = self.profile_guard(quote! { ResolveInputs }, "resolve_inputs");
+        let cancellation_check = self.cancellable.then(|| {
+            quote! {
+                turbo_tasks::macro_helpers::bail_if_cancelled()?;
+            }
+        });
+
+        let input_idents: Vec<_> = self.input_idents().collect();
+
+        if !self.parallel_inputs && !self.cancellable {
+            return Some(quote! {
+                |inputs| {
+                    ::std::boxed::Box::pin(async move {
+                        #profile_guard
+                        let inputs = turbo_tasks::macro_helpers::downcast_inputs::<
+                            (#(#input_types,)*)
+                        >(inputs);
+                        Ok(::std::boxed::Box::new(turbo_tasks::TaskInput::resolve(inputs).await?)
+                            as ::std::boxed::Box<dyn turbo_tasks::macro_helpers::MagicAny>)
+                    })
+                }
+            });
+        }
+
+        if !self.parallel_inputs {
+            return Some(quote! {
+                |inputs| {
+                    ::std::boxed::Box::pin(async move {
+                        #profile_guard
+                        let (#(#input_idents,)*) = turbo_tasks::macro_helpers::downcast_inputs::<
+                            (#(#input_types,)*)
+                        >(inputs);
+                        #(
+                            #cancellation_check
+                            let #input_idents = turbo_tasks::TaskInput::resolve(#input_idents).await?;
+                        )*
+                        Ok(::std::boxed::Box::new((#(#input_idents,)*))
+                            as ::std::boxed::Box<dyn turbo_tasks::macro_helpers::MagicAny>)
+                    })
+                }
+            });
+        }
+
+        let (vc_idents, plain_idents): (Vec<_>, Vec<_>) = self
+            .inputs
+            .iter()
+            .map(|Input { ident, ty, .. }| (ident, contains_vc_type(ty)))
+            .partition(|(_, is_vc)| *is_vc);
+        let vc_idents: Vec<_> = vc_idents.into_iter().map(|(ident, _)| ident).collect();
+        let plain_idents = plain_idents.into_iter().map(|(ident, _)| ident);
+
+        let join = (!vc_idents.is_empty()).then(|| {
+            quote! {
+                #cancellation_check
+                let (#(#vc_idents,)*) = turbo_tasks::macro_helpers::futures::try_join!(
+                    #(turbo_tasks::TaskInput::resolve(#vc_idents)),*
+                )?;
+            }
+        });
+
+        Some(quote! {
+            |inputs| {
+                ::std::boxed::Box::pin(async move {
+                    #profile_guard
+                    let (#(#input_idents,)*) = turbo_tasks::macro_helpers::downcast_inputs::<
+                        (#(#input_types,)*)
+                    >(inputs);
+                    #(
+                        let #plain_idents = if turbo_tasks::TaskInput::is_resolved(#plain_idents) {
+                            ::std::clone::Clone::clone(#plain_idents)
+                        } else {
+                            #cancellation_check
+                            turbo_tasks::TaskInput::resolve(#plain_idents).await?
+                        };
+                    )*
+                    #join
+                    Ok(::std::boxed::Box::new((#(#input_idents,)*))
+                        as ::std::boxed::Box<dyn turbo_tasks::macro_helpers::MagicAny>)
+                })
+            }
+        })
+    }
+
+    /// With `profile`, a guard that reports the time until the end of the current scope to
+    /// `turbo_tasks::profile::report` as the given `turbo_tasks::profile::ProfilePhase`, under a
+    /// metric name made of the function's path and `metric_suffix`.
+    fn profile_guard(&self, phase: TokenStream, metric_suffix: &str) -> Option<TokenStream> {
+        if !self.profile {
+            return None;
+        }
+        let ident = &self.ident;
+        Some(quote! {
+            let _profile_guard = turbo_tasks::duration_span::DurationSpanGuard::new(|duration| {
+                turbo_tasks::profile::report(
+                    concat!(module_path!(), "::", stringify!(#ident), "::", #metric_suffix),
+                    turbo_tasks::profile::ProfilePhase::#phase,
+                    duration,
+                )
+            });
+        })
+    }
+
+    /// Logs a warning when dropped after more than `warn_after`, if set. Must be placed at the
+    /// start of the inline block.
+    fn warn_after_guard(&self) -> Option<TokenStream> {
+        let threshold = self.warn_after?;
+        let ident = &self.ident;
+        Some(quote! {
+            let _warn_after_guard = turbo_tasks::duration_span::DurationSpanGuard::new(|duration| {
+                turbo_tasks::macro_helpers::warn_slow_execution(
+                    concat!(module_path!(), "::", stringify!(#ident)),
+                    duration,
+                    #threshold,
+                )
+            });
+        })
+    }
+
+    pub fn persistence(&self) -> impl ToTokens {
+        if self.local_cells {
+            quote! {
+                turbo_tasks::TaskPersistence::LocalCells
+            }
+        } else {
+            quote! {
+                turbo_tasks::macro_helpers::get_non_local_persistence_from_inputs(&*inputs)
+            }
+        }
+    }
+
+    pub fn persistence_with_this(&self) -> impl ToTokens {
+        if self.local_cells {
+            quote! {
+                turbo_tasks::TaskPersistence::LocalCells
+            }
+        } else {
+            quote! {
+                turbo_tasks::macro_helpers::get_non_local_persistence_from_inputs_and_this(this, &*inputs)
+            }
+        }
+    }
+
+    fn converted_this(&self) -> Option<Expr> {
+        self.this.as_ref().map(|Input { ident, .. }| {
+            parse_quote! {
+                turbo_tasks::Vc::into_raw(#ident)
+            }
+        })
+    }
+
+    fn get_assertions(&self) -> TokenStream {
+        let returns_resolved_value = self.resolved.map(|span| {
+            let return_type = &self.output;
+            quote_spanned! {
+                span =>
+                {
+                    turbo_tasks::macro_helpers::assert_returns_resolved_value::<#return_type, _>()
+                }
+            }
+        });
+        let self_is_value_type = self.value_receiver.map(|span| {
+            quote_spanned! {
+                span =>
+                {
+                    turbo_tasks::macro_helpers::assert_self_is_value_type::<Self>()
+                }
+            }
+        });
+        // The exposed signature accepts the approximation of each argument's task input type
+        // computed by `expand_task_input_type`. Checking it against the conversion reports a
+        // mismatch at the argument, instead of deep inside of the inline function.
+        let task_input_types: Vec<_> = self
+            .inputs
+            .iter()
+            .filter(|input| {
+                !self.direct_from_task_input
+                    && !input.flatten
+                    && !input.map_value
+                    && !is_cow_str_type(&input.ty)
+                    && owned_borrowed_type(&input.ty).is_none()
+                    && ImplTraitArgument::parse(&input.ty).is_none()
+            })
+            .filter_map(|Input { ty, .. }| {
+                let Cow::Owned(exposed_ty) = expand_task_input_type(ty) else {
+                    return None;
+                };
+                let from_task_input = self.from_task_input_path(ty);
+                Some(quote_spanned! {
+                    ty.span() =>
+                    let _: fn(#exposed_ty) -> #ty = #from_task_input;
+                })
+            })
+            .collect();
+        let task_input_types = (!task_input_types.is_empty()).then(|| {
+            quote! {
+                {
+                    #[allow(unused_imports)]
+                    use turbo_tasks::macro_helpers::AutoFromTaskInputIdentity as _;
+                    #(#task_input_types)*
+                }
+            }
+        });
+        let operation_values = self.operation.then(|| {
+            let assertions = self.inputs.iter().map(|Input { ty, .. }| {
+                // an `impl Trait` can't be named, the converted task input is checked instead
+                let checked_ty = match ImplTraitArgument::parse(ty) {
+                    Some(impl_trait) => Cow::Owned(impl_trait.task_input_type()),
+                    None => Cow::Borrowed(ty),
+                };
+                quote_spanned! {
+                    ty.span() =>
+                    turbo_tasks::macro_helpers::assert_argument_is_operation_value::<#checked_ty>();
+                }
+            });
+            quote! {
+                {
+                    #(#assertions)*
+                }
+            }
+        });
+        quote! {
+            #returns_resolved_value
+            #self_is_value_type
+            #task_input_types
+            #operation_values
+        }
+    }
+
+    /// Enters a span named by the user-provided `name_fn`, if any. Must be placed after `inputs`
+    /// is defined, and is held until the end of the enclosing block.
+    fn get_dispatch_span(&self) -> TokenStream {
+        let span_fields: Vec<_> = self
+            .inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, input)| {
+                let level = match (&input.span, &input.trace_as) {
+                    (Some(level), _) => *level,
+                    (None, Some(_)) => None,
+                    (None, None) => return None,
+                };
+                Some((syn::Index::from(index), input, level))
+            })
+            .collect();
+        if self.name_fn.is_none() && self.trace_level.is_none() && span_fields.is_empty() {
+            return quote! {};
+        }
+
+        let level = self.trace_level.unwrap_or(TraceLevel::Trace);
+        let name = match &self.name_fn {
+            Some(name_fn) => quote_spanned! { name_fn.span() => #name_fn(&*inputs).as_str() },
+            None => match &self.name {
+                Some(name) => quote! { #name },
+                None => {
+                    let ident = &self.ident;
+                    quote! { stringify!(#ident) }
+                }
+            },
+        };
+        let field_idents = span_fields.iter().map(|(_, input, _)| &input.ident);
+        // Fields are recorded separately, so that fields with a more verbose level than the span
+        // are skipped unless that level is enabled.
+        let record_fields = span_fields.iter().map(|(index, input, field_level)| {
+            let field_ident = &input.ident;
+            let field_level = field_level.unwrap_or(level);
+            let unwrap_fields = input.unwrap_fields();
+            let value = match &input.trace_as {
+                Some(trace_as) => quote_spanned! { trace_as.span() =>
+                    {
+                        #[allow(unused_variables)]
+                        let #field_ident = &inputs.#index #(.#unwrap_fields)*;
+                        #trace_as
+                    }
+                },
+                None => quote! { inputs.#index },
+            };
+            quote! {
+                if turbo_tasks::macro_helpers::tracing::enabled!(#field_level) {
+                    _span.record(
+                        stringify!(#field_ident),
+                        turbo_tasks::macro_helpers::tracing::field::debug(&#value),
+                    );
+                }
+            }
+        });
+        quote! {
+            let _span = turbo_tasks::macro_helpers::tracing::span!(
+                #level,
+                "turbo_tasks::function_call",
+                name = #name,
+                #(#field_idents = turbo_tasks::macro_helpers::tracing::field::Empty,)*
+            )
+            .entered();
+            #(#record_fields)*
         }
     }
 
     /// The block of the exposed function for a dynamic dispatch call to the
     /// given trait.
-    pub fn dynamic_block(&self, trait_type_id_ident: &Ident) -> Block {
+    ///
+    /// Associated functions without `self` have no value to dispatch on, so when called on a
+    /// trait object (`<Box<dyn Trait>>::new(..)`), they're dispatched statically to the trait's
+    /// default implementation. Without one, they can't be called on a trait object, see
+    /// [`Self::value_type_bound`].
+    pub fn dynamic_block(
+        &self,
+        trait_type_id_ident: &Ident,
+        default_function_id_ident: Option<&Ident>,
+    ) -> Block {
         let Some(converted_this) = self.converted_this() else {
+            if let Some(default_function_id_ident) = default_function_id_ident {
+                return self.static_block(default_function_id_ident);
+            }
+            let assertions = self.get_assertions();
             return parse_quote! {
                 {
-                    unimplemented!("trait methods without self are not yet supported")
+                    #assertions
+                    unreachable!("trait objects aren't value types")
                 }
             };
         };
@@ -372,12 +1930,14 @@ impl TurboFn {
         let ident = &self.ident;
         let output = &self.output;
         let assertions = self.get_assertions();
-        let inputs = self.input_idents();
+        let inputs = self.input_values();
         let persistence = self.persistence_with_this();
+        let dispatch_span = self.get_dispatch_span();
         parse_quote! {
             {
                 #assertions
                 let inputs = std::boxed::Box::new((#(#inputs,)*));
+                #dispatch_span
                 let this = #converted_this;
                 let persistence = #persistence;
                 <#output as turbo_tasks::task::TaskOutput>::try_from_raw_vc(
@@ -394,19 +1954,72 @@ impl TurboFn {
     }
 
     /// The block of the exposed function for a static dispatch call to the
-    /// given native function.
+    /// given native function. With `resolve_return`, the returned `Vc` is resolved. With
+    /// `operation`, it's wrapped in an `OperationVc`.
     pub fn static_block(&self, native_function_id_ident: &Ident) -> Block {
-        let output = &self.output;
-        let inputs = self.input_idents();
-        let assertions = self.get_assertions();
-        if let Some(converted_this) = self.converted_this() {
-            let persistence = self.persistence_with_this();
-            parse_quote! {
+        let dispatch_block = self.static_dispatch_block(native_function_id_ident);
+        if self.operation {
+            // `operation` can't be combined with `resolve_return`, so this is the task's output
+            return parse_quote! {
                 {
-                    #assertions
-                    let inputs = std::boxed::Box::new((#(#inputs,)*));
-                    let this = #converted_this;
-                    let persistence = #persistence;
+                    turbo_tasks::OperationVc::new_private(#dispatch_block)
+                }
+            };
+        }
+        if !self.resolve_return {
+            return dispatch_block;
+        }
+        // The dispatch span isn't `Send`, so it's dropped with the dispatch block before awaiting
+        parse_quote! {
+            {
+                let vc = #dispatch_block;
+                turbo_tasks::Vc::resolve(vc).await
+            }
+        }
+    }
+
+    /// The block of the exposed function for a `no_cache` function, if it is one: the body runs
+    /// directly in the calling task, so nothing is cached and no task is created. With
+    /// `resolve_return`, the returned `Vc` is resolved.
+    pub fn no_cache_block(&self, inline_function_ident: &Ident) -> Option<Block> {
+        if !self.no_cache {
+            return None;
+        }
+        let assertions = self.get_assertions();
+        let call_count_increment = self.call_count_increment();
+        let direct_call_block = self.direct_call_block(inline_function_ident);
+        let resolve = self.resolve_return.then(|| {
+            quote! {
+                let vc = turbo_tasks::Vc::resolve(vc).await?;
+            }
+        });
+        Some(parse_quote! {
+            {
+                #assertions
+                #call_count_increment
+                let vc = #direct_call_block;
+                #resolve
+                Ok(vc)
+            }
+        })
+    }
+
+    fn static_dispatch_block(&self, native_function_id_ident: &Ident) -> Block {
+        let output = &self.output;
+        let inputs = self.input_values();
+        let assertions = self.get_assertions();
+        let dispatch_span = self.get_dispatch_span();
+        let call_count_increment = self.call_count_increment();
+        if let Some(converted_this) = self.converted_this() {
+            let persistence = self.persistence_with_this();
+            parse_quote! {
+                {
+                    #assertions
+                    let inputs = std::boxed::Box::new((#(#inputs,)*));
+                    #dispatch_span
+                    #call_count_increment
+                    let this = #converted_this;
+                    let persistence = #persistence;
                     <#output as turbo_tasks::task::TaskOutput>::try_from_raw_vc(
                         turbo_tasks::dynamic_this_call(
                             *#native_function_id_ident,
@@ -423,6 +2036,8 @@ impl TurboFn {
                 {
                     #assertions
                     let inputs = std::boxed::Box::new((#(#inputs,)*));
+                    #dispatch_span
+                    #call_count_increment
                     let persistence = #persistence;
                     <#output as turbo_tasks::task::TaskOutput>::try_from_raw_vc(
                         turbo_tasks::dynamic_call(
@@ -436,13 +2051,274 @@ impl TurboFn {
         }
     }
 
+    pub fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    /// Increments the call counter, if `count_calls` is set.
+    fn call_count_increment(&self) -> Option<TokenStream> {
+        if !self.count_calls {
+            return None;
+        }
+        let call_count_ident =
+            get_function_call_count_ident(&get_native_function_ident(&self.ident));
+        Some(quote! {
+            #call_count_ident.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed);
+        })
+    }
+
+    /// The visibility of the generated statics and metadata constants, see `static_vis`.
+    pub fn static_vis(&self) -> Visibility {
+        self.static_vis
+            .clone()
+            .unwrap_or_else(|| parse_quote! { pub(crate) })
+    }
+
+    /// The name the native function is registered with for traces: the custom `name`, or
+    /// `default` (derived from the function's path) if unset.
+    pub fn native_function_name(&self, default: String) -> String {
+        match &self.name {
+            Some(name) => name.value(),
+            None => default,
+        }
+    }
+
+    /// The module path recorded for the function, see `module_path`.
+    pub fn module_path(&self) -> TokenStream {
+        match &self.module_path {
+            Some(path) => quote! { #path },
+            None => quote! { module_path!() },
+        }
+    }
+
+    /// The call counter static and the `<name>_call_count()` function reading it, if
+    /// `count_calls` is set.
+    pub fn call_count_definitions(&self, vis: &Visibility) -> Option<TokenStream> {
+        if !self.count_calls {
+            return None;
+        }
+        let ident = &self.ident;
+        let call_count_ident = get_function_call_count_ident(&get_native_function_ident(ident));
+        let call_count_fn_ident = format_ident!("{}_call_count", ident);
+        let static_vis = self.static_vis();
+        Some(quote! {
+            #[doc(hidden)]
+            #static_vis static #call_count_ident: ::std::sync::atomic::AtomicU64 =
+                ::std::sync::atomic::AtomicU64::new(0);
+
+            /// The number of times the exposed function was called.
+            #vis fn #call_count_fn_ident() -> u64 {
+                #call_count_ident.load(::std::sync::atomic::Ordering::Relaxed)
+            }
+        })
+    }
+
+    /// The `<name>_invalidator()` function, if `invalidation = manual` is set. It takes the same
+    /// arguments as the exposed function, and returns an `Invalidator` for the task of that call,
+    /// so that it can be invalidated without the task stashing an invalidator itself.
+    pub fn invalidator_definition(
+        &self,
+        vis: &Visibility,
+        native_function_id_ident: &Ident,
+    ) -> Option<TokenStream> {
+        if !self.manual_invalidation {
+            return None;
+        }
+        let mut signature = self.signature();
+        signature.ident = format_ident!("{}_invalidator", self.ident);
+        signature.asyncness = Some(Default::default());
+        signature.output = parse_quote! { -> turbo_tasks::Result<turbo_tasks::Invalidator> };
+        let inputs = self.input_values();
+        let persistence = self.persistence();
+        Some(quote! {
+            /// An invalidator for the task of calling the exposed function with these arguments.
+            #vis #signature {
+                // Like a call, the task is looked up by the resolved arguments
+                let inputs = std::boxed::Box::new(
+                    turbo_tasks::TaskInput::resolve(&(#(#inputs,)*)).await?,
+                );
+                let persistence = #persistence;
+                Ok(turbo_tasks::macro_helpers::task_invalidator(
+                    *#native_function_id_ident,
+                    inputs as std::boxed::Box<dyn turbo_tasks::MagicAny>,
+                    persistence,
+                ))
+            }
+        })
+    }
+
+    /// The `<name>_untracked()` function, if `also_untracked` is set. It takes the same arguments
+    /// as the exposed function, and runs the body directly, without creating or scheduling a
+    /// task. Its result isn't cached, and reads within the body aren't tracked for it.
+    pub fn untracked_definition(
+        &self,
+        vis: &Visibility,
+        inline_function_ident: &Ident,
+    ) -> Option<TokenStream> {
+        if !self.also_untracked {
+            return None;
+        }
+        let new_output = expand_vc_return_type(&self.output);
+        let mut signature = self.signature();
+        signature.ident = format_ident!("{}_untracked", self.ident);
+        signature.asyncness = Some(Default::default());
+        signature.output = parse_quote! { -> turbo_tasks::Result<#new_output> };
+        let direct_call_block = self.direct_call_block(inline_function_ident);
+        Some(quote! {
+            /// Runs the body of the exposed function directly, bypassing the task system.
+            ///
+            /// This must still be called from within a turbo-tasks context (e.g. another
+            /// task), as the arguments are resolved first, and any cells the body creates
+            /// belong to the calling task.
+            #vis #signature {
+                Ok(#direct_call_block)
+            }
+        })
+    }
+
+    /// A block calling the inline function directly, in the current task. The arguments are
+    /// resolved first, like the inputs of a task, and the output is converted like a task's
+    /// output, so the block evaluates to the exposed function's return type. Errors are returned
+    /// from the enclosing function with `?`.
+    fn direct_call_block(&self, inline_function_ident: &Ident) -> Block {
+        let output = &self.output;
+        let input_idents: Vec<_> = self.input_idents().collect();
+        let inputs = self.input_values();
+        let call = quote! { #inline_function_ident(#(#input_idents),*) };
+        let call = if self.orig_signature.asyncness.is_some() {
+            quote! { #call.await }
+        } else {
+            call
+        };
+        parse_quote! {
+            {
+                let (#(#input_idents,)*) =
+                    turbo_tasks::TaskInput::resolve(&(#(#inputs,)*)).await?;
+                let output = #call;
+                <#output as turbo_tasks::task::TaskOutput>::try_from_raw_vc(
+                    turbo_tasks::task::TaskOutput::try_into_raw_vc(output)?,
+                )
+            }
+        }
+    }
+
+    /// With `track_duration`, a guard that records the time until the end of the current scope as
+    /// the duration of the last execution.
+    fn last_duration_guard(&self) -> Option<TokenStream> {
+        if !self.track_duration {
+            return None;
+        }
+        let last_duration_ident =
+            get_function_last_duration_ident(&get_native_function_ident(&self.ident));
+        Some(quote! {
+            let _last_duration_guard = turbo_tasks::duration_span::DurationSpanGuard::new(|duration| {
+                #last_duration_ident.store(duration, ::std::sync::atomic::Ordering::Relaxed);
+            });
+        })
+    }
+
+    /// The last duration static and the `<name>_last_duration()` function reading it, if
+    /// `track_duration` is set.
+    pub fn last_duration_definitions(&self, vis: &Visibility) -> Option<TokenStream> {
+        if !self.track_duration {
+            return None;
+        }
+        let ident = &self.ident;
+        let last_duration_ident =
+            get_function_last_duration_ident(&get_native_function_ident(ident));
+        let last_duration_fn_ident = format_ident!("{}_last_duration", ident);
+        let static_vis = self.static_vis();
+        Some(quote! {
+            #[doc(hidden)]
+            #static_vis static #last_duration_ident: ::std::sync::atomic::AtomicU64 =
+                ::std::sync::atomic::AtomicU64::new(0);
+
+            /// The duration of the body's last execution, or zero if it wasn't executed yet.
+            #vis fn #last_duration_fn_ident() -> ::std::time::Duration {
+                ::std::time::Duration::from_micros(
+                    #last_duration_ident.load(::std::sync::atomic::Ordering::Relaxed),
+                )
+            }
+        })
+    }
+
+    /// The generic type parameter and instantiations of a `monomorphize`d function.
+    pub fn monomorphize(&self) -> Option<&Monomorphize> {
+        self.monomorphize.as_ref()
+    }
+
+    /// Like [`Self::static_block`], but for a `monomorphize`d function: the function id of the
+    /// instantiation is looked up through the marker trait implemented for each type.
+    pub fn monomorphized_static_block(&self, marker_ident: &Ident) -> Block {
+        let Some(Monomorphize { param, .. }) = &self.monomorphize else {
+            unreachable!("the function is not monomorphized");
+        };
+        let param_ident = &param.ident;
+        let function_id_ident = Ident::new("monomorphized_function_id", Span::call_site());
+        let static_block = self.static_block(&function_id_ident);
+        parse_quote! {
+            {
+                let #function_id_ident = <#param_ident as #marker_ident>::function_id();
+                #static_block
+            }
+        }
+    }
+
+    /// A `#[turbo_tasks::test]` calling the exposed function with `Default` inputs, if
+    /// `gen_smoke_test` is set. The test fails if the call panics or returns an error. The
+    /// assertions that the inputs implement `Default` are emitted outside of the test, so that
+    /// they're checked in every build.
+    pub fn smoke_test(&self) -> Option<TokenStream> {
+        let register = self.gen_smoke_test.as_ref()?;
+        let ident = &self.ident;
+        let smoke_test_ident = format_ident!("{}_smoke_test", ident);
+        let input_types: Vec<_> = self
+            .signature()
+            .inputs
+            .into_iter()
+            .filter_map(|arg| match arg {
+                FnArg::Typed(PatType { ty, .. }) => Some(ty),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let assertions = input_types.iter().map(|ty| {
+            quote_spanned! {
+                ty.span() =>
+                const _: () = {
+                    fn assert_default<T: ::std::default::Default>() {}
+                    let _ = assert_default::<#ty>;
+                };
+            }
+        });
+        let call = quote! { #ident(#(<#input_types as ::std::default::Default>::default()),*) };
+        // with `resolve_return`, the exposed function resolves the `Vc` already
+        let call = if self.resolve_return {
+            call
+        } else if self.operation {
+            quote! { #call.resolve_strongly_consistent() }
+        } else {
+            quote! { #call.resolve() }
+        };
+        Some(quote! {
+            #(#assertions)*
+
+            #[cfg(test)]
+            #[turbo_tasks::test(register = #register)]
+            async fn #smoke_test_ident() {
+                if let Err(err) = #call.await {
+                    panic!("smoke test of `{}` failed: {:?}", stringify!(#ident), err);
+                }
+            }
+        })
+    }
+
     pub(crate) fn is_method(&self) -> bool {
         self.this.is_some()
     }
 }
 
-/// An indication of what kind of IO this function does. Currently only used for
-/// static analysis, and ignored within this macro.
+/// An indication of what kind of IO this function does. It's used by static analysis, and recorded
+/// as `turbo_tasks::IoMarkers` in `FunctionMeta`.
 #[derive(Hash, PartialEq, Eq)]
 enum IoMarker {
     Filesystem,
@@ -484,80 +2360,1593 @@ impl<T: Parse> Parse for MaybeParenthesized<T> {
             } else {
                 None
             },
-        })
+        })
+    }
+}
+
+/// A single entry in the arguments of the `#[turbo_tasks::function]` macro.
+///
+/// This is similar to [`syn::Meta`], but the value of a `name = value` pair may be any expression
+/// (e.g. a path to a function), not just a literal.
+enum FunctionArgument {
+    Path(Path),
+    NameValue(Path, Token![=], Expr),
+    List(Path, Paren, Punctuated<FunctionArgument, Token![,]>),
+}
+
+impl FunctionArgument {
+    fn path(&self) -> &Path {
+        match self {
+            FunctionArgument::Path(path)
+            | FunctionArgument::NameValue(path, _, _)
+            | FunctionArgument::List(path, _, _) => path,
+        }
+    }
+}
+
+impl Parse for FunctionArgument {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.call(Path::parse_mod_style)?;
+        if input.peek(Paren) {
+            let content;
+            Ok(FunctionArgument::List(
+                path,
+                parenthesized!(content in input),
+                content.parse_terminated(FunctionArgument::parse)?,
+            ))
+        } else if input.peek(Token![=]) {
+            Ok(FunctionArgument::NameValue(
+                path,
+                input.parse()?,
+                input.parse()?,
+            ))
+        } else {
+            Ok(FunctionArgument::Path(path))
+        }
+    }
+}
+
+impl ToTokens for FunctionArgument {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            FunctionArgument::Path(path) => path.to_tokens(tokens),
+            FunctionArgument::NameValue(path, eq_token, value) => {
+                path.to_tokens(tokens);
+                eq_token.to_tokens(tokens);
+                value.to_tokens(tokens);
+            }
+            FunctionArgument::List(path, paren_token, nested) => {
+                path.to_tokens(tokens);
+                paren_token.surround(tokens, |tokens| nested.to_tokens(tokens));
+            }
+        }
+    }
+}
+
+/// The arguments of `retry(count = 3, on = predicate)`.
+#[derive(Debug)]
+pub struct RetryArguments {
+    /// The maximum number of retries after the first attempt.
+    count: LitInt,
+    /// A `fn(&anyhow::Error) -> bool` deciding if an error should be retried. All errors are
+    /// retried if this isn't set.
+    on: Option<ExprPath>,
+    /// The span of the `retry(...)` argument, used for errors.
+    span: Span,
+}
+
+impl RetryArguments {
+    fn parse_nested(
+        meta: &FunctionArgument,
+        nested: &Punctuated<FunctionArgument, Token![,]>,
+    ) -> syn::Result<Self> {
+        let mut count = None;
+        let mut on = None;
+        for nested_meta in nested {
+            match (
+                nested_meta
+                    .path()
+                    .get_ident()
+                    .map(ToString::to_string)
+                    .as_deref()
+                    .unwrap_or_default(),
+                nested_meta,
+            ) {
+                (
+                    "count",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Int(lit), ..
+                        }),
+                    ),
+                ) => {
+                    lit.base10_parse::<u32>()?;
+                    count = Some(lit.clone());
+                }
+                ("on", FunctionArgument::NameValue(_, _, Expr::Path(path))) => {
+                    on = Some(path.clone());
+                }
+                (_, nested_meta) => {
+                    return Err(syn::Error::new_spanned(
+                        nested_meta,
+                        "unexpected token, expected one of: \"count\", \"on\"",
+                    ))
+                }
+            }
+        }
+        let Some(count) = count else {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "\"retry\" requires a \"count\", e.g. `retry(count = 3)`",
+            ));
+        };
+        Ok(RetryArguments {
+            count,
+            on,
+            span: meta.span(),
+        })
+    }
+}
+
+/// Arguments to the `#[turbo_tasks::function]` macro.
+#[derive(Default)]
+pub struct FunctionArguments {
+    /// Manually annotated metadata about what kind of IO this function does. It's used by some
+    /// static analysis tools, and recorded in `FunctionMeta` so backends and tracing can see which
+    /// tasks perform IO.
+    ///
+    /// This should only be used by the task that directly performs the IO. Tasks that transitively
+    /// perform IO should not be manually annotated.
+    io_markers: HashSet<IoMarker>,
+    /// Should we check that the return type contains a `ResolvedValue`?
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    ///
+    /// If [`Self::local_cells`] is set, this will also be set to the same span.
+    resolved: Option<Span>,
+    /// Changes the behavior of `Vc::cell` to create local cells that are not cached across task
+    /// executions. Cells can be converted to their non-local versions by calling `Vc::resolve`.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    ///
+    /// Setting this option will also set [`Self::resolved`] to the same span.
+    pub local_cells: Option<Span>,
+    /// A function called with a reference to the task's inputs tuple, returning the `String` name
+    /// used for the span around each call of this function.
+    name_fn: Option<ExprPath>,
+    /// The level of the span around each call of this function, e.g. `trace_level = "debug"`.
+    /// Defaults to `"trace"`. Arguments marked `#[turbo_tasks(span)]` are recorded as fields of
+    /// this span.
+    trace_level: Option<TraceLevel>,
+    /// A type implementing [`std::hash::Hasher`] and [`Default`]. If set, the task's inputs are
+    /// hashed with a fresh instance of this hasher, and only the resulting digest is used as part
+    /// of the task cache key.
+    hasher: Option<Type>,
+    /// Marks the exposed function as `#[deprecated]`, with an optional string note.
+    deprecated: Option<Option<LitStr>>,
+    /// A path to a function with the same signature. If the function's body is empty (`{}`), the
+    /// body is replaced with a call forwarding every argument to this function. Requires
+    /// `deprecated`.
+    replacement: Option<ExprPath>,
+    /// A path to a type (usually an alias) that rustdoc shows as the exposed function's return
+    /// type. The real return type is unchanged.
+    doc_return: Option<Type>,
+    /// Converts arguments with `<T as FromTaskInput>::from_task_input` directly, and accepts
+    /// `<T as FromTaskInput>::TaskInput` in the exposed function. This skips the
+    /// `AutoFromTaskInput` specialization, which may pick the identity conversion for types
+    /// with their own `FromTaskInput` implementation. Every argument type must implement
+    /// `FromTaskInput`.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    direct_from_task_input: Option<Span>,
+    /// Warns about argument types containing `std::path::PathBuf` or `std::path::Path`. These
+    /// usually hold absolute paths, which make the task cache non-portable across machines.
+    portable_inputs: Option<Span>,
+    /// Resolves the inputs that contain a `Vc` concurrently, instead of one after another.
+    /// Other inputs are only resolved (sequentially) if they aren't already resolved.
+    parallel_inputs: Option<Span>,
+    /// Checks whether the task was cancelled (i.e. turbo-tasks was stopped) before each input is
+    /// resolved, returning an error instead of resolving the remaining inputs.
+    cancellable: Option<Span>,
+    /// Times the resolution of the inputs and the execution of the body separately, and reports
+    /// both to the hook set with `turbo_tasks::profile::set_profile_hook`.
+    profile: Option<Span>,
+    /// Marks a function whose calls are operations, i.e. side-effecting tasks. The exposed
+    /// function returns an `OperationVc<T>` instead of a `Vc<T>`, and all inputs must implement
+    /// `OperationValue`.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    operation: Option<Span>,
+    /// Re-runs the body up to `count` more times when it returns an error, e.g. `retry(count =
+    /// 3)`. If a predicate is given with `on = path`, only errors for which it returns `true` are
+    /// retried. The inputs are cloned for each attempt.
+    retry: Option<RetryArguments>,
+    /// Asserts that the future returned by an async function's body is `Send`. Without this, a
+    /// non-`Send` body is only reported by the bounds of the function's registration, far from the
+    /// cause.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    assert_send: Option<Span>,
+    /// A path to another (non-method) `#[turbo_tasks::function]` whose tasks should preferably be
+    /// scheduled before this function's tasks, e.g. `schedule_after = prereq`. This is a soft
+    /// ordering hint that is recorded in the function's `FunctionMeta`.
+    schedule_after: Option<ExprPath>,
+    /// Instantiates the function's single generic type parameter with each of the listed types,
+    /// e.g. `monomorphize = [Foo, Bar]`. Every instantiation is registered as a separate native
+    /// function, and the exposed (generic) function dispatches to it. The bounds of the parameter
+    /// are checked for each type.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    monomorphize: Option<(Span, Vec<Type>)>,
+    /// Logs a warning when the body takes longer than the given duration, e.g. `warn_after =
+    /// "100ms"`. The units `us`, `ms` and `s` are supported. For async functions, this is the wall
+    /// time until the body completes, including the time spent waiting.
+    warn_after: Option<(Span, u64)>,
+    /// Generates a `#[cfg(test)]` smoke test calling the function with `Default` inputs, and
+    /// asserting that it doesn't panic (or return an error), e.g. `gen_smoke_test = register`.
+    /// The value is a function registering the tested functions, which is passed to
+    /// `#[turbo_tasks::test(register = ...)]`. Every input must implement `Default`. Only
+    /// supported on standalone functions without `monomorphize`, `no_cache` or `args_struct`.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    gen_smoke_test: Option<(Span, ExprPath)>,
+    /// Counts the calls of the exposed function in a static `AtomicU64`, which is read with a
+    /// generated `<name>_call_count()` function. Only supported on standalone functions.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    count_calls: Option<Span>,
+    /// Generates a `<name>_invalidator()` function with `invalidation = manual`. It takes the same
+    /// arguments as the exposed function, and returns an `Invalidator` for the task of that call.
+    /// It's async, as the arguments are resolved first, like for a call. This replaces stashing
+    /// the result of `get_invalidator()` from within the task. Only supported on standalone
+    /// functions.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    manual_invalidation: Option<Span>,
+    /// Generates an additional `<name>_untracked()` function, which takes the same arguments as
+    /// the exposed function and runs the body directly, bypassing the task system. Meant for
+    /// benchmarks and one-shot code paths that don't benefit from caching. It's async and returns
+    /// a `Result`, as the arguments are resolved first, so it still needs a turbo-tasks context.
+    /// Only supported on standalone functions.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    also_untracked: Option<Span>,
+    /// Records the duration of the body's last execution in a static `AtomicU64`, which is read
+    /// with a generated `<name>_last_duration()` function. Only supported on standalone
+    /// functions.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    track_duration: Option<Span>,
+    /// Packs the arguments into a generated `<Name>Args` struct, which the exposed function
+    /// accepts as its only (task) input. The struct has a builder, and the body still sees the
+    /// arguments as individual bindings. Only supported on standalone, non-generic functions.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    args_struct: Option<Span>,
+    /// The visibility of the statics (`<NAME>_FUNCTION` and `<NAME>_FUNCTION_ID`) and metadata
+    /// constants generated for the function, e.g. `static_vis = "pub"`. Defaults to `pub(crate)`,
+    /// which prevents re-exporting them from another crate. Only supported on standalone
+    /// functions.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    static_vis: Option<(Span, Visibility)>,
+    /// The module path recorded in the generated `<NAME>_FUNCTION_MODULE_PATH` constant, e.g.
+    /// `module_path = "my_crate::api"` for a function that's re-exported from there. Defaults to
+    /// the module the function is defined in. Only supported on standalone functions.
+    module_path: Option<LitStr>,
+    /// The name of the function in traces, e.g. `name = "resolve/esm"`. Defaults to the name
+    /// derived from the function's path.
+    name: Option<LitStr>,
+    /// A path to a function without arguments, e.g. `fallback = default_value`. When the body
+    /// returns an error (after any `retry`), the error is logged as a warning and the `Ok` value
+    /// returned by this function is used instead. Requires the function to return a `Result`.
+    fallback: Option<ExprPath>,
+    /// The version of the function's logic, e.g. `version = 2`. Authors bump it when the logic
+    /// changes, so results persisted by earlier versions are treated as stale. Defaults to `0`.
+    version: Option<u32>,
+    /// Marks a function as a pure reader, which returns existing cells without creating any. This
+    /// is recorded in `FunctionMeta`, and creating a cell in the task panics in debug builds.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    readonly: Option<Span>,
+    /// The name of a concurrency group, e.g. `concurrency_group = "fs"`. The group is recorded in
+    /// the function's `FunctionMeta`, so that the scheduler can bound the combined parallelism of
+    /// all functions in the group.
+    concurrency_group: Option<LitStr>,
+    /// Defers constructing the future of an async function's body until the task is first
+    /// polled. The body's future is then boxed separately, so a large future doesn't inflate the
+    /// future that's allocated when the task is scheduled.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    lazy_body: Option<Span>,
+    /// Makes the exposed function async, resolving the returned `Vc` before handing it back, for
+    /// callers that always need a resolved `Vc`. The exposed function returns a
+    /// `turbo_tasks::Result<Vc<T>>` then. Not supported on trait methods.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    resolve_return: Option<Span>,
+    /// Never caches the result: every call of the exposed function runs the body again, directly
+    /// in the calling task, e.g. for functions that return timestamps or random ids. The exposed
+    /// function is async and returns a `turbo_tasks::Result<Vc<T>>` then. Only supported on
+    /// standalone functions. `cache = false` is the same as `no_cache`.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    no_cache: Option<Span>,
+    /// Starts executing the function's tasks as soon as they're created, instead of when their
+    /// output is first read, to shorten the critical path of call graphs that are demanded
+    /// lazily. Recorded in `FunctionMeta`.
+    ///
+    /// If there is an error due to this option being set, it should be reported to this span.
+    eager: Option<Span>,
+    /// The scheduling priority of the function's tasks, `priority = high` or `priority = low`.
+    /// Recorded in `FunctionMeta`.
+    priority: Option<TaskPriority>,
+}
+
+impl FunctionArguments {
+    /// See [`Self::args_struct`], the arguments of a naked function are packed before a [`TurboFn`]
+    /// is created for it.
+    pub fn args_struct(&self) -> Option<Span> {
+        self.args_struct
+    }
+
+    /// The arguments of a method declared in a `#[turbo_tasks::value_trait]`. For a
+    /// `resolved` trait, every method checks that its return type contains a `ResolvedValue`,
+    /// which also covers implementations, as they must match the trait's signatures.
+    pub fn value_trait_method(resolved: Option<Span>) -> Self {
+        FunctionArguments {
+            resolved,
+            ..Default::default()
+        }
+    }
+}
+
+impl Parse for FunctionArguments {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut parsed_args = FunctionArguments::default();
+        let punctuated: Punctuated<FunctionArgument, Token![,]> =
+            input.parse_terminated(FunctionArgument::parse)?;
+        for meta in punctuated {
+            match (
+                meta.path()
+                    .get_ident()
+                    .map(ToString::to_string)
+                    .as_deref()
+                    .unwrap_or_default(),
+                &meta,
+            ) {
+                ("fs", FunctionArgument::Path(_)) => {
+                    parsed_args.io_markers.insert(IoMarker::Filesystem);
+                }
+                ("network", FunctionArgument::Path(_)) => {
+                    parsed_args.io_markers.insert(IoMarker::Network);
+                }
+                ("resolved", FunctionArgument::Path(_)) => {
+                    parsed_args.resolved = Some(meta.span());
+                }
+                ("local_cells", FunctionArgument::Path(_)) => {
+                    let span = Some(meta.span());
+                    parsed_args.local_cells = span;
+                    parsed_args.resolved = span;
+                }
+                ("name_fn", FunctionArgument::NameValue(_, _, Expr::Path(path))) => {
+                    parsed_args.name_fn = Some(path.clone());
+                }
+                (
+                    "trace_level",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(level),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    parsed_args.trace_level = Some(TraceLevel::parse_lit(level)?);
+                }
+                (
+                    "concurrency_group",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(group),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    if group.value().is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            group,
+                            "the name of a concurrency group must not be empty",
+                        ));
+                    }
+                    parsed_args.concurrency_group = Some(group.clone());
+                }
+                (
+                    "hasher",
+                    FunctionArgument::NameValue(_, _, Expr::Path(ExprPath { path, .. })),
+                ) => {
+                    parsed_args.hasher = Some(Type::Path(TypePath {
+                        qself: None,
+                        path: path.clone(),
+                    }));
+                }
+                ("deprecated", FunctionArgument::Path(_)) => {
+                    parsed_args.deprecated = Some(None);
+                }
+                (
+                    "deprecated",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(note),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    parsed_args.deprecated = Some(Some(note.clone()));
+                }
+                ("replacement", FunctionArgument::NameValue(_, _, Expr::Path(path))) => {
+                    parsed_args.replacement = Some(path.clone());
+                }
+                ("retry", FunctionArgument::List(_, _, nested)) => {
+                    parsed_args.retry = Some(RetryArguments::parse_nested(&meta, nested)?);
+                }
+                (
+                    "monomorphize",
+                    FunctionArgument::NameValue(_, _, Expr::Array(ExprArray { elems, .. })),
+                ) => {
+                    let types = elems
+                        .iter()
+                        .map(|elem| match elem {
+                            Expr::Path(ExprPath { path, .. }) => Ok(Type::Path(TypePath {
+                                qself: None,
+                                path: path.clone(),
+                            })),
+                            _ => Err(syn::Error::new_spanned(elem, "expected a type path")),
+                        })
+                        .collect::<syn::Result<Vec<_>>>()?;
+                    if types.is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            &meta,
+                            "\"monomorphize\" requires at least one type",
+                        ));
+                    }
+                    parsed_args.monomorphize = Some((meta.span(), types));
+                }
+                (
+                    "warn_after",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(duration),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    let Some(micros) = parse_duration_micros(&duration.value()) else {
+                        return Err(syn::Error::new_spanned(
+                            duration,
+                            "expected a duration like \"250us\", \"100ms\" or \"2s\"",
+                        ));
+                    };
+                    parsed_args.warn_after = Some((meta.span(), micros));
+                }
+                (
+                    "cache",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Bool(cache),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    if !cache.value {
+                        parsed_args.no_cache = Some(meta.span());
+                    }
+                }
+                (
+                    "version",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Int(version),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    parsed_args.version = Some(version.base10_parse::<u32>()?);
+                }
+                ("fallback", FunctionArgument::NameValue(_, _, Expr::Path(path))) => {
+                    parsed_args.fallback = Some(path.clone());
+                }
+                ("schedule_after", FunctionArgument::NameValue(_, _, Expr::Path(path))) => {
+                    parsed_args.schedule_after = Some(path.clone());
+                }
+                ("count_calls", FunctionArgument::Path(_)) => {
+                    parsed_args.count_calls = Some(meta.span());
+                }
+                (
+                    "invalidation",
+                    FunctionArgument::NameValue(_, _, Expr::Path(ExprPath { path, .. })),
+                ) if path.is_ident("manual") => {
+                    parsed_args.manual_invalidation = Some(meta.span());
+                }
+                ("invalidation", FunctionArgument::NameValue(_, _, value)) => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "unexpected token, expected \"manual\"",
+                    ));
+                }
+                ("also_untracked", FunctionArgument::Path(_)) => {
+                    parsed_args.also_untracked = Some(meta.span());
+                }
+                ("track_duration", FunctionArgument::Path(_)) => {
+                    parsed_args.track_duration = Some(meta.span());
+                }
+                (
+                    "static_vis",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(vis), ..
+                        }),
+                    ),
+                ) => {
+                    // the statics must be visible to the crate's registration, which lives at
+                    // the crate's root
+                    let parsed_vis =
+                        vis.parse::<Visibility>()
+                            .ok()
+                            .filter(|parsed_vis| match parsed_vis {
+                                Visibility::Public(_) => true,
+                                Visibility::Restricted(VisRestricted {
+                                    in_token: None,
+                                    path,
+                                    ..
+                                }) => path.is_ident("crate"),
+                                _ => false,
+                            });
+                    let Some(parsed_vis) = parsed_vis else {
+                        return Err(syn::Error::new_spanned(
+                            vis,
+                            "expected \"pub\" or \"pub(crate)\", the statics must be visible to \
+                             the crate's registration",
+                        ));
+                    };
+                    parsed_args.static_vis = Some((meta.span(), parsed_vis));
+                }
+                (
+                    "module_path",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(path),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    if path.value().is_empty() {
+                        return Err(syn::Error::new_spanned(
+                            path,
+                            "the module path must not be empty",
+                        ));
+                    }
+                    parsed_args.module_path = Some(path.clone());
+                }
+                (
+                    "name",
+                    FunctionArgument::NameValue(
+                        _,
+                        _,
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(name),
+                            ..
+                        }),
+                    ),
+                ) => {
+                    if name.value().is_empty() {
+                        return Err(syn::Error::new_spanned(name, "the name must not be empty"));
+                    }
+                    parsed_args.name = Some(name.clone());
+                }
+                ("gen_smoke_test", FunctionArgument::NameValue(_, _, Expr::Path(path))) => {
+                    parsed_args.gen_smoke_test = Some((meta.span(), path.clone()));
+                }
+                ("gen_smoke_test", FunctionArgument::Path(_)) => {
+                    return Err(syn::Error::new_spanned(
+                        &meta,
+                        "\"gen_smoke_test\" requires a function registering the tested functions, \
+                         e.g. `gen_smoke_test = register`",
+                    ));
+                }
+                ("assert_send", FunctionArgument::Path(_)) => {
+                    parsed_args.assert_send = Some(meta.span());
+                }
+                ("operation", FunctionArgument::Path(_)) => {
+                    parsed_args.operation = Some(meta.span());
+                }
+                ("profile", FunctionArgument::Path(_)) => {
+                    parsed_args.profile = Some(meta.span());
+                }
+                ("parallel_inputs", FunctionArgument::Path(_)) => {
+                    parsed_args.parallel_inputs = Some(meta.span());
+                }
+                ("resolve_return", FunctionArgument::Path(_)) => {
+                    parsed_args.resolve_return = Some(meta.span());
+                }
+                ("lazy_body", FunctionArgument::Path(_)) => {
+                    parsed_args.lazy_body = Some(meta.span());
+                }
+                ("no_cache", FunctionArgument::Path(_)) => {
+                    parsed_args.no_cache = Some(meta.span());
+                }
+                ("eager", FunctionArgument::Path(_)) => {
+                    parsed_args.eager = Some(meta.span());
+                }
+                ("priority", FunctionArgument::NameValue(_, _, value)) => {
+                    parsed_args.priority = Some(TaskPriority::parse_expr(value)?);
+                }
+                ("args_struct", FunctionArgument::Path(_)) => {
+                    parsed_args.args_struct = Some(meta.span());
+                }
+                ("readonly", FunctionArgument::Path(_)) => {
+                    parsed_args.readonly = Some(meta.span());
+                }
+                ("cancellable", FunctionArgument::Path(_)) => {
+                    parsed_args.cancellable = Some(meta.span());
+                }
+                ("portable_inputs", FunctionArgument::Path(_)) => {
+                    parsed_args.portable_inputs = Some(meta.span());
+                }
+                ("direct_from_task_input", FunctionArgument::Path(_)) => {
+                    parsed_args.direct_from_task_input = Some(meta.span());
+                }
+                (
+                    "doc_return",
+                    FunctionArgument::NameValue(_, _, Expr::Path(ExprPath { path, .. })),
+                ) => {
+                    parsed_args.doc_return = Some(Type::Path(TypePath {
+                        qself: None,
+                        path: path.clone(),
+                    }));
+                }
+                (_, meta) => {
+                    return Err(syn::Error::new_spanned(
+                        meta,
+                        "unexpected token, expected one of: \"fs\", \"network\", \"resolved\", \
+                         \"local_cells\", \"name_fn\", \"hasher\", \"deprecated\", \
+                         \"replacement\", \"doc_return\", \"direct_from_task_input\", \
+                         \"portable_inputs\", \"parallel_inputs\", \"profile\", \"operation\", \
+                         \"retry\", \"assert_send\", \"schedule_after\", \"monomorphize\", \
+                         \"warn_after\", \"cache\", \"gen_smoke_test\", \"count_calls\", \
+                         \"fallback\", \"trace_level\", \"cancellable\", \"version\", \
+                         \"readonly\", \"track_duration\", \"concurrency_group\", \"lazy_body\", \
+                         \"resolve_return\", \"no_cache\", \"eager\", \"args_struct\", \
+                         \"invalidation\", \"static_vis\", \"module_path\", \"also_untracked\", \
+                         \"name\", \"priority\"",
+                    ))
+                }
+            }
+        }
+        if let (Some(operation), Some(_)) = (parsed_args.operation, parsed_args.local_cells) {
+            return Err(syn::Error::new(
+                operation,
+                "\"operation\" and \"local_cells\" cannot be used together: local cells are \
+                 discarded when the task finishes, but an operation's cells must outlive it",
+            ));
+        }
+        if let (Some(operation), Some(_)) = (parsed_args.operation, parsed_args.resolve_return) {
+            return Err(syn::Error::new(
+                operation,
+                "\"operation\" and \"resolve_return\" cannot be used together: the exposed \
+                 function returns the unresolved output of the operation's task",
+            ));
+        }
+        if let (Some(readonly), Some(_)) = (parsed_args.readonly, parsed_args.local_cells) {
+            return Err(syn::Error::new(
+                readonly,
+                "\"readonly\" and \"local_cells\" cannot be used together: local cells only \
+                 change how the cells created by the task are stored, but a readonly task doesn't \
+                 create cells",
+            ));
+        }
+        if let (Some(no_cache), Some(_)) = (parsed_args.no_cache, parsed_args.local_cells) {
+            return Err(syn::Error::new(
+                no_cache,
+                "\"no_cache\" and \"local_cells\" cannot be used together: the body of a \
+                 \"no_cache\" function runs in the calling task, which decides how cells are \
+                 stored",
+            ));
+        }
+        if let (Some(manual_invalidation), Some(_)) =
+            (parsed_args.manual_invalidation, parsed_args.local_cells)
+        {
+            return Err(syn::Error::new(
+                manual_invalidation,
+                "\"invalidation = manual\" and \"local_cells\" cannot be used together: tasks \
+                 with local cells can't be looked up outside of their parent task",
+            ));
+        }
+        if let (Some(replacement), None) = (&parsed_args.replacement, &parsed_args.deprecated) {
+            return Err(syn::Error::new_spanned(
+                replacement,
+                "\"replacement\" can only be used together with \"deprecated\"",
+            ));
+        }
+        Ok(parsed_args)
+    }
+}
+
+/// Reports a `self` argument of a naked function. The function isn't emitted at all in that case,
+/// so the compiler wouldn't report the receiver on its own.
+fn emit_naked_fn_receiver_error(span: Span, definition_context: &DefinitionContext) {
+    span.error(format!(
+        "{} cannot take self, as they're not associated with a type",
+        definition_context.function_type(),
+    ))
+    .help("define methods in a #[turbo_tasks::value_impl] block instead")
+    .emit();
+}
+
+/// Returns the span of the first rest (`..`) or slice pattern within `pat`, if any.
+fn find_rest_pattern(pat: &Pat) -> Option<Span> {
+    match pat {
+        Pat::Rest(_) | Pat::Slice(_) => Some(pat.span()),
+        Pat::Struct(PatStruct {
+            dot2_token: Some(dot2_token),
+            ..
+        }) => Some(dot2_token.span()),
+        Pat::Struct(PatStruct { fields, .. }) => fields
+            .iter()
+            .find_map(|field| find_rest_pattern(&field.pat)),
+        Pat::Tuple(PatTuple { elems, .. })
+        | Pat::TupleStruct(PatTupleStruct {
+            pat: PatTuple { elems, .. },
+            ..
+        }) => elems.iter().find_map(find_rest_pattern),
+        Pat::Ident(PatIdent {
+            subpat: Some((_, subpat)),
+            ..
+        })
+        | Pat::Type(PatType { pat: subpat, .. })
+        | Pat::Reference(PatReference { pat: subpat, .. })
+        | Pat::Box(PatBox { pat: subpat, .. }) => find_rest_pattern(subpat),
+        _ => None,
+    }
+}
+
+/// Collects the names of all the bindings within `pat`.
+pub fn collect_bound_idents(pat: &Pat, idents: &mut HashSet<String>) {
+    match pat {
+        Pat::Ident(PatIdent { ident, subpat, .. }) => {
+            idents.insert(ident.unraw().to_string());
+            if let Some((_, subpat)) = subpat {
+                collect_bound_idents(subpat, idents);
+            }
+        }
+        Pat::Struct(PatStruct { fields, .. }) => {
+            for field in fields {
+                collect_bound_idents(&field.pat, idents);
+            }
+        }
+        Pat::Tuple(PatTuple { elems, .. })
+        | Pat::TupleStruct(PatTupleStruct {
+            pat: PatTuple { elems, .. },
+            ..
+        }) => {
+            for elem in elems {
+                collect_bound_idents(elem, idents);
+            }
+        }
+        Pat::Type(PatType { pat: subpat, .. })
+        | Pat::Reference(PatReference { pat: subpat, .. })
+        | Pat::Box(PatBox { pat: subpat, .. }) => collect_bound_idents(subpat, idents),
+        _ => {}
+    }
+}
+
+/// The name of the argument at `position` (starting at 1) that's bound by the pattern `pat`, e.g.
+/// `arg2`. It's prefixed with underscores until it doesn't clash with the `bound_idents`.
+pub fn pattern_argument_ident(pat: &Pat, position: usize, bound_idents: &HashSet<String>) -> Ident {
+    let mut name = format!("arg{position}");
+    while bound_idents.contains(&name) {
+        name.insert(0, '_');
+    }
+    Ident::new(&name, pat.span())
+}
+
+/// Parses a duration like `100ms` into microseconds.
+fn parse_duration_micros(duration: &str) -> Option<u64> {
+    let (value, factor) = if let Some(value) = duration.strip_suffix("us") {
+        (value, 1)
+    } else if let Some(value) = duration.strip_suffix("ms") {
+        (value, 1_000)
+    } else if let Some(value) = duration.strip_suffix('s') {
+        (value, 1_000_000)
+    } else {
+        return None;
+    };
+    value.trim().parse::<u64>().ok()?.checked_mul(factor)
+}
+
+/// The level of a span (field), e.g. `trace_level = "debug"`.
+#[derive(Clone, Copy, Debug)]
+pub enum TraceLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl TraceLevel {
+    fn parse_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "trace" => Ok(TraceLevel::Trace),
+            "debug" => Ok(TraceLevel::Debug),
+            "info" => Ok(TraceLevel::Info),
+            "warn" => Ok(TraceLevel::Warn),
+            "error" => Ok(TraceLevel::Error),
+            _ => Err(syn::Error::new_spanned(
+                lit,
+                "expected one of: \"trace\", \"debug\", \"info\", \"warn\", \"error\"",
+            )),
+        }
+    }
+}
+
+impl ToTokens for TraceLevel {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let level = match self {
+            TraceLevel::Trace => quote! { TRACE },
+            TraceLevel::Debug => quote! { DEBUG },
+            TraceLevel::Info => quote! { INFO },
+            TraceLevel::Warn => quote! { WARN },
+            TraceLevel::Error => quote! { ERROR },
+        };
+        tokens.extend(quote! { turbo_tasks::macro_helpers::tracing::Level::#level });
+    }
+}
+
+/// The scheduling priority of a function's tasks, e.g. `priority = high`.
+#[derive(Clone, Copy, Debug)]
+pub enum TaskPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl TaskPriority {
+    fn parse_expr(expr: &Expr) -> syn::Result<Self> {
+        match expr {
+            Expr::Path(ExprPath { path, .. }) if path.is_ident("high") => Ok(TaskPriority::High),
+            Expr::Path(ExprPath { path, .. }) if path.is_ident("low") => Ok(TaskPriority::Low),
+            _ => Err(syn::Error::new_spanned(
+                expr,
+                "unexpected token, expected \"high\" or \"low\"",
+            )),
+        }
+    }
+}
+
+impl ToTokens for TaskPriority {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let priority = match self {
+            TaskPriority::Low => quote! { Low },
+            TaskPriority::Normal => quote! { Normal },
+            TaskPriority::High => quote! { High },
+        };
+        tokens.extend(quote! { turbo_tasks::TaskPriority::#priority });
+    }
+}
+
+/// The `#[turbo_tasks(...)]` attributes of an argument: `flatten`, `map_value`, `span`,
+/// `span = "level"`, `trace_as = expr`, `untracked`, `debug_skip` and `key(expr)`. Other
+/// attributes aren't supported on arguments.
+#[derive(Default)]
+struct ArgumentAttributes {
+    flatten: bool,
+    map_value: bool,
+    span: Option<Option<TraceLevel>>,
+    trace_as: Option<Expr>,
+    untracked: bool,
+    debug_skip: bool,
+    key: Option<Expr>,
+}
+
+/// A single item of an argument's `#[turbo_tasks(...)]` attribute. Unlike `Meta`, this accepts an
+/// expression as the value of `trace_as` and within `key(...)`.
+enum ArgumentAttribute {
+    Meta(Meta),
+    TraceAs(Expr),
+    Key(Expr),
+}
+
+impl Parse for ArgumentAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let fork = input.fork();
+        if fork.parse::<Ident>().is_ok_and(|ident| ident == "trace_as") && fork.peek(Token![=]) {
+            input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            // `trace_as = "expr"` is accepted as well, for consistency with the other attributes.
+            let expr = match input.parse::<Expr>()? {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => lit.parse()?,
+                expr => expr,
+            };
+            return Ok(ArgumentAttribute::TraceAs(expr));
+        }
+        let fork = input.fork();
+        if fork.parse::<Ident>().is_ok_and(|ident| ident == "key") && fork.peek(Paren) {
+            input.parse::<Ident>()?;
+            let content;
+            parenthesized!(content in input);
+            return Ok(ArgumentAttribute::Key(content.parse()?));
+        }
+        Ok(ArgumentAttribute::Meta(input.parse()?))
+    }
+}
+
+impl ArgumentAttributes {
+    /// Parses the attributes of `typed`, emitting an error if they're invalid.
+    fn parse(typed: &PatType, definition_context: &DefinitionContext) -> Option<Self> {
+        match Self::parse_attrs(&typed.attrs) {
+            Ok(Some(attributes))
+                if attributes.trace_as.is_some()
+                    && (attributes.flatten || attributes.map_value) =>
+            {
+                typed
+                    .span()
+                    .error(
+                        "#[turbo_tasks(trace_as = ...)] can't be combined with \
+                         #[turbo_tasks(flatten)] or #[turbo_tasks(map_value)]",
+                    )
+                    .emit();
+                None
+            }
+            Ok(Some(attributes))
+                if attributes.key.is_some()
+                    && (attributes.flatten || attributes.map_value || attributes.untracked) =>
+            {
+                typed
+                    .span()
+                    .error(
+                        "#[turbo_tasks(key(...))] can't be combined with #[turbo_tasks(flatten)], \
+                         #[turbo_tasks(map_value)] or #[turbo_tasks(untracked)]",
+                    )
+                    .emit();
+                None
+            }
+            Ok(Some(attributes)) if attributes.map_value && attributes.flatten => {
+                typed
+                    .span()
+                    .error(
+                        "#[turbo_tasks(map_value)] can't be combined with #[turbo_tasks(flatten)]",
+                    )
+                    .emit();
+                None
+            }
+            Ok(Some(attributes))
+                if attributes.map_value && map_key_value_types(&typed.ty).is_none() =>
+            {
+                typed
+                    .ty
+                    .span()
+                    .error(
+                        "#[turbo_tasks(map_value)] expects a map type with key and value type \
+                         arguments, e.g. `IndexMap<K, V>`",
+                    )
+                    .emit();
+                None
+            }
+            Ok(Some(attributes)) => Some(attributes),
+            Ok(None) => {
+                typed
+                    .span()
+                    .error(format!(
+                        "{} does not support attributes on arguments, except for \
+                         #[turbo_tasks(flatten)], #[turbo_tasks(map_value)], \
+                         #[turbo_tasks(span)], #[turbo_tasks(trace_as = ...)], \
+                         #[turbo_tasks(untracked)], #[turbo_tasks(debug_skip)] and \
+                         #[turbo_tasks(key(...))]",
+                        definition_context.function_type(),
+                    ))
+                    .emit();
+                None
+            }
+            Err(err) => {
+                err.span().error(err.to_string()).emit();
+                None
+            }
+        }
+    }
+
+    /// Returns `None` if there's an attribute other than `#[turbo_tasks(...)]`.
+    fn parse_attrs(attrs: &[Attribute]) -> syn::Result<Option<Self>> {
+        let mut attributes = ArgumentAttributes::default();
+        for attr in attrs {
+            if !attr.path.is_ident("turbo_tasks") {
+                return Ok(None);
+            }
+            let nested =
+                attr.parse_args_with(Punctuated::<ArgumentAttribute, Token![,]>::parse_terminated)?;
+            for item in nested {
+                let meta = match item {
+                    ArgumentAttribute::TraceAs(expr) => {
+                        attributes.trace_as = Some(expr);
+                        continue;
+                    }
+                    ArgumentAttribute::Key(expr) => {
+                        attributes.key = Some(expr);
+                        continue;
+                    }
+                    ArgumentAttribute::Meta(meta) => meta,
+                };
+                match &meta {
+                    Meta::Path(path) if path.is_ident("flatten") => attributes.flatten = true,
+                    Meta::Path(path) if path.is_ident("map_value") => attributes.map_value = true,
+                    Meta::Path(path) if path.is_ident("span") => attributes.span = Some(None),
+                    Meta::Path(path) if path.is_ident("untracked") => attributes.untracked = true,
+                    Meta::Path(path) if path.is_ident("debug_skip") => attributes.debug_skip = true,
+                    Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(level),
+                        ..
+                    }) if path.is_ident("span") => {
+                        attributes.span = Some(Some(TraceLevel::parse_lit(level)?));
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            meta,
+                            "unexpected argument attribute, expected one of: \"flatten\", \
+                             \"map_value\", \"span\", \"trace_as\", \"untracked\", \
+                             \"debug_skip\", \"key\"",
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(Some(attributes))
+    }
+}
+
+/// Formats tokens without most of the whitespace that `TokenStream`'s `Display` impl inserts.
+fn tokens_to_string(tokens: impl ToTokens) -> String {
+    tokens
+        .to_token_stream()
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" < ", "<")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace("& ", "&")
+        .replace(" :", ":")
+        .replace("( ", "(")
+        .replace(" )", ")")
+}
+
+fn return_type_to_type(return_type: &ReturnType) -> Type {
+    match return_type {
+        ReturnType::Default => parse_quote! { () },
+        ReturnType::Type(_, ref return_type) => (**return_type).clone(),
+    }
+}
+
+/// A `PathBuf` or `Path` argument of a `portable_inputs` function.
+struct PathArgumentWarning {
+    span: Span,
+    /// Should we suggest marking the function with "fs"? Owned paths suggest filesystem IO.
+    suggest_fs: bool,
+}
+
+impl PathArgumentWarning {
+    fn emit(&self) {
+        let warning = self
+            .span
+            .warning(
+                "filesystem path arguments may be absolute, which makes the task cache \
+                 non-portable across machines",
+            )
+            .help(
+                "use a workspace-relative path type instead, e.g. \
+                 `Vc<turbo_tasks_fs::FileSystemPath>` or an `RcStr` relative to the project root",
+            );
+        if self.suggest_fs {
+            warning
+                .note(
+                    "owned paths (e.g. `PathBuf` or `Box<Path>`) suggest that the function does \
+                     filesystem IO, which should be marked with \"fs\"",
+                )
+                .emit();
+        } else {
+            warning.emit();
+        }
+    }
+}
+
+/// Returns the spans of all path types within `ty` whose last segment is one of `names`, including
+/// generic arguments, references, tuples, slices and arrays.
+fn find_types_by_name(ty: &Type, names: &[&str]) -> Vec<Span> {
+    fn visit(ty: &Type, names: &[&str], spans: &mut Vec<Span>) {
+        match ty {
+            Type::Group(TypeGroup { elem, .. })
+            | Type::Paren(TypeParen { elem, .. })
+            | Type::Reference(TypeReference { elem, .. })
+            | Type::Slice(TypeSlice { elem, .. })
+            | Type::Array(TypeArray { elem, .. }) => visit(elem, names, spans),
+            Type::Tuple(TypeTuple { elems, .. }) => {
+                for elem in elems {
+                    visit(elem, names, spans);
+                }
+            }
+            Type::Path(TypePath { path, .. }) => {
+                if let Some(last) = path.segments.last() {
+                    if names.iter().any(|name| last.ident == name) {
+                        spans.push(ty.span());
+                    }
+                }
+                for segment in &path.segments {
+                    let PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                        args, ..
+                    }) = &segment.arguments
+                    else {
+                        continue;
+                    };
+                    for arg in args {
+                        if let GenericArgument::Type(ty) = arg {
+                            visit(ty, names, spans);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut spans = Vec::new();
+    visit(ty, names, &mut spans);
+    spans
+}
+
+/// Returns the spans of all `PathBuf` and `Path` types (from `std::path`) within `ty`. For a
+/// `Box<Path>`, this is the span of the `Path`.
+fn find_path_types(ty: &Type) -> Vec<Span> {
+    find_types_by_name(ty, &["PathBuf", "Path"])
+}
+
+/// Does `ty` contain a `Vc` or `ResolvedVc`, making it likely to need resolution?
+fn contains_vc_type(ty: &Type) -> bool {
+    !find_types_by_name(ty, &["Vc", "ResolvedVc"]).is_empty()
+}
+
+/// The owned type of an argument borrowed as `&str` (an `RcStr`) or `&[T]` (a `Vec<T>`). The
+/// exposed function converts the reference into its [`expand_task_input_type`], and the inline
+/// function re-borrows it.
+fn owned_borrowed_type(ty: &Type) -> Option<Type> {
+    let Type::Reference(TypeReference {
+        mutability: None,
+        elem,
+        ..
+    }) = ty
+    else {
+        return None;
+    };
+    match &**elem {
+        Type::Path(TypePath { qself: None, path }) if path.is_ident("str") => {
+            Some(parse_quote! { turbo_tasks::RcStr })
+        }
+        Type::Slice(TypeSlice { elem, .. }) => Some(parse_quote! { Vec<#elem> }),
+        _ => None,
+    }
+}
+
+/// The reference that the exposed function accepts for an argument borrowed as `&str` or `&[T]`.
+/// The elements of a slice are expanded like those of the owned `Vec<T>`, e.g. a
+/// `&[ResolvedVc<T>]` is accepted as a `&[Vc<T>]`.
+fn exposed_borrowed_type(ty: &Type) -> Type {
+    let mut ty = ty.clone();
+    if let Type::Reference(TypeReference { elem, .. }) = &mut ty {
+        if let Type::Slice(TypeSlice { elem, .. }) = &mut **elem {
+            *elem = Box::new(expand_task_input_type(elem).into_owned());
+        }
+    }
+    ty
+}
+
+/// An `impl Into<T>`, `impl AsRef<T>` or `impl IntoIterator<Item = T>` argument. The exposed
+/// function accepts it as-is, and converts it into a concrete task input: a `T` for
+/// `impl Into<T>`, an owned `T` for `impl AsRef<T>` (e.g. an `RcStr` for `impl AsRef<str>`), and a
+/// collected `Vec<T>` for `impl IntoIterator<Item = T>`.
+enum ImplTraitArgument {
+    Into(Type),
+    AsRef(Type),
+    IntoIterator(Type),
+}
+
+impl ImplTraitArgument {
+    fn parse(ty: &Type) -> Option<Self> {
+        let Type::ImplTrait(TypeImplTrait { bounds, .. }) = ty else {
+            return None;
+        };
+        let mut bounds = bounds.iter();
+        let (
+            Some(TypeParamBound::Trait(TraitBound {
+                paren_token: None,
+                modifier: TraitBoundModifier::None,
+                lifetimes: None,
+                path,
+            })),
+            None,
+        ) = (bounds.next(), bounds.next())
+        else {
+            return None;
+        };
+        let idents: Vec<_> = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) =
+            &path.segments.last()?.arguments
+        else {
+            return None;
+        };
+        let mut args = args.iter();
+        let (Some(arg), None) = (args.next(), args.next()) else {
+            return None;
+        };
+        match (
+            &idents.iter().map(String::as_str).collect::<Vec<_>>()[..],
+            arg,
+        ) {
+            (["Into"] | ["std", "convert", "Into"], GenericArgument::Type(target)) => {
+                Some(ImplTraitArgument::Into(target.clone()))
+            }
+            (["AsRef"] | ["std", "convert", "AsRef"], GenericArgument::Type(target)) => {
+                Some(ImplTraitArgument::AsRef(target.clone()))
+            }
+            (
+                ["IntoIterator"] | ["std", "iter", "IntoIterator"],
+                GenericArgument::Binding(Binding { ident, ty, .. }),
+            ) if ident == "Item" => Some(ImplTraitArgument::IntoIterator(ty.clone())),
+            _ => None,
+        }
+    }
+
+    /// The owned type of an `impl AsRef<T>`, if it's borrowed as a `&str` or `&[T]` elsewhere.
+    fn owned_borrowed_type(target: &Type) -> Option<Type> {
+        owned_borrowed_type(&parse_quote! { &#target })
+    }
+
+    fn task_input_type(&self) -> Type {
+        match self {
+            ImplTraitArgument::Into(target) => target.clone(),
+            ImplTraitArgument::AsRef(target) => Self::owned_borrowed_type(target)
+                .unwrap_or_else(|| parse_quote! { <#target as ::std::borrow::ToOwned>::Owned }),
+            ImplTraitArgument::IntoIterator(item) => parse_quote! { Vec<#item> },
+        }
+    }
+
+    /// Converts the argument of the exposed function into the task input.
+    fn exposed_conversion(&self, ident: &Ident) -> TokenStream {
+        match self {
+            ImplTraitArgument::Into(target) => {
+                quote! { ::std::convert::Into::<#target>::into(#ident) }
+            }
+            ImplTraitArgument::AsRef(target) => {
+                let borrowed = quote! { ::std::convert::AsRef::<#target>::as_ref(&#ident) };
+                match Self::owned_borrowed_type(target) {
+                    Some(owned) => {
+                        quote! { <#owned as ::std::convert::From<&#target>>::from(#borrowed) }
+                    }
+                    None => quote! { ::std::borrow::ToOwned::to_owned(#borrowed) },
+                }
+            }
+            ImplTraitArgument::IntoIterator(item) => quote! {
+                ::std::iter::IntoIterator::into_iter(#ident).collect::<Vec<#item>>()
+            },
+        }
+    }
+
+    /// Binds the task input to the original pattern in the inline function. An `impl AsRef<T>`
+    /// is re-borrowed as a `&T`, which lives until the end of the body.
+    fn inline_conversion(&self, orig_pat: &Pat, ident: &Ident) -> TokenStream {
+        match self {
+            ImplTraitArgument::Into(target) => quote! {
+                let #orig_pat: #target = #ident;
+            },
+            ImplTraitArgument::AsRef(target) => quote! {
+                let #orig_pat: &#target = ::std::borrow::Borrow::borrow(&#ident);
+            },
+            ImplTraitArgument::IntoIterator(item) => quote! {
+                let #orig_pat: Vec<#item> = #ident;
+            },
+        }
     }
 }
 
-/// Arguments to the `#[turbo_tasks::function]` macro.
-#[derive(Default)]
-pub struct FunctionArguments {
-    /// Manually annotated metadata about what kind of IO this function does. Currently only used
-    /// by some static analysis tools. May be exposed via `tracing` or used as part of an
-    /// optimization heuristic in the future.
-    ///
-    /// This should only be used by the task that directly performs the IO. Tasks that transitively
-    /// perform IO should not be manually annotated.
-    io_markers: HashSet<IoMarker>,
-    /// Should we check that the return type contains a `ResolvedValue`?
-    ///
-    /// If there is an error due to this option being set, it should be reported to this span.
-    ///
-    /// If [`Self::local_cells`] is set, this will also be set to the same span.
-    resolved: Option<Span>,
-    /// Changes the behavior of `Vc::cell` to create local cells that are not cached across task
-    /// executions. Cells can be converted to their non-local versions by calling `Vc::resolve`.
-    ///
-    /// If there is an error due to this option being set, it should be reported to this span.
-    ///
-    /// Setting this option will also set [`Self::resolved`] to the same span.
-    pub local_cells: Option<Span>,
-}
+/// Approximates the type that the exposed function should accept for an argument of the original
+/// function, matching [`turbo_tasks::task::FromTaskInput::TaskInput`].
+///
+/// `ResolvedVc<T>` is accepted as a `Vc<T>`, which the framework resolves before calling the inline
+/// function. This recurses into `Vec`, `Option`, `Box`, tuples and arrays. Other types are returned
+/// unchanged. That includes `Vc<T>`: the contents of a `Vc` are not task inputs, so e.g.
+/// `Vc<Vec<Vc<T>>>` is passed through as-is.
+///
+/// A top-level `&str` or `&[T]` is owned as an `RcStr` or `Vec<T>` (see [`owned_borrowed_type`]),
+/// the exposed function still accepts the reference.
+fn expand_task_input_type(orig_input: &Type) -> Cow<'_, Type> {
+    match orig_input {
+        Type::Group(TypeGroup { elem, .. }) => expand_task_input_type(elem),
+        Type::Reference(_) => match owned_borrowed_type(orig_input) {
+            Some(owned) => Cow::Owned(expand_task_input_type(&owned).into_owned()),
+            None => Cow::Borrowed(orig_input),
+        },
+        Type::Tuple(TypeTuple { paren_token, elems }) => {
+            let expanded_elems: Vec<_> = elems.iter().map(expand_task_input_type).collect();
+            if expanded_elems
+                .iter()
+                .all(|elem| matches!(elem, Cow::Borrowed(_)))
+            {
+                return Cow::Borrowed(orig_input);
+            }
+            let mut elems: Punctuated<Type, Token![,]> =
+                expanded_elems.into_iter().map(Cow::into_owned).collect();
+            if elems.len() == 1 {
+                // a single-element tuple needs a trailing comma
+                elems.push_punct(Default::default());
+            }
+            Cow::Owned(Type::Tuple(TypeTuple {
+                paren_token: *paren_token,
+                elems,
+            }))
+        }
+        // The length is kept as-is, so a const like `[ResolvedVc<T>; SOME_CONST]` works. Generic
+        // const parameters are rejected together with the other generic parameters.
+        Type::Array(TypeArray {
+            bracket_token,
+            elem,
+            semi_token,
+            len,
+        }) => match expand_task_input_type(elem) {
+            Cow::Borrowed(_) => Cow::Borrowed(orig_input),
+            Cow::Owned(expanded_elem) => Cow::Owned(Type::Array(TypeArray {
+                bracket_token: *bracket_token,
+                elem: Box::new(expanded_elem),
+                semi_token: *semi_token,
+                len: len.clone(),
+            })),
+        },
+        Type::Path(TypePath {
+            qself: None,
+            path: Path {
+                leading_colon,
+                segments,
+            },
+        }) => {
+            enum PathMatch {
+                Empty,
+                StdMod,
+                VecMod,
+                Vec,
+                OptionMod,
+                Option,
+                BoxedMod,
+                Box,
+                SyncMod,
+                Arc,
+                ArcSlice,
+                CollectionsMod,
+                BTreeMap,
+                TurboTasksMod,
+                ResolvedVc,
+            }
 
-impl Parse for FunctionArguments {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let mut parsed_args = FunctionArguments::default();
-        let punctuated: Punctuated<Meta, Token![,]> = input.parse_terminated(Meta::parse)?;
-        for meta in punctuated {
-            match (
-                meta.path()
-                    .get_ident()
-                    .map(ToString::to_string)
-                    .as_deref()
-                    .unwrap_or_default(),
-                &meta,
-            ) {
-                ("fs", Meta::Path(_)) => {
-                    parsed_args.io_markers.insert(IoMarker::Filesystem);
+            let mut path_match = PathMatch::Empty;
+            let has_leading_colon = leading_colon.is_some();
+            for segment in segments {
+                path_match = match (has_leading_colon, path_match, &segment.ident) {
+                    (_, PathMatch::Empty, id) if id == "std" || id == "core" || id == "alloc" => {
+                        PathMatch::StdMod
+                    }
+
+                    (_, PathMatch::StdMod, id) if id == "vec" => PathMatch::VecMod,
+                    (false, PathMatch::Empty, id) | (_, PathMatch::VecMod, id) if id == "Vec" => {
+                        PathMatch::Vec
+                    }
+
+                    (_, PathMatch::StdMod, id) if id == "option" => PathMatch::OptionMod,
+                    (false, PathMatch::Empty, id) | (_, PathMatch::OptionMod, id)
+                        if id == "Option" =>
+                    {
+                        PathMatch::Option
+                    }
+
+                    (_, PathMatch::StdMod, id) if id == "boxed" => PathMatch::BoxedMod,
+                    (false, PathMatch::Empty, id) | (_, PathMatch::BoxedMod, id) if id == "Box" => {
+                        PathMatch::Box
+                    }
+
+                    (_, PathMatch::StdMod, id) if id == "sync" => PathMatch::SyncMod,
+                    (false, PathMatch::Empty, id) | (_, PathMatch::SyncMod, id) if id == "Arc" => {
+                        PathMatch::Arc
+                    }
+
+                    (_, PathMatch::StdMod, id) if id == "collections" => PathMatch::CollectionsMod,
+                    (false, PathMatch::Empty, id) | (_, PathMatch::CollectionsMod, id)
+                        if id == "BTreeMap" =>
+                    {
+                        PathMatch::BTreeMap
+                    }
+
+                    (_, PathMatch::Empty, id) if id == "turbo_tasks" => PathMatch::TurboTasksMod,
+                    (false, PathMatch::Empty, id) | (_, PathMatch::TurboTasksMod, id)
+                        if id == "ResolvedVc" =>
+                    {
+                        PathMatch::ResolvedVc
+                    }
+
+                    // some type we don't have an expansion for
+                    _ => return Cow::Borrowed(orig_input),
                 }
-                ("network", Meta::Path(_)) => {
-                    parsed_args.io_markers.insert(IoMarker::Network);
+            }
+
+            let last_segment = segments.last().expect("non-empty");
+            if let (PathMatch::Arc, PathArguments::AngleBracketed(bracketed_args)) =
+                (&path_match, &last_segment.arguments)
+            {
+                if let Some(GenericArgument::Type(Type::Slice(_))) = bracketed_args.args.first() {
+                    path_match = PathMatch::ArcSlice;
                 }
-                ("resolved", Meta::Path(_)) => {
-                    parsed_args.resolved = Some(meta.span());
+            }
+            match path_match {
+                // `Box<str>` and `Box<[u8]>` (or `Arc<str>`) have no inner type to expand, so
+                // they're returned unchanged below
+                PathMatch::Vec | PathMatch::Option | PathMatch::Box | PathMatch::Arc => {
+                    let PathArguments::AngleBracketed(bracketed_args) = &last_segment.arguments
+                    else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    let Some(GenericArgument::Type(elem_ty)) = bracketed_args.args.first() else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    let Cow::Owned(expanded_elem_ty) = expand_task_input_type(elem_ty) else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    let mut segments = segments.clone();
+                    let last_segment = segments.last_mut().expect("non-empty");
+                    let mut bracketed_args = bracketed_args.clone();
+                    *bracketed_args.args.first_mut().expect("non-empty") =
+                        GenericArgument::Type(expanded_elem_ty);
+                    last_segment.arguments = PathArguments::AngleBracketed(bracketed_args);
+                    Cow::Owned(Type::Path(TypePath {
+                        qself: None,
+                        path: Path {
+                            leading_colon: *leading_colon,
+                            segments,
+                        },
+                    }))
                 }
-                ("local_cells", Meta::Path(_)) => {
-                    let span = Some(meta.span());
-                    parsed_args.local_cells = span;
-                    parsed_args.resolved = span;
+                // Unlike for `Box`, the slice's elements can be converted without taking the `Arc`
+                // apart.
+                PathMatch::ArcSlice => {
+                    let PathArguments::AngleBracketed(bracketed_args) = &last_segment.arguments
+                    else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    let Some(GenericArgument::Type(Type::Slice(slice_ty))) =
+                        bracketed_args.args.first()
+                    else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    let Cow::Owned(expanded_elem_ty) = expand_task_input_type(&slice_ty.elem)
+                    else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    let mut segments = segments.clone();
+                    let last_segment = segments.last_mut().expect("non-empty");
+                    let mut bracketed_args = bracketed_args.clone();
+                    *bracketed_args.args.first_mut().expect("non-empty") =
+                        GenericArgument::Type(Type::Slice(TypeSlice {
+                            bracket_token: slice_ty.bracket_token,
+                            elem: Box::new(expanded_elem_ty),
+                        }));
+                    last_segment.arguments = PathArguments::AngleBracketed(bracketed_args);
+                    Cow::Owned(Type::Path(TypePath {
+                        qself: None,
+                        path: Path {
+                            leading_colon: *leading_colon,
+                            segments,
+                        },
+                    }))
                 }
-                (_, meta) => {
-                    return Err(syn::Error::new_spanned(
-                        meta,
-                        "unexpected token, expected one of: \"fs\", \"network\", \"resolved\", \
-                         \"local_cells\"",
-                    ))
+                // Only the value type is expanded, the keys must be task inputs on their own, as
+                // they determine the order of the entries.
+                PathMatch::BTreeMap => {
+                    let mut ty = orig_input.clone();
+                    let Some(value_ty) = map_value_type_mut(&mut ty) else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    let Cow::Owned(expanded_value_ty) = expand_task_input_type(value_ty) else {
+                        return Cow::Borrowed(orig_input);
+                    };
+                    *value_ty = expanded_value_ty;
+                    Cow::Owned(ty)
+                }
+                PathMatch::ResolvedVc => {
+                    let args = &last_segment.arguments;
+                    Cow::Owned(parse_quote!(turbo_tasks::Vc #args))
                 }
+                _ => Cow::Borrowed(orig_input),
             }
         }
-        Ok(parsed_args)
+        _ => Cow::Borrowed(orig_input),
     }
 }
 
-fn return_type_to_type(return_type: &ReturnType) -> Type {
-    match return_type {
-        ReturnType::Default => parse_quote! { () },
-        ReturnType::Type(_, ref return_type) => (**return_type).clone(),
-    }
+/// The key and value types of a map-like type such as `IndexMap<K, V>` or `HashMap<K, V, S>`,
+/// i.e. its first two generic type arguments.
+fn map_key_value_types(ty: &Type) -> Option<(&Type, &Type)> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(bracketed_args) = &path.segments.last()?.arguments else {
+        return None;
+    };
+    let mut types = bracketed_args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    Some((types.next()?, types.next()?))
+}
+
+/// The name of `ty` if it's a hash or insertion-ordered map like `FxHashMap<K, V>` or
+/// `IndexMap<K, V>`. These don't implement `Hash`, so they can't be task inputs on their own.
+fn unhashable_map_type_name(ty: &Type) -> Option<String> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let name = path.segments.last()?.ident.to_string();
+    (matches!(
+        name.as_str(),
+        "HashMap" | "FxHashMap" | "IndexMap" | "FxIndexMap" | "AutoMap"
+    ) && map_key_value_types(ty).is_some())
+    .then_some(name)
+}
+
+/// The value type of a map-like type, see [`map_key_value_types`].
+fn map_value_type_mut(ty: &mut Type) -> Option<&mut Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(bracketed_args) = &mut path.segments.last_mut()?.arguments
+    else {
+        return None;
+    };
+    bracketed_args
+        .args
+        .iter_mut()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .nth(1)
 }
 
 fn expand_vc_return_type(orig_output: &Type) -> Type {
@@ -567,6 +3956,7 @@ fn expand_vc_return_type(orig_output: &Type) -> Type {
     // recognize.
     let mut new_output = orig_output.clone();
     let mut found_vc = false;
+    let mut wrapped_output = None;
     loop {
         new_output = match new_output {
             Type::Group(TypeGroup { elem, .. }) => *elem,
@@ -625,7 +4015,21 @@ fn expand_vc_return_type(orig_output: &Type) -> Type {
                     found_vc = true;
                     break; // Vc is the bottom-most level
                 }
-                if ident == "Result" && args.len() == 1 {
+                if ident == "Option" && args.len() == 1 {
+                    // `Option<Vc<T>>` is returned as a `Vc<Option<Vc<T>>>`
+                    if let Some(GenericArgument::Type(inner)) = args.first() {
+                        if is_vc_type(inner) {
+                            found_vc = true;
+                            let option_output = new_output.clone();
+                            wrapped_output = Some(parse_quote! {
+                                turbo_tasks::Vc<#option_output>
+                            });
+                        }
+                    }
+                    break;
+                }
+                // `Result<Vc<T>, E>` with a custom error type that's `Into<TaskError>`
+                if ident == "Result" && (args.len() == 1 || args.len() == 2) {
                     let GenericArgument::Type(ty) =
                         args.first().expect("Result<...> type has an argument")
                     else {
@@ -643,15 +4047,189 @@ fn expand_vc_return_type(orig_output: &Type) -> Type {
     if !found_vc {
         orig_output
             .span()
-            .unwrap()
             .error(
-                "Expected return type to be `turbo_tasks::Vc<T>` or `anyhow::Result<Vc<T>>`. \
-                 Unable to process type.",
+                "Expected return type to be `turbo_tasks::Vc<T>`, `Option<Vc<T>>`, \
+                 `anyhow::Result<Vc<T>>` or `Result<Vc<T>, E>`. Unable to process type.",
             )
             .emit();
     }
 
-    new_output
+    wrapped_output.unwrap_or(new_output)
+}
+
+/// The `OperationVc<T>` exposed by an operation returning the `Vc<T>` computed by
+/// [`expand_vc_return_type`]. Other types are returned as they are, an error has been reported for
+/// them already.
+fn operation_vc_type(vc_type: &Type) -> Type {
+    match vc_type {
+        Type::Path(TypePath { qself: None, path }) if is_vc_type(vc_type) => {
+            let arguments = &path
+                .segments
+                .last()
+                .expect("Vc type has a segment")
+                .arguments;
+            parse_quote! { turbo_tasks::OperationVc #arguments }
+        }
+        _ => vc_type.clone(),
+    }
+}
+
+/// Is `ty` a path to a type named `Result`, e.g. `anyhow::Result<Vc<T>>`?
+fn is_result_type(ty: &Type) -> bool {
+    match ty {
+        Type::Group(TypeGroup { elem, .. }) => is_result_type(elem),
+        Type::Path(TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Result"),
+        _ => false,
+    }
+}
+
+/// The `T` of a `Result<T>` or `Result<T, E>` (see [`is_result_type`]), if `ty` is one.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Group(TypeGroup { elem, .. }) => result_ok_type(elem),
+        Type::Path(TypePath { qself: None, path }) => {
+            let segment = path.segments.last()?;
+            let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) =
+                &segment.arguments
+            else {
+                return None;
+            };
+            match args.first() {
+                Some(GenericArgument::Type(ok)) if segment.ident == "Result" => Some(ok),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Is `ty` a `Vc<T>` or `turbo_tasks::Vc<T>`?
+fn is_vc_type(ty: &Type) -> bool {
+    is_turbo_tasks_type(ty, "Vc")
+}
+
+/// Is `ty` a `ResolvedVc<T>` or `turbo_tasks::ResolvedVc<T>`?
+fn is_resolved_vc_type(ty: &Type) -> bool {
+    is_turbo_tasks_type(ty, "ResolvedVc")
+}
+
+/// Is `ty` an `Arc<T>`, optionally prefixed with `std::sync::`?
+fn is_arc_type(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return false;
+    };
+    let idents: Vec<_> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    matches!(
+        idents.iter().map(String::as_str).collect::<Vec<_>>()[..],
+        ["Arc"] | ["std" | "alloc", "sync", "Arc"]
+    ) && matches!(
+        path.segments.last().map(|segment| &segment.arguments),
+        Some(PathArguments::AngleBracketed(_))
+    )
+}
+
+/// Is `ty` a `Cow<str>` (with any lifetime), optionally prefixed with `std::borrow::`?
+fn is_cow_str_type(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return false;
+    };
+    let idents: Vec<_> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    if !matches!(
+        idents.iter().map(String::as_str).collect::<Vec<_>>()[..],
+        ["Cow"] | ["std", "borrow", "Cow"]
+    ) {
+        return false;
+    }
+    let Some(PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. })) =
+        path.segments.last().map(|segment| &segment.arguments)
+    else {
+        return false;
+    };
+    let mut types = args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    matches!(
+        (types.next(), types.next()),
+        (Some(Type::Path(TypePath { qself: None, path })), None) if path.is_ident("str")
+    )
+}
+
+/// Is `ty` a generic type named `name`, optionally prefixed with `turbo_tasks::`?
+fn is_turbo_tasks_type(ty: &Type, name: &str) -> bool {
+    let Type::Path(TypePath {
+        qself: None,
+        path: Path {
+            leading_colon: None,
+            segments,
+        },
+    }) = ty
+    else {
+        return false;
+    };
+    let mut segments = segments.iter();
+    let Some(mut segment) = segments.next() else {
+        return false;
+    };
+    if segment.ident == "turbo_tasks" && segment.arguments.is_none() {
+        let Some(next) = segments.next() else {
+            return false;
+        };
+        segment = next;
+    }
+    segment.ident == name
+        && matches!(segment.arguments, PathArguments::AngleBracketed(_))
+        && segments.next().is_none()
+}
+
+/// The `#[cfg(...)]` attributes among `attrs`.
+pub fn cfg_attributes<'a>(attrs: impl IntoIterator<Item = &'a Attribute>) -> Vec<&'a Attribute> {
+    attrs
+        .into_iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .collect()
+}
+
+/// Adds the `cfg_attrs` of a function to every item generated for it (statics, impl blocks, ...),
+/// so they're configured out together with the function.
+pub fn cfg_gate_items(cfg_attrs: &[&Attribute], items: TokenStream) -> TokenStream {
+    if cfg_attrs.is_empty() {
+        return items;
+    }
+    let mut file: syn::File = match syn::parse2(items.clone()) {
+        Ok(file) => file,
+        // leave it to the compiler to report the invalid items
+        Err(_) => return items,
+    };
+    for item in &mut file.items {
+        let attrs = match item {
+            Item::Const(item) => &mut item.attrs,
+            Item::Enum(item) => &mut item.attrs,
+            Item::Fn(item) => &mut item.attrs,
+            Item::Impl(item) => &mut item.attrs,
+            Item::Macro(item) => &mut item.attrs,
+            Item::Mod(item) => &mut item.attrs,
+            Item::Static(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            Item::Trait(item) => &mut item.attrs,
+            Item::Type(item) => &mut item.attrs,
+            Item::Use(item) => &mut item.attrs,
+            _ => continue,
+        };
+        attrs.splice(0..0, cfg_attrs.iter().map(|attr| (*attr).clone()));
+    }
+    file.into_token_stream()
 }
 
 /// The context in which the function is being defined.
@@ -684,23 +4262,61 @@ pub struct NativeFn {
     function_path: ExprPath,
     is_method: bool,
     local_cells: bool,
+    hasher: Option<Type>,
+    arg_resolver: Option<TokenStream>,
+    arg_names: Vec<String>,
+    schedule_after: Option<ExprPath>,
+    version: u32,
+    readonly: bool,
+    concurrency_group: Option<LitStr>,
+    eager: bool,
+    priority: TaskPriority,
+    io_fs: bool,
+    io_network: bool,
 }
 
 impl NativeFn {
     pub fn new(
         function_path_string: &str,
         function_path: &ExprPath,
-        is_method: bool,
-        local_cells: bool,
+        turbo_fn: &TurboFn,
     ) -> NativeFn {
         NativeFn {
             function_path_string: function_path_string.to_owned(),
             function_path: function_path.clone(),
-            is_method,
-            local_cells,
+            is_method: turbo_fn.is_method(),
+            local_cells: turbo_fn.local_cells,
+            hasher: turbo_fn.hasher.clone(),
+            arg_resolver: turbo_fn.arg_resolver(),
+            arg_names: turbo_fn.input_idents().map(ToString::to_string).collect(),
+            schedule_after: turbo_fn.schedule_after.as_ref().map(|path| {
+                let mut native_function_path = path.clone();
+                let last_segment = native_function_path
+                    .path
+                    .segments
+                    .last_mut()
+                    .expect("non-empty");
+                last_segment.ident = get_native_function_ident(&last_segment.ident);
+                native_function_path
+            }),
+            version: turbo_fn.version,
+            readonly: turbo_fn.readonly,
+            concurrency_group: turbo_fn.concurrency_group.clone(),
+            eager: turbo_fn.eager,
+            priority: turbo_fn.priority,
+            io_fs: turbo_fn.io_fs,
+            io_network: turbo_fn.io_network,
         }
     }
 
+    pub fn is_method(&self) -> bool {
+        self.is_method
+    }
+
+    pub fn local_cells(&self) -> bool {
+        self.local_cells
+    }
+
     pub fn ty(&self) -> Type {
         parse_quote! { turbo_tasks::macro_helpers::Lazy<turbo_tasks::NativeFunction> }
     }
@@ -711,6 +4327,17 @@ impl NativeFn {
             function_path,
             is_method,
             local_cells,
+            hasher,
+            arg_resolver,
+            arg_names,
+            schedule_after,
+            version,
+            readonly,
+            concurrency_group,
+            eager,
+            priority,
+            io_fs,
+            io_network,
         } = self;
 
         let constructor = if *is_method {
@@ -719,6 +4346,30 @@ impl NativeFn {
             quote! { new_function }
         };
 
+        let schedule_after = match schedule_after {
+            Some(native_function_path) => quote! {
+                Some((|| &*#native_function_path) as fn() -> &'static turbo_tasks::NativeFunction)
+            },
+            None => quote! { None },
+        };
+
+        let concurrency_group = match concurrency_group {
+            Some(group) => quote! { Some(#group) },
+            None => quote! { None },
+        };
+
+        let with_arg_hasher = hasher.as_ref().map(|hasher| {
+            quote! {
+                .with_arg_hasher::<#hasher>()
+            }
+        });
+
+        let with_arg_resolver = arg_resolver.as_ref().map(|arg_resolver| {
+            quote! {
+                .with_arg_resolver(#arg_resolver)
+            }
+        });
+
         parse_quote! {
             turbo_tasks::macro_helpers::Lazy::new(|| {
                 #[allow(deprecated)]
@@ -726,9 +4377,22 @@ impl NativeFn {
                     #function_path_string.to_owned(),
                     turbo_tasks::FunctionMeta {
                         local_cells: #local_cells,
+                        schedule_after: #schedule_after,
+                        version: #version,
+                        readonly: #readonly,
+                        concurrency_group: #concurrency_group,
+                        eager: #eager,
+                        priority: #priority,
+                        io_markers: turbo_tasks::IoMarkers {
+                            fs: #io_fs,
+                            network: #io_network,
+                        },
                     },
                     #function_path,
                 )
+                .with_arg_names(&[#(#arg_names),*])
+                #with_arg_hasher
+                #with_arg_resolver
             })
         }
     }
@@ -745,3 +4409,567 @@ impl NativeFn {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+    use quote::{format_ident, ToTokens};
+    use syn::{parse_quote, Expr, FnArg, PatType, Type};
+
+    use super::{DefinitionContext, FunctionArguments, IoMarker, TurboFn};
+
+    #[test]
+    fn test_inline_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    a: ResolvedVc<Foo>,
+                    b: Option<Vec<turbo_tasks::ResolvedVc<Bar>>>,
+                    c: (u32, ResolvedVc<Baz>),
+                    d: Vc<Qux>,
+                    e: RcStr,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let original: Vec<Type> = vec![
+            parse_quote!(ResolvedVc<Foo>),
+            parse_quote!(Option<Vec<turbo_tasks::ResolvedVc<Bar>>>),
+            parse_quote!((u32, ResolvedVc<Baz>)),
+            parse_quote!(Vc<Qux>),
+            parse_quote!(RcStr),
+        ];
+        assert_eq!(turbo_fn.input_types(), original.iter().collect::<Vec<_>>());
+
+        let expanded: Vec<Type> = vec![
+            parse_quote!(turbo_tasks::Vc<Foo>),
+            parse_quote!(Option<Vec<turbo_tasks::Vc<Bar>>>),
+            parse_quote!((u32, turbo_tasks::Vc<Baz>)),
+            parse_quote!(Vc<Qux>),
+            parse_quote!(RcStr),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_arc_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    a: Arc<str>,
+                    b: Arc<[ResolvedVc<Foo>]>,
+                    c: std::sync::Arc<[Option<ResolvedVc<Bar>>]>,
+                    d: Arc<[u32]>,
+                    e: Arc<(u32, ResolvedVc<Foo>)>,
+                    f: std::sync::Arc<Config>,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let expanded: Vec<Type> = vec![
+            parse_quote!(Arc<str>),
+            parse_quote!(Arc<[turbo_tasks::Vc<Foo>]>),
+            parse_quote!(std::sync::Arc<[Option<turbo_tasks::Vc<Bar>>]>),
+            parse_quote!(Arc<[u32]>),
+            parse_quote!(Arc<(u32, turbo_tasks::Vc<Foo>)>),
+            parse_quote!(std::sync::Arc<Config>),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_where_clause() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(self: Vc<Self>, a: ResolvedVc<Foo>) -> Vc<Foo> where Self: Sized
+            },
+            DefinitionContext::ValueInherentImpl,
+            FunctionArguments::default(),
+        )
+        .expect("where clauses are supported");
+
+        let where_clause: syn::WhereClause = parse_quote! { where Self: Sized };
+        assert_eq!(
+            turbo_fn.signature().generics.where_clause,
+            Some(where_clause)
+        );
+    }
+
+    #[test]
+    fn test_trait_object_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    a: Vec<ResolvedVc<Box<dyn Foo>>>,
+                    b: Option<Vec<turbo_tasks::ResolvedVc<Box<dyn Bar + Send>>>>,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let expanded: Vec<Type> = vec![
+            parse_quote!(Vec<turbo_tasks::Vc<Box<dyn Foo>>>),
+            parse_quote!(Option<Vec<turbo_tasks::Vc<Box<dyn Bar + Send>>>>),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_array_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    a: [ResolvedVc<Foo>; SOME_CONST],
+                    b: [Option<ResolvedVc<Bar>>; 2 * SOME_CONST],
+                    c: [u32; 4],
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let expanded: Vec<Type> = vec![
+            parse_quote!([turbo_tasks::Vc<Foo>; SOME_CONST]),
+            parse_quote!([Option<turbo_tasks::Vc<Bar>>; 2 * SOME_CONST]),
+            parse_quote!([u32; 4]),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_borrowed_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(a: &str, b: &[ResolvedVc<Foo>], c: &[u32], d: &u32) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        // the exposed function accepts the references
+        let exposed: Vec<Type> = turbo_fn
+            .signature()
+            .inputs
+            .into_iter()
+            .filter_map(|arg| match arg {
+                FnArg::Typed(PatType { ty, .. }) => Some(*ty),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let exposed_expected: Vec<Type> = vec![
+            parse_quote!(&str),
+            parse_quote!(&[turbo_tasks::Vc<Foo>]),
+            parse_quote!(&[u32]),
+            parse_quote!(&u32),
+        ];
+        assert_eq!(exposed, exposed_expected);
+
+        // only `&str` and `&[T]` are owned, with the elements of a slice expanded
+        let expanded: Vec<Type> = vec![
+            parse_quote!(turbo_tasks::RcStr),
+            parse_quote!(Vec<turbo_tasks::Vc<Foo>>),
+            parse_quote!(Vec<u32>),
+            parse_quote!(&u32),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_impl_trait_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    a: impl Into<RcStr>,
+                    b: impl AsRef<str>,
+                    c: impl AsRef<Path>,
+                    d: impl IntoIterator<Item = Vc<Bar>>,
+                    e: &[Vc<Bar>],
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        // the exposed function accepts the `impl Trait`s
+        let exposed: Vec<Type> = turbo_fn
+            .signature()
+            .inputs
+            .into_iter()
+            .filter_map(|arg| match arg {
+                FnArg::Typed(PatType { ty, .. }) => Some(*ty),
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let exposed_expected: Vec<Type> = vec![
+            parse_quote!(impl Into<RcStr>),
+            parse_quote!(impl AsRef<str>),
+            parse_quote!(impl AsRef<Path>),
+            parse_quote!(impl IntoIterator<Item = Vc<Bar>>),
+            parse_quote!(&[Vc<Bar>]),
+        ];
+        assert_eq!(exposed, exposed_expected);
+
+        let expanded: Vec<Type> = vec![
+            parse_quote!(RcStr),
+            parse_quote!(turbo_tasks::RcStr),
+            parse_quote!(<Path as ::std::borrow::ToOwned>::Owned),
+            parse_quote!(Vec<Vc<Bar>>),
+            parse_quote!(Vec<Vc<Bar>>),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_btree_map_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    a: BTreeMap<RcStr, ResolvedVc<Foo>>,
+                    b: std::collections::BTreeMap<u32, Box<(u32, ResolvedVc<Bar>)>>,
+                    c: BTreeMap<RcStr, u32>,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let expanded: Vec<Type> = vec![
+            parse_quote!(BTreeMap<RcStr, turbo_tasks::Vc<Foo>>),
+            parse_quote!(std::collections::BTreeMap<u32, Box<(u32, turbo_tasks::Vc<Bar>)>>),
+            parse_quote!(BTreeMap<RcStr, u32>),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_map_value_input_types() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    #[turbo_tasks(map_value)] a: IndexMap<RcStr, ResolvedVc<Foo>>,
+                    #[turbo_tasks(map_value)] b: HashMap<u32, Option<ResolvedVc<Bar>>, S>,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let expanded: Vec<Type> = vec![
+            parse_quote!(Vec<(RcStr, turbo_tasks::Vc<Foo>)>),
+            parse_quote!(Vec<(u32, Option<turbo_tasks::Vc<Bar>>)>),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), expanded);
+    }
+
+    #[test]
+    fn test_trace_as_attribute() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    #[turbo_tasks(trace_as = url.host())] url: Url,
+                    #[turbo_tasks(span = "debug", trace_as = "names.len()")] names: Vec<RcStr>,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let trace_as: Vec<Option<Expr>> = turbo_fn
+            .inputs
+            .iter()
+            .map(|input| input.trace_as.clone())
+            .collect();
+        assert_eq!(
+            trace_as,
+            vec![
+                Some(parse_quote!(url.host())),
+                Some(parse_quote!(names.len()))
+            ]
+        );
+        assert!(turbo_fn
+            .get_dispatch_span()
+            .to_string()
+            .contains("url . host ()"));
+    }
+
+    #[test]
+    fn test_untracked_and_debug_skip_attributes() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    #[turbo_tasks(untracked)] a: ResolvedVc<Foo>,
+                    #[turbo_tasks(debug_skip)] b: RcStr,
+                    #[turbo_tasks(untracked, debug_skip)] c: u32,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let inline_input_types: Vec<Type> = vec![
+            parse_quote!(turbo_tasks::task::UntrackedTaskInput<turbo_tasks::Vc<Foo>>),
+            parse_quote!(turbo_tasks::task::DebugSkippedTaskInput<RcStr>),
+            parse_quote!(
+                turbo_tasks::task::UntrackedTaskInput<
+                    turbo_tasks::task::DebugSkippedTaskInput<u32>,
+                >
+            ),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), inline_input_types);
+
+        let (_, block) =
+            turbo_fn.inline_signature_and_block(&format_ident!("foo_inline"), &parse_quote! {{}});
+        assert!(block
+            .to_token_stream()
+            .to_string()
+            .contains("let c = c . 0 . 0 ;"));
+    }
+
+    #[test]
+    fn test_key_attribute() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(
+                    #[turbo_tasks(key(|options| options.mode))] a: Options,
+                    #[turbo_tasks(debug_skip, key(Options::mode))] b: Options,
+                ) -> Vc<Foo>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let inline_input_types: Vec<Type> = vec![
+            parse_quote!(turbo_tasks::task::KeyedTaskInput<Options, foo_a_key>),
+            parse_quote!(
+                turbo_tasks::task::DebugSkippedTaskInput<
+                    turbo_tasks::task::KeyedTaskInput<Options, foo_b_key>,
+                >
+            ),
+        ];
+        assert_eq!(turbo_fn.inline_input_types(), inline_input_types);
+
+        let key_definitions = turbo_fn.key_definitions().to_string();
+        assert!(key_definitions.contains("struct foo_a_key ;"));
+        assert!(key_definitions.contains("struct foo_b_key ;"));
+
+        let (_, block) =
+            turbo_fn.inline_signature_and_block(&format_ident!("foo_inline"), &parse_quote! {{}});
+        assert!(block
+            .to_token_stream()
+            .to_string()
+            .contains("let b = b . 0 . 0 ;"));
+    }
+
+    #[test]
+    fn test_no_cache() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(a: u32) -> Vc<u32>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments {
+                no_cache: Some(Span::call_site()),
+                ..Default::default()
+            },
+        )
+        .expect("valid signature");
+
+        let signature = turbo_fn.signature();
+        assert!(signature.asyncness.is_some());
+        let output: Type = parse_quote!(turbo_tasks::Result<Vc<u32>>);
+        assert_eq!(signature.output, parse_quote!(-> #output));
+
+        // the body is called directly, without dispatching a task
+        let block = turbo_fn
+            .no_cache_block(&format_ident!("foo_inline"))
+            .expect("no_cache block")
+            .to_token_stream()
+            .to_string();
+        assert!(block.contains("foo_inline (a)"));
+        assert!(!block.contains("dynamic_call"));
+    }
+
+    #[test]
+    fn test_lazy_body() {
+        let inline_block = |lazy_body| {
+            let turbo_fn = TurboFn::new(
+                &parse_quote! {
+                    async fn foo(a: u32) -> Result<Vc<u32>>
+                },
+                DefinitionContext::NakedFn,
+                FunctionArguments {
+                    lazy_body,
+                    ..Default::default()
+                },
+            )
+            .expect("valid signature");
+            let (_, block) = turbo_fn.inline_signature_and_block(
+                &format_ident!("foo_inline"),
+                &parse_quote! {{ Ok(Vc::cell(a)) }},
+            );
+            block.to_token_stream().to_string()
+        };
+
+        assert!(inline_block(Some(Span::call_site())).contains("lazy_body"));
+        assert!(!inline_block(None).contains("lazy_body"));
+    }
+
+    #[test]
+    fn test_destructuring_patterns() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo((a, b): (u32, u32), arg2: u32, Foo { arg3, c }: Foo) -> Vc<u32>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+        let parameter_idents: Vec<_> = turbo_fn
+            .parameter_idents()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(parameter_idents, ["arg1", "arg2", "_arg3"]);
+
+        let (signature, block) = turbo_fn.inline_signature_and_block(
+            &format_ident!("foo_inline"),
+            &parse_quote! {{ Vc::cell(a + b + arg2 + arg3 + c) }},
+        );
+        let signature = signature.to_token_stream().to_string();
+        assert!(signature.contains("arg1 : (u32 , u32)"));
+        assert!(signature.contains("_arg3 : Foo"));
+        let block = block.to_token_stream().to_string();
+        assert!(block.contains("let (a , b) ="));
+        assert!(block.contains("let Foo { arg3 , c } ="));
+    }
+
+    #[test]
+    fn test_mut_bindings() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(mut state: Vec<u32>, (mut a, b): (u32, u32)) -> Vc<u32>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+
+        let signature = turbo_fn.signature().to_token_stream().to_string();
+        assert!(!signature.contains("mut"), "{signature}");
+
+        let (signature, block) = turbo_fn.inline_signature_and_block(
+            &format_ident!("foo_inline"),
+            &parse_quote! {{
+                state.push(b);
+                a += 1;
+                Vc::cell(state.len() as u32 + a)
+            }},
+        );
+        let signature = signature.to_token_stream().to_string();
+        assert!(!signature.contains("mut"), "{signature}");
+        let block = block.to_token_stream().to_string();
+        assert!(block.contains("let mut state ="), "{block}");
+        assert!(block.contains("let (mut a , b) ="), "{block}");
+    }
+
+    #[test]
+    fn test_cancellable_arg_resolver() {
+        let cancellable_fn = |parallel_inputs| {
+            TurboFn::new(
+                &parse_quote! {
+                    fn foo(a: ResolvedVc<Foo>, b: ResolvedVc<Bar>, c: u32) -> Vc<Foo>
+                },
+                DefinitionContext::NakedFn,
+                FunctionArguments {
+                    cancellable: Some(Span::call_site()),
+                    parallel_inputs,
+                    ..Default::default()
+                },
+            )
+            .expect("valid signature")
+        };
+        let count_checks = |turbo_fn: TurboFn| {
+            turbo_fn
+                .arg_resolver()
+                .expect("cancellable functions have a resolver")
+                .to_string()
+                .matches("bail_if_cancelled")
+                .count()
+        };
+
+        // checked before each input is resolved
+        assert_eq!(count_checks(cancellable_fn(None)), 3);
+        // checked before the `Vc` inputs are joined and before the other input is resolved
+        assert_eq!(count_checks(cancellable_fn(Some(Span::call_site()))), 2);
+    }
+
+    #[test]
+    fn test_portable_inputs_warnings() {
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(a: Value<PathBuf>, b: u32, c: Value<(std::path::PathBuf, u32)>) -> Vc<u32>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments {
+                portable_inputs: Some(Span::call_site()),
+                ..Default::default()
+            },
+        )
+        .expect("valid signature");
+        assert_eq!(turbo_fn.path_argument_warnings.len(), 2);
+
+        let turbo_fn = TurboFn::new(
+            &parse_quote! {
+                fn foo(a: Value<PathBuf>) -> Vc<u32>
+            },
+            DefinitionContext::NakedFn,
+            FunctionArguments::default(),
+        )
+        .expect("valid signature");
+        assert!(turbo_fn.path_argument_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_portable_inputs_fs_suggestion() {
+        let suggest_fs = |fs: bool| {
+            let mut args = FunctionArguments {
+                portable_inputs: Some(Span::call_site()),
+                ..Default::default()
+            };
+            if fs {
+                args.io_markers.insert(IoMarker::Filesystem);
+            }
+            TurboFn::new(
+                &parse_quote! {
+                    fn foo(a: Value<Box<Path>>) -> Vc<bool>
+                },
+                DefinitionContext::NakedFn,
+                args,
+            )
+            .expect("valid signature")
+            .path_argument_warnings
+            .iter()
+            .map(|warning| warning.suggest_fs)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(suggest_fs(false), [true]);
+        // the function is already marked as doing filesystem IO
+        assert_eq!(suggest_fs(true), [false]);
+    }
+}
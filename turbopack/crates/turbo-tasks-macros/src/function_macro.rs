@@ -1,10 +1,24 @@
+use std::collections::HashSet;
+
 use proc_macro::TokenStream;
-use proc_macro2::Ident;
-use quote::quote;
-use syn::{parse_macro_input, parse_quote, ExprPath, ItemFn};
-use turbo_tasks_macros_shared::{get_native_function_id_ident, get_native_function_ident};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, spanned::Spanned, Attribute, Block, ExprPath,
+    FnArg, Item, ItemFn, Pat, PatIdent, PatType, Signature, Visibility,
+};
+use turbo_tasks_macros_shared::{
+    get_function_arg_count_ident, get_function_definition_context_ident,
+    get_function_has_receiver_ident, get_function_is_method_ident, get_function_local_cells_ident,
+    get_function_module_path_ident, get_function_parameter_names_ident,
+    get_function_return_type_ident, get_function_signature_ident, get_native_function_id_ident,
+    get_native_function_ident,
+};
 
-use crate::func::{DefinitionContext, FunctionArguments, NativeFn, TurboFn};
+use crate::func::{
+    cfg_attributes, cfg_gate_items, collect_bound_idents, pattern_argument_ident,
+    DefinitionContext, FunctionArguments, Monomorphize, NativeFn, TurboFn,
+};
 
 /// This macro generates the virtual function that powers turbo tasks.
 /// An annotated task is replaced with a stub function that returns a
@@ -33,12 +47,37 @@ pub fn function(args: TokenStream, input: TokenStream) -> TokenStream {
         vis,
         sig,
         block,
-    } = parse_macro_input!(input as ItemFn);
+    } = match parse_macro_input!(input as Item) {
+        Item::Fn(item_fn) => item_fn,
+        item => {
+            let kind = match &item {
+                Item::Const(_) => "a const",
+                Item::Static(_) => "a static",
+                Item::Struct(_) | Item::Enum(_) | Item::Union(_) => "a type definition",
+                Item::Impl(_) => "an impl block, use #[turbo_tasks::value_impl] instead",
+                Item::Trait(_) => "a trait, use #[turbo_tasks::value_trait] instead",
+                _ => "an item that isn't a function",
+            };
+            return syn::Error::new(
+                item.span(),
+                format!("#[turbo_tasks::function] expected a function, but was applied to {kind}"),
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
 
     let args = syn::parse::<FunctionArguments>(args)
         .inspect_err(|err| errors.push(err.to_compile_error()))
         .unwrap_or_default();
-    let local_cells = args.local_cells.is_some();
+
+    let (sig, block, args_struct_definition) = match args.args_struct() {
+        Some(_) => match pack_arguments(&vis, &sig, &block) {
+            Ok((sig, block, definition)) => (sig, block, Some(definition)),
+            Err(err) => return err.to_compile_error().into(),
+        },
+        None => (sig, block, None),
+    };
 
     let Some(turbo_fn) = TurboFn::new(&sig, DefinitionContext::NakedFn, args) else {
         return quote! {
@@ -46,20 +85,35 @@ pub fn function(args: TokenStream, input: TokenStream) -> TokenStream {
         }
         .into();
     };
+    turbo_fn.emit_warnings();
 
     let ident = &sig.ident;
 
-    let inline_function_ident = Ident::new(&format!("{ident}_inline_function"), ident.span());
+    // `format_ident!` strips the `r#` of a raw identifier, e.g. of `r#type`
+    let inline_function_ident = format_ident!("{}_inline_function", ident);
     let inline_function_path: ExprPath = parse_quote! { #inline_function_ident };
-    let mut inline_signature = sig.clone();
-    inline_signature.ident = inline_function_ident;
+    let (inline_signature, inline_block) =
+        turbo_fn.inline_signature_and_block(&inline_function_ident, &block);
+
+    if let Some(monomorphize) = turbo_fn.monomorphize() {
+        return monomorphized_function(
+            &turbo_fn,
+            monomorphize,
+            attrs,
+            vis,
+            &inline_function_ident,
+            inline_signature,
+            inline_block,
+            errors,
+        );
+    }
 
     let native_fn = NativeFn::new(
-        &ident.to_string(),
+        &turbo_fn.native_function_name(ident.unraw().to_string()),
         &inline_function_path,
-        turbo_fn.is_method(),
-        local_cells,
+        &turbo_fn,
     );
+    let static_vis = turbo_fn.static_vis();
     let native_function_ident = get_native_function_ident(ident);
     let native_function_ty = native_fn.ty();
     let native_function_def = native_fn.definition();
@@ -68,22 +122,398 @@ pub fn function(args: TokenStream, input: TokenStream) -> TokenStream {
     let native_function_id_ty = native_fn.id_ty();
     let native_function_id_def = native_fn.id_definition(&native_function_ident.clone().into());
 
+    let signature_ident = get_function_signature_ident(&native_function_ident);
+    let signature_string = turbo_fn.signature_string();
+    let return_type_ident = get_function_return_type_ident(&native_function_ident);
+    let return_type_string = turbo_fn.return_type_string();
+    let module_path_ident = get_function_module_path_ident(&native_function_ident);
+    let module_path = turbo_fn.module_path();
+    let parameter_names_ident = get_function_parameter_names_ident(&native_function_ident);
+    let parameter_names = turbo_fn.parameter_idents().map(ToString::to_string);
+    let arg_count_ident = get_function_arg_count_ident(&native_function_ident);
+    let arg_count = turbo_fn.arg_count();
+    let definition_context_ident = get_function_definition_context_ident(&native_function_ident);
+    let definition_context = turbo_fn.definition_context().function_type();
+    let local_cells_ident = get_function_local_cells_ident(&native_function_ident);
+    let local_cells = native_fn.local_cells();
+    let is_method_ident = get_function_is_method_ident(&native_function_ident);
+    let is_method = native_fn.is_method();
+    let has_receiver_ident = get_function_has_receiver_ident(&native_function_ident);
+    let has_receiver = turbo_fn.is_method();
+
     let exposed_signature = turbo_fn.signature();
-    let exposed_block = turbo_fn.static_block(&native_function_id_ident);
+    let exposed_block = turbo_fn
+        .no_cache_block(&inline_function_ident)
+        .unwrap_or_else(|| turbo_fn.static_block(&native_function_id_ident));
+    let smoke_test = turbo_fn.smoke_test();
+    let call_count_definitions = turbo_fn.call_count_definitions(&vis);
+    let last_duration_definitions = turbo_fn.last_duration_definitions(&vis);
+    let invalidator_definition = turbo_fn.invalidator_definition(&vis, &native_function_id_ident);
+    let untracked_definition = turbo_fn.untracked_definition(&vis, &inline_function_ident);
+    let key_definitions = turbo_fn.key_definitions();
+    let deprecated_attribute = turbo_fn.deprecated_attribute();
+    let exposed_doc_definition = turbo_fn.doc_signature().map(|doc_signature| {
+        quote! {
+            #(#attrs)*
+            #deprecated_attribute
+            #[cfg(doc)]
+            #vis #doc_signature {
+                unimplemented!()
+            }
+        }
+    });
+    let cfg_not_doc = exposed_doc_definition
+        .is_some()
+        .then(|| quote! { #[cfg(not(doc))] });
+
+    let expanded = quote! {
+        #(#attrs)*
+        #deprecated_attribute
+        #cfg_not_doc
+        #vis #exposed_signature #exposed_block
+        #exposed_doc_definition
+
+        #args_struct_definition
+
+        #key_definitions
+
+        #(#attrs)*
+        #[doc(hidden)]
+        #inline_signature #inline_block
+
+        #[doc(hidden)]
+        #static_vis static #native_function_ident: #native_function_ty = #native_function_def;
+
+        #[doc(hidden)]
+        #static_vis static #native_function_id_ident: #native_function_id_ty = #native_function_id_def;
+
+        #[doc(hidden)]
+        #static_vis const #signature_ident: &str = #signature_string;
+        #[doc(hidden)]
+        #static_vis const #return_type_ident: &str = #return_type_string;
+        #[doc(hidden)]
+        #static_vis const #module_path_ident: &str = #module_path;
+        #[doc(hidden)]
+        #static_vis const #parameter_names_ident: &[&str] = &[#(#parameter_names),*];
+        #[doc(hidden)]
+        #static_vis const #arg_count_ident: usize = #arg_count;
+        #[doc(hidden)]
+        #static_vis const #definition_context_ident: &str = #definition_context;
+        #[doc(hidden)]
+        #static_vis const #local_cells_ident: bool = #local_cells;
+        #[doc(hidden)]
+        #static_vis const #is_method_ident: bool = #is_method;
+        #[doc(hidden)]
+        #static_vis const #has_receiver_ident: bool = #has_receiver;
+
+        #smoke_test
+
+        #call_count_definitions
+
+        #last_duration_definitions
+
+        #invalidator_definition
+
+        #untracked_definition
+    };
+    let expanded = cfg_gate_items(&cfg_attributes(&attrs), expanded);
 
     quote! {
+        #expanded
+
+        #(#errors)*
+    }
+    .into()
+}
+
+/// Packs the arguments of a `#[turbo_tasks::function(args_struct)]` into a `<Name>Args` struct.
+///
+/// Returns the signature taking the struct as its only argument, the block destructuring it into
+/// the original argument patterns, and the definition of the struct and its builder.
+fn pack_arguments(
+    vis: &Visibility,
+    sig: &Signature,
+    block: &Block,
+) -> syn::Result<(Signature, Box<Block>, TokenStream2)> {
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "\"args_struct\" is not supported on generic functions",
+        ));
+    }
+
+    let ident = sig.ident.unraw();
+    let name = ident
+        .to_string()
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<String>();
+    let args_ident = format_ident!("{name}Args", span = sig.ident.span());
+    let builder_ident = format_ident!("{name}ArgsBuilder", span = sig.ident.span());
+
+    // Arguments bound by a pattern are fields with a generated name, which must not clash with
+    // any other name bound in the signature.
+    let mut bound_idents = HashSet::new();
+    for input in &sig.inputs {
+        if let FnArg::Typed(PatType { pat, .. }) = input {
+            collect_bound_idents(pat, &mut bound_idents);
+        }
+    }
+
+    let mut field_idents = Vec::with_capacity(sig.inputs.len());
+    let mut field_types = Vec::with_capacity(sig.inputs.len());
+    let mut field_pats = Vec::with_capacity(sig.inputs.len());
+    for (index, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(PatType { attrs, pat, ty, .. }) = input else {
+            return Err(syn::Error::new_spanned(
+                input,
+                "\"args_struct\" is not supported on methods",
+            ));
+        };
+        if let Some(attr) = attrs.first() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "argument attributes are not supported with \"args_struct\", the arguments are \
+                 fields of a single task input",
+            ));
+        }
+        field_idents.push(match &**pat {
+            Pat::Ident(PatIdent { ident, .. }) => ident.clone(),
+            _ => pattern_argument_ident(pat, index + 1, &bound_idents),
+        });
+        field_types.push(ty);
+        field_pats.push(pat);
+    }
+
+    let mut packed_sig = sig.clone();
+    packed_sig.inputs = parse_quote! { args: #args_ident };
+    let packed_block = Box::new(parse_quote! {
+        {
+            let #args_ident { #(#field_idents: #field_pats),* } = args;
+            #block
+        }
+    });
+
+    let args_doc = format!(" The arguments of [`{ident}`].");
+    let builder_doc = format!(" A builder for [`{args_ident}`].");
+    let missing_messages = field_idents
+        .iter()
+        .map(|field| format!("the argument `{field}` of `{ident}` is not set"));
+    let build_doc = format!(" Returns an error if an argument of [`{ident}`] isn't set.");
+    let definition = quote! {
+        #[doc = #args_doc]
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            turbo_tasks::TaskInput,
+            turbo_tasks::trace::TraceRawVcs,
+            turbo_tasks::macro_helpers::serde::Serialize,
+            turbo_tasks::macro_helpers::serde::Deserialize,
+        )]
+        #[serde(crate = "turbo_tasks::macro_helpers::serde")]
+        #vis struct #args_ident {
+            #(#vis #field_idents: #field_types,)*
+        }
+
+        impl #args_ident {
+            #vis fn builder() -> #builder_ident {
+                #builder_ident::default()
+            }
+        }
+
+        #[doc = #builder_doc]
+        #[derive(Default)]
+        #vis struct #builder_ident {
+            #(#field_idents: Option<#field_types>,)*
+        }
+
+        impl #builder_ident {
+            #(
+                #vis fn #field_idents(mut self, #field_idents: #field_types) -> Self {
+                    self.#field_idents = Some(#field_idents);
+                    self
+                }
+            )*
+
+            #[doc = #build_doc]
+            #vis fn build(self) -> turbo_tasks::Result<#args_ident> {
+                Ok(#args_ident {
+                    #(
+                        #field_idents: self
+                            .#field_idents
+                            .ok_or_else(|| turbo_tasks::Error::msg(#missing_messages))?,
+                    )*
+                })
+            }
+        }
+    };
+
+    Ok((packed_sig, packed_block, definition))
+}
+
+/// Expands a `#[turbo_tasks::function(monomorphize = [...])]`. Every instantiation gets its own
+/// native function, which are registered together through the `MonomorphizedFunctions` static
+/// that takes the place of the native function.
+///
+/// The exposed function requires the generic parameter to implement a sealed marker trait, which
+/// is only implemented for the listed types and maps them to their native function. Calling the
+/// function with any other type is a compile error.
+#[allow(clippy::too_many_arguments)]
+fn monomorphized_function(
+    turbo_fn: &TurboFn,
+    monomorphize: &Monomorphize,
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    inline_function_ident: &Ident,
+    inline_signature: Signature,
+    inline_block: Block,
+    errors: Vec<TokenStream2>,
+) -> TokenStream {
+    let Monomorphize { param, types } = monomorphize;
+    let ident = turbo_fn.ident();
+    let native_function_ident = get_native_function_ident(ident);
+    let native_function_id_ident = get_native_function_id_ident(ident);
+    let static_vis = turbo_fn.static_vis();
+    let marker_ident = format_ident!("{native_function_ident}_MONOMORPHIZED");
+    let sealed_mod_ident = format_ident!("{native_function_ident}_SEALED");
+
+    let mut instance_definitions = Vec::with_capacity(types.len());
+    let mut instance_type_names = Vec::with_capacity(types.len());
+    let mut instance_idents = Vec::with_capacity(types.len());
+    for (index, ty) in types.iter().enumerate() {
+        let type_string = ty.to_token_stream().to_string().replace(' ', "");
+        let inline_function_path: ExprPath = parse_quote! { #inline_function_ident::<#ty> };
+        let native_fn = NativeFn::new(
+            &format!(
+                "{}<{type_string}>",
+                turbo_fn.native_function_name(ident.unraw().to_string())
+            ),
+            &inline_function_path,
+            turbo_fn,
+        );
+        let instance_ident = format_ident!("{native_function_ident}_{index}");
+        let instance_ty = native_fn.ty();
+        let instance_def = native_fn.definition();
+        let instance_id_ident = format_ident!("{native_function_id_ident}_{index}");
+        let instance_id_ty = native_fn.id_ty();
+        let instance_id_def = native_fn.id_definition(&instance_ident.clone().into());
+
+        // checks the bounds of the generic parameter, reporting errors at the type in the list
+        let assert_bounds = quote_spanned! {
+            ty.span() =>
+            const _: () = {
+                fn assert_bounds<#param>() {}
+                let _ = assert_bounds::<#ty>;
+            };
+        };
+
+        instance_definitions.push(quote! {
+            #assert_bounds
+
+            #[doc(hidden)]
+            #static_vis static #instance_ident: #instance_ty = #instance_def;
+
+            #[doc(hidden)]
+            #static_vis static #instance_id_ident: #instance_id_ty = #instance_id_def;
+
+            impl #sealed_mod_ident::Sealed for #ty {}
+
+            impl #marker_ident for #ty {
+                fn function_id() -> &'static turbo_tasks::FunctionId {
+                    &*#instance_id_ident
+                }
+            }
+        });
+        instance_type_names.push(type_string);
+        instance_idents.push(instance_ident);
+    }
+
+    let param_ident = &param.ident;
+    let mut exposed_signature = turbo_fn.signature();
+    exposed_signature
+        .generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! { #param_ident: #marker_ident });
+    let exposed_block = turbo_fn.monomorphized_static_block(&marker_ident);
+    let call_count_definitions = turbo_fn.call_count_definitions(&vis);
+    let last_duration_definitions = turbo_fn.last_duration_definitions(&vis);
+    let deprecated_attribute = turbo_fn.deprecated_attribute();
+
+    let signature_ident = get_function_signature_ident(&native_function_ident);
+    let signature_string = turbo_fn.signature_string();
+    let return_type_ident = get_function_return_type_ident(&native_function_ident);
+    let return_type_string = turbo_fn.return_type_string();
+    let module_path_ident = get_function_module_path_ident(&native_function_ident);
+    let module_path = turbo_fn.module_path();
+    let parameter_names_ident = get_function_parameter_names_ident(&native_function_ident);
+    let parameter_names = turbo_fn.parameter_idents().map(ToString::to_string);
+    let arg_count_ident = get_function_arg_count_ident(&native_function_ident);
+    let arg_count = turbo_fn.arg_count();
+    let definition_context_ident = get_function_definition_context_ident(&native_function_ident);
+    let definition_context = turbo_fn.definition_context().function_type();
+
+    let expanded = quote! {
         #(#attrs)*
+        #deprecated_attribute
         #vis #exposed_signature #exposed_block
 
         #(#attrs)*
         #[doc(hidden)]
-        #inline_signature #block
+        #inline_signature #inline_block
+
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        mod #sealed_mod_ident {
+            pub trait Sealed {}
+        }
+
+        /// Implemented for the types the function is monomorphized for.
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #vis trait #marker_ident: #sealed_mod_ident::Sealed {
+            fn function_id() -> &'static turbo_tasks::FunctionId;
+        }
+
+        #(#instance_definitions)*
 
         #[doc(hidden)]
-        pub(crate) static #native_function_ident: #native_function_ty = #native_function_def;
+        #static_vis static #native_function_ident: turbo_tasks::macro_helpers::MonomorphizedFunctions =
+            turbo_tasks::macro_helpers::MonomorphizedFunctions::new(
+                &[#(#instance_type_names),*],
+                &[#(&#instance_idents),*],
+            );
 
         #[doc(hidden)]
-        pub(crate) static #native_function_id_ident: #native_function_id_ty = #native_function_id_def;
+        #static_vis const #signature_ident: &str = #signature_string;
+        #[doc(hidden)]
+        #static_vis const #return_type_ident: &str = #return_type_string;
+        #[doc(hidden)]
+        #static_vis const #module_path_ident: &str = #module_path;
+        #[doc(hidden)]
+        #static_vis const #parameter_names_ident: &[&str] = &[#(#parameter_names),*];
+        #[doc(hidden)]
+        #static_vis const #arg_count_ident: usize = #arg_count;
+        #[doc(hidden)]
+        #static_vis const #definition_context_ident: &str = #definition_context;
+
+        #call_count_definitions
+
+        #last_duration_definitions
+    };
+    let expanded = cfg_gate_items(&cfg_attributes(&attrs), expanded);
+
+    quote! {
+        #expanded
 
         #(#errors)*
     }
@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, spanned::Spanned, visit_mut::VisitMut, GenericParam, Lifetime, Type};
-use turbo_tasks_macros_shared::{get_type_ident, GenericTypeInput};
+use turbo_tasks_macros_shared::{get_type_ident, GenericTypeInput, SpanDiagnosticExt};
 
 use crate::value_macro::value_type_and_register;
 
@@ -13,20 +13,17 @@ pub fn generic_type(input: TokenStream) -> TokenStream {
             syn::GenericParam::Type(ty) => {
                 if ty.ident == "Vc" {
                     ty.span()
-                        .unwrap()
                         .error("Vc is a reserved name in generic_type")
                         .emit();
                 }
             }
             syn::GenericParam::Lifetime(lt) => {
                 lt.span()
-                    .unwrap()
                     .error("lifetime parameters are not supported in generic_type")
                     .emit();
             }
             syn::GenericParam::Const(c) => {
                 c.span()
-                    .unwrap()
                     .error("const parameters are not supported in generic_type")
                     .emit();
             }
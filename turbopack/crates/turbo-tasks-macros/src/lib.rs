@@ -1,13 +1,15 @@
 #![allow(internal_features)]
-#![feature(proc_macro_diagnostic)]
+// The generated code uses unstable features (e.g. `min_specialization`) on behalf of the calling
+// crate, which requires `#[allow_internal_unstable]` on the macros. This still pins the crate to
+// nightly, even though diagnostics no longer need it.
 #![feature(allow_internal_unstable)]
-#![feature(box_patterns)]
 
 mod derive;
 mod func;
 mod function_macro;
 mod generic_type_macro;
 mod primitive_macro;
+mod test_macro;
 mod value_impl_macro;
 mod value_macro;
 mod value_trait_macro;
@@ -17,36 +19,54 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 use proc_macro_error::proc_macro_error;
 
+#[proc_macro_error]
 #[proc_macro_derive(TraceRawVcs, attributes(turbo_tasks))]
 pub fn derive_trace_raw_vcs_attr(input: TokenStream) -> TokenStream {
     derive::derive_trace_raw_vcs(input)
 }
 
+#[proc_macro_error]
 #[proc_macro_derive(ResolvedValue, attributes(turbo_tasks))]
 pub fn derive_resolved_value_attr(input: TokenStream) -> TokenStream {
     derive::derive_resolved_value(input)
 }
 
+#[proc_macro_error]
+#[proc_macro_derive(OperationValue, attributes(turbo_tasks))]
+pub fn derive_operation_value_attr(input: TokenStream) -> TokenStream {
+    derive::derive_operation_value(input)
+}
+
+#[proc_macro_error]
 #[proc_macro_derive(ValueDebug, attributes(turbo_tasks))]
 pub fn derive_value_debug_attr(input: TokenStream) -> TokenStream {
     derive::derive_value_debug(input)
 }
 
+#[proc_macro_error]
 #[proc_macro_derive(ValueDebugFormat, attributes(turbo_tasks))]
 pub fn derive_value_debug_format_attr(input: TokenStream) -> TokenStream {
     derive::derive_value_debug_format(input)
 }
 
+#[proc_macro_error]
 #[proc_macro_derive(DeterministicHash, attributes(turbo_tasks))]
 pub fn derive_deterministic_hash(input: TokenStream) -> TokenStream {
     derive::derive_deterministic_hash(input)
 }
 
+#[proc_macro_error]
 #[proc_macro_derive(TaskInput, attributes(turbo_tasks))]
 pub fn derive_task_input(input: TokenStream) -> TokenStream {
     derive::derive_task_input(input)
 }
 
+#[proc_macro_error]
+#[proc_macro_derive(FlattenTaskInput)]
+pub fn derive_flatten_task_input(input: TokenStream) -> TokenStream {
+    derive::derive_flatten_task_input(input)
+}
+
 /// Creates a Vc<Value> struct for a `struct` or `enum` that represent
 /// that type placed into a cell in a Task.
 ///
@@ -99,6 +119,11 @@ pub fn derive_task_input(input: TokenStream) -> TokenStream {
 ///   [turbo_tasks::TypedForInput].
 /// - "none": Disables serialization and prevents deriving the traits.
 ///
+/// With "auto" and "auto_for_input", `#[serde(...)]` attributes on the type, its
+/// variants and its fields apply to the derived traits, e.g. `#[serde(default)]`
+/// for a field added to a type that's already serialized. With "none", they are
+/// removed.
+///
 /// ### `shared`
 ///
 /// Sets both `cell = "shared"` and `into = "shared"`
@@ -109,13 +134,30 @@ pub fn derive_task_input(input: TokenStream) -> TokenStream {
 ///
 /// ### `transparent`
 ///
-/// If applied to a unit struct (e.g. `struct Wrapper(Value)`) the outer struct
+/// If applied to a unit struct (e.g. `struct Wrapper(Value)`) or a
+/// single-variant enum (e.g. `enum Wrapper { Variant(Value) }`) the outer type
 /// is skipped for all operations (cell, into, reading).
 ///
 /// No value.
 ///
 /// Example: `#[turbo_tasks::value(transparent)]`
 ///
+/// ### `map_get`
+///
+/// Generates a `get(self: Vc<Self>, key: K) -> Vc<Option<V>>` task function
+/// for a transparent value wrapping a map. Requires `Option<V>` to be a value
+/// type.
+///
+/// Example: `#[turbo_tasks::value(transparent, map_get)]`
+///
+/// ### `map_traits`
+///
+/// Implements `Deref` and `DerefMut` to the map, `IntoIterator` for references
+/// and `FromIterator` for its entries on a transparent value wrapping a map
+/// (`IndexMap`, `FxIndexMap`, `HashMap`, `FxHashMap`, `BTreeMap` or `AutoMap`).
+///
+/// Example: `#[turbo_tasks::value(transparent, map_traits)]`
+///
 /// ### `resolved`
 ///
 /// A shorthand syntax for
@@ -123,6 +165,16 @@ pub fn derive_task_input(input: TokenStream) -> TokenStream {
 ///
 /// Example: `#[turbo_tasks::value(resolved)]`
 ///
+/// ### `monomorphize`
+///
+/// Required for a value type with a generic type parameter. A separate value
+/// type is registered for each listed type, named e.g. `Keyed<u32>`. Other
+/// instantiations can't be placed in a `Vc`.
+///
+/// Generic value types can't be `transparent` and don't implement `ValueDebug`.
+///
+/// Example: `#[turbo_tasks::value(monomorphize = [u32, RcStr])]`
+///
 /// TODO: add more documentation: presets, traits
 #[allow_internal_unstable(min_specialization, into_future, trivial_bounds)]
 #[proc_macro_error]
@@ -134,6 +186,16 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Allows this trait to be used as part of a trait object inside of a value
 /// cell, in the form of `Vc<dyn MyTrait>`.
 ///
+/// Associated consts are moved to a separate `MyTraitConsts` trait, as they'd
+/// prevent using the trait as `dyn MyTrait`. Consts in a
+/// `#[turbo_tasks::value_impl]` of the trait implement `MyTraitConsts`, which
+/// needs to be in scope there.
+///
+/// A `Vc<Box<dyn MyTrait>>` can be upcast to every value trait listed as a
+/// supertrait. The macro can't see the supertraits of those, so to upcast
+/// along a longer chain, list the indirect supertraits as well, e.g.
+/// `trait ChunkItem: Asset + ValueToString` when `Asset: ValueToString`.
+///
 /// ## Arguments
 ///
 /// Example: `#[turbo_tasks::value_trait(no_debug, resolved)]`
@@ -146,7 +208,10 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// ### 'resolved`
 ///
-/// Adds [`turbo_tasks::ResolvedValue`] as a supertrait of this trait.
+/// Adds [`turbo_tasks::ResolvedValue`] as a supertrait of this trait, and
+/// checks that every method of the trait returns a `ResolvedValue`, like
+/// `#[turbo_tasks::function(resolved)]`. Implementations share the trait's
+/// signatures, so they're checked as well.
 ///
 /// Example: `#[turbo_tasks::value_trait(resolved)]`
 #[allow_internal_unstable(min_specialization, into_future, trivial_bounds)]
@@ -163,6 +228,28 @@ pub fn function(args: TokenStream, input: TokenStream) -> TokenStream {
     function_macro::function(args, input)
 }
 
+/// Runs an async test within a fresh turbo-tasks instance backed by the
+/// memory backend.
+///
+/// The test body runs inside `run_once`, so it can call and await turbo-tasks
+/// functions. Afterwards the test waits for all foreground tasks and asserts
+/// that the instance is idle. The crate needs `tokio` and
+/// `turbo_tasks_memory` as (dev-)dependencies.
+///
+/// ## Arguments
+///
+/// ### `register`
+///
+/// Functions that register the turbo-tasks functions and value types used by
+/// the test. `turbo_tasks::register` is always called.
+///
+/// Example: `#[turbo_tasks::test(register = [turbo_tasks_fs::register, register])]`
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
+    test_macro::test(args, input)
+}
+
 #[allow_internal_unstable(min_specialization, into_future, trivial_bounds)]
 #[proc_macro_error]
 #[proc_macro_attribute]
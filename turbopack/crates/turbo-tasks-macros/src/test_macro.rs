@@ -0,0 +1,128 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    Expr, ExprArray, ExprPath, Ident, ItemFn, ReturnType, Token,
+};
+use turbo_tasks_macros_shared::SpanDiagnosticExt;
+
+/// The arguments of `#[turbo_tasks::test(register = [...])]`.
+#[derive(Default)]
+struct TestArguments {
+    /// Functions registering the turbo-tasks functions and value types used by the test, e.g.
+    /// `register = [turbo_tasks_fs::register, register]`. `turbo_tasks::register` is always
+    /// called.
+    register: Vec<ExprPath>,
+}
+
+impl Parse for TestArguments {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut parsed_args = TestArguments::default();
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            if ident != "register" {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "unexpected token, expected \"register\"",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            match input.parse::<Expr>()? {
+                Expr::Path(path) => parsed_args.register.push(path),
+                Expr::Array(ExprArray { elems, .. }) => {
+                    for elem in elems {
+                        let Expr::Path(path) = elem else {
+                            return Err(syn::Error::new_spanned(
+                                elem,
+                                "expected a path to a function",
+                            ));
+                        };
+                        parsed_args.register.push(path);
+                    }
+                }
+                expr => {
+                    return Err(syn::Error::new_spanned(
+                        expr,
+                        "expected a path to a function, or a list of paths",
+                    ))
+                }
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(parsed_args)
+    }
+}
+
+pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
+    let TestArguments { register } = parse_macro_input!(args as TestArguments);
+    let item = parse_macro_input!(input as ItemFn);
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = &item;
+
+    if sig.asyncness.is_none() {
+        sig.fn_token
+            .span()
+            .error("#[turbo_tasks::test] functions must be async")
+            .emit();
+        return quote! { #item }.into();
+    }
+    if !sig.inputs.is_empty() {
+        sig.inputs
+            .span()
+            .error("#[turbo_tasks::test] functions cannot take arguments")
+            .emit();
+        return quote! { #item }.into();
+    }
+    if !sig.generics.params.is_empty() {
+        sig.generics
+            .span()
+            .error("#[turbo_tasks::test] functions do not support generic parameters")
+            .emit();
+        return quote! { #item }.into();
+    }
+
+    let ident = &sig.ident;
+    let output = &sig.output;
+    // The body runs within a once task, which must return a `Result`. A test returning a `Result`
+    // itself returns the result of the task.
+    let (body, result) = match output {
+        ReturnType::Default => (
+            quote! {
+                #block
+                Ok(())
+            },
+            quote! { result.unwrap() },
+        ),
+        ReturnType::Type(..) => (quote! { #block }, quote! { result }),
+    };
+
+    quote! {
+        #(#attrs)*
+        #[tokio::test]
+        #vis async fn #ident() #output {
+            turbo_tasks::register();
+            #(#register();)*
+            let tt = turbo_tasks::TurboTasks::new(turbo_tasks_memory::MemoryBackend::default());
+            let result = tt.run_once(async move #body).await;
+            tt.wait_foreground_done().await;
+            assert_eq!(
+                tt.get_in_progress_count(),
+                0,
+                "turbo-tasks should be idle after the test `{}`",
+                stringify!(#ident),
+            );
+            tt.stop_and_wait().await;
+            #result
+        }
+    }
+    .into()
+}
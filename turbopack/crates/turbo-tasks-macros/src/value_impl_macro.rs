@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     parse::{Parse, ParseStream},
     parse_macro_input, parse_quote,
@@ -10,12 +10,19 @@ use syn::{
     MetaNameValue, Path, Result, Token, Type,
 };
 use turbo_tasks_macros_shared::{
+    get_function_arg_count_ident, get_function_definition_context_ident,
+    get_function_has_receiver_ident, get_function_is_method_ident, get_function_local_cells_ident,
+    get_function_module_path_ident, get_function_parameter_names_ident,
+    get_function_return_type_ident, get_function_signature_ident,
     get_inherent_impl_function_id_ident, get_inherent_impl_function_ident, get_path_ident,
-    get_register_trait_methods_ident, get_trait_impl_function_id_ident,
+    get_register_trait_methods_ident, get_trait_consts_ident, get_trait_impl_function_id_ident,
     get_trait_impl_function_ident, get_type_ident,
 };
 
-use crate::func::{DefinitionContext, FunctionArguments, MaybeParenthesized, NativeFn, TurboFn};
+use crate::func::{
+    cfg_attributes, cfg_gate_items, DefinitionContext, FunctionArguments, MaybeParenthesized,
+    NativeFn, TurboFn,
+};
 
 fn is_attribute(attr: &Attribute, name: &str) -> bool {
     let path = &attr.path;
@@ -59,6 +66,37 @@ fn split_function_attributes<'a>(
     (func_args, attrs)
 }
 
+/// Is `item` a method marked with `#[turbo_tasks::function]`?
+fn is_function_method(item: &ImplItem) -> bool {
+    match item {
+        ImplItem::Method(method) => method
+            .attrs
+            .iter()
+            .any(|attr| is_attribute(attr, "function")),
+        _ => false,
+    }
+}
+
+/// Returns an error if a `#[turbo_tasks::function]` attribute is applied to an impl item that isn't
+/// a method, such as an associated const.
+fn reject_non_method_function_attribute(item: &ImplItem) -> Option<TokenStream2> {
+    let (attrs, kind) = match item {
+        ImplItem::Method(_) => return None,
+        ImplItem::Const(item) => (&item.attrs, "an associated const"),
+        ImplItem::Type(item) => (&item.attrs, "an associated type"),
+        ImplItem::Macro(item) => (&item.attrs, "a macro invocation"),
+        _ => return None,
+    };
+    let attr = attrs.iter().find(|attr| is_attribute(attr, "function"))?;
+    Some(
+        Error::new(
+            attr.span(),
+            format!("#[turbo_tasks::function] expected a method, but was applied to {kind}"),
+        )
+        .to_compile_error(),
+    )
+}
+
 struct ValueImplArguments {
     ident: Option<LitStr>,
 }
@@ -106,6 +144,16 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
         let mut errors = Vec::new();
 
         for item in items.iter() {
+            if let Some(error) = reject_non_method_function_attribute(item) {
+                errors.push(error);
+                continue;
+            }
+            // Consts, associated types and plain methods are passed through unchanged, so that a
+            // type's whole API can live in one impl block
+            if !is_function_method(item) {
+                exposed_impl_items.push(item.to_token_stream());
+                continue;
+            }
             if let ImplItem::Method(ImplItemMethod {
                 attrs,
                 vis,
@@ -119,14 +167,6 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                 let func_args = func_args
                     .inspect_err(|err| errors.push(err.to_compile_error()))
                     .unwrap_or_default();
-                let local_cells = func_args.local_cells.is_some();
-
-                // TODO(alexkirsz) These should go into their own utilities.
-                let inline_function_ident: Ident =
-                    Ident::new(&format!("{}_inline", ident), ident.span());
-                let inline_function_path: ExprPath = parse_quote! { <#ty>::#inline_function_ident };
-                let mut inline_signature = sig.clone();
-                inline_signature.ident = inline_function_ident;
 
                 let Some(turbo_fn) =
                     TurboFn::new(sig, DefinitionContext::ValueInherentImpl, func_args)
@@ -135,12 +175,19 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                         // An error occurred while parsing the function signature.
                     };
                 };
+                turbo_fn.emit_warnings();
+
+                // TODO(alexkirsz) These should go into their own utilities.
+                let inline_function_ident: Ident = format_ident!("{}_inline", ident);
+                let inline_function_path: ExprPath = parse_quote! { <#ty>::#inline_function_ident };
+                let (inline_signature, inline_block) =
+                    turbo_fn.inline_signature_and_block(&inline_function_ident, block);
 
                 let native_fn = NativeFn::new(
-                    &format!("{ty}::{ident}", ty = ty.to_token_stream()),
+                    &turbo_fn
+                        .native_function_name(format!("{ty}::{ident}", ty = ty.to_token_stream())),
                     &inline_function_path,
-                    turbo_fn.is_method(),
-                    local_cells,
+                    &turbo_fn,
                 );
 
                 let native_function_ident = get_inherent_impl_function_ident(ty_ident, ident);
@@ -152,14 +199,51 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                     #native_function_ident
                 });
 
+                let signature_ident = get_function_signature_ident(&native_function_ident);
+                let signature_string = turbo_fn.signature_string();
+                let return_type_ident = get_function_return_type_ident(&native_function_ident);
+                let return_type_string = turbo_fn.return_type_string();
+                let module_path_ident = get_function_module_path_ident(&native_function_ident);
+                let parameter_names_ident =
+                    get_function_parameter_names_ident(&native_function_ident);
+                let parameter_names = turbo_fn.parameter_idents().map(ToString::to_string);
+                let arg_count_ident = get_function_arg_count_ident(&native_function_ident);
+                let arg_count = turbo_fn.arg_count();
+                let definition_context_ident =
+                    get_function_definition_context_ident(&native_function_ident);
+                let definition_context = turbo_fn.definition_context().function_type();
+                let local_cells_ident = get_function_local_cells_ident(&native_function_ident);
+                let local_cells = native_fn.local_cells();
+                let is_method_ident = get_function_is_method_ident(&native_function_ident);
+                let is_method = native_fn.is_method();
+                let has_receiver_ident = get_function_has_receiver_ident(&native_function_ident);
+                let has_receiver = turbo_fn.is_method();
+
                 let turbo_signature = turbo_fn.signature();
                 let turbo_block = turbo_fn.static_block(&native_function_id_ident);
+                let deprecated_attribute = turbo_fn.deprecated_attribute();
+                let exposed_doc_definition = turbo_fn.doc_signature().map(|doc_signature| {
+                    quote! {
+                        #(#attrs)*
+                        #deprecated_attribute
+                        #[cfg(doc)]
+                        #vis #doc_signature {
+                            unimplemented!()
+                        }
+                    }
+                });
+                let cfg_not_doc = exposed_doc_definition
+                    .is_some()
+                    .then(|| quote! { #[cfg(not(doc))] });
                 exposed_impl_items.push(quote! {
                     #(#attrs)*
+                    #deprecated_attribute
+                    #cfg_not_doc
                     #vis #turbo_signature #turbo_block
+                    #exposed_doc_definition
                 });
 
-                all_definitions.push(quote! {
+                let definitions = quote! {
                     #[doc(hidden)]
                     impl #ty {
                         // By declaring the native function's body within an `impl` block, we ensure that `Self` refers
@@ -175,14 +259,36 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                         #(#attrs)*
                         #[doc(hidden)]
                         #[deprecated(note = "This function is only exposed for use in macros. Do not call it directly.")]
-                        pub(self) #inline_signature #block
+                        pub(self) #inline_signature #inline_block
                     }
 
                     #[doc(hidden)]
                     pub(crate) static #native_function_ident: #native_function_ty = <#ty>::#native_function_ident;
                     #[doc(hidden)]
                     pub(crate) static #native_function_id_ident: #native_function_id_ty = <#ty>::#native_function_id_ident;
-                })
+                    #[doc(hidden)]
+                    pub(crate) const #signature_ident: &str = #signature_string;
+                    #[doc(hidden)]
+                    pub(crate) const #return_type_ident: &str = #return_type_string;
+                    #[doc(hidden)]
+                    pub(crate) const #module_path_ident: &str = module_path!();
+                    #[doc(hidden)]
+                    pub(crate) const #parameter_names_ident: &[&str] = &[#(#parameter_names),*];
+                    #[doc(hidden)]
+                    pub(crate) const #arg_count_ident: usize = #arg_count;
+                    #[doc(hidden)]
+                    pub(crate) const #definition_context_ident: &str = #definition_context;
+                    #[doc(hidden)]
+                    pub(crate) const #local_cells_ident: bool = #local_cells;
+                    #[doc(hidden)]
+                    pub(crate) const #is_method_ident: bool = #is_method;
+                    #[doc(hidden)]
+                    pub(crate) const #has_receiver_ident: bool = #has_receiver;
+                };
+                all_definitions.push(cfg_gate_items(
+                    &cfg_attributes(attrs.iter().copied()),
+                    definitions,
+                ));
             }
         }
 
@@ -209,12 +315,31 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
 
         let register = get_register_trait_methods_ident(&trait_ident, ty_ident);
 
+        // The consts of the value trait are declared in a separate trait, see
+        // `get_trait_consts_ident`
+        let const_items: Vec<_> = items
+            .iter()
+            .filter(|item| matches!(item, ImplItem::Const(_)))
+            .filter(|item| reject_non_method_function_attribute(item).is_none())
+            .collect();
+        let consts_impl = (!const_items.is_empty()).then(|| {
+            let mut consts_trait_path = trait_path.clone();
+            let last_segment = consts_trait_path.segments.last_mut().expect("non-empty");
+            last_segment.ident = get_trait_consts_ident(&last_segment.ident);
+            quote! {
+                impl #impl_generics #consts_trait_path for #ty #where_clause {
+                    #(#const_items)*
+                }
+            }
+        });
+
         let mut trait_registers = Vec::new();
         let mut trait_functions = Vec::with_capacity(items.len());
         let mut all_definitions = Vec::with_capacity(items.len());
         let mut errors = Vec::new();
 
         for item in items.iter() {
+            errors.extend(reject_non_method_function_attribute(item));
             if let ImplItem::Method(ImplItemMethod {
                 sig, attrs, block, ..
             }) = item
@@ -225,7 +350,6 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                 let func_args = func_args
                     .inspect_err(|err| errors.push(err.to_compile_error()))
                     .unwrap_or_default();
-                let local_cells = func_args.local_cells.is_some();
 
                 let Some(turbo_fn) =
                     TurboFn::new(sig, DefinitionContext::ValueTraitImpl, func_args)
@@ -234,28 +358,25 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                         // An error occurred while parsing the function signature.
                     };
                 };
+                turbo_fn.emit_warnings();
 
                 // TODO(alexkirsz) These should go into their own utilities.
-                let inline_function_ident: Ident =
-                    Ident::new(&format!("{}_inline", ident), ident.span());
-                let inline_extension_trait_ident = Ident::new(
-                    &format!("{}_{}_{}_inline", ty_ident, trait_ident, ident),
-                    ident.span(),
-                );
+                let inline_function_ident: Ident = format_ident!("{}_inline", ident);
+                let inline_extension_trait_ident =
+                    format_ident!("{}_{}_{}_inline", ty_ident, trait_ident, ident);
                 let inline_function_path: ExprPath =
                     parse_quote! { <#ty as #inline_extension_trait_ident>::#inline_function_ident };
-                let mut inline_signature = sig.clone();
-                inline_signature.ident = inline_function_ident;
+                let (inline_signature, inline_block) =
+                    turbo_fn.inline_signature_and_block(&inline_function_ident, block);
 
                 let native_fn = NativeFn::new(
-                    &format!(
+                    &turbo_fn.native_function_name(format!(
                         "<{ty} as {trait_path}>::{ident}",
                         ty = ty.to_token_stream(),
                         trait_path = trait_path.to_token_stream()
-                    ),
+                    )),
                     &inline_function_path,
-                    turbo_fn.is_method(),
-                    local_cells,
+                    &turbo_fn,
                 );
 
                 let native_function_ident =
@@ -270,6 +391,26 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                     #native_function_ident
                 });
 
+                let signature_ident = get_function_signature_ident(&native_function_ident);
+                let signature_string = turbo_fn.signature_string();
+                let return_type_ident = get_function_return_type_ident(&native_function_ident);
+                let return_type_string = turbo_fn.return_type_string();
+                let module_path_ident = get_function_module_path_ident(&native_function_ident);
+                let parameter_names_ident =
+                    get_function_parameter_names_ident(&native_function_ident);
+                let parameter_names = turbo_fn.parameter_idents().map(ToString::to_string);
+                let arg_count_ident = get_function_arg_count_ident(&native_function_ident);
+                let arg_count = turbo_fn.arg_count();
+                let definition_context_ident =
+                    get_function_definition_context_ident(&native_function_ident);
+                let definition_context = turbo_fn.definition_context().function_type();
+                let local_cells_ident = get_function_local_cells_ident(&native_function_ident);
+                let local_cells = native_fn.local_cells();
+                let is_method_ident = get_function_is_method_ident(&native_function_ident);
+                let is_method = native_fn.is_method();
+                let has_receiver_ident = get_function_has_receiver_ident(&native_function_ident);
+                let has_receiver = turbo_fn.is_method();
+
                 let turbo_signature = turbo_fn.signature();
                 let turbo_block = turbo_fn.static_block(&native_function_id_ident);
 
@@ -278,7 +419,8 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                     #turbo_signature #turbo_block
                 });
 
-                all_definitions.push(quote! {
+                let cfg_attrs = cfg_attributes(attrs.iter().copied());
+                let definitions = quote! {
                     #[doc(hidden)]
                     #[allow(non_camel_case_types)]
                     trait #inline_extension_trait_ident: std::marker::Send {
@@ -306,16 +448,36 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                         #(#attrs)*
                         #[doc(hidden)]
                         #[deprecated(note = "This function is only exposed for use in macros. Do not call it directly.")]
-                        #inline_signature #block
+                        #inline_signature #inline_block
                     }
 
                     #[doc(hidden)]
                     pub(crate) static #native_function_ident: #native_function_ty = <#ty as #inline_extension_trait_ident>::#native_function_ident;
                     #[doc(hidden)]
                     pub(crate) static #native_function_id_ident: #native_function_id_ty = <#ty as #inline_extension_trait_ident>::#native_function_id_ident;
-                });
+                    #[doc(hidden)]
+                    pub(crate) const #signature_ident: &str = #signature_string;
+                    #[doc(hidden)]
+                    pub(crate) const #return_type_ident: &str = #return_type_string;
+                    #[doc(hidden)]
+                    pub(crate) const #module_path_ident: &str = module_path!();
+                    #[doc(hidden)]
+                    pub(crate) const #parameter_names_ident: &[&str] = &[#(#parameter_names),*];
+                    #[doc(hidden)]
+                    pub(crate) const #arg_count_ident: usize = #arg_count;
+                    #[doc(hidden)]
+                    pub(crate) const #definition_context_ident: &str = #definition_context;
+                    #[doc(hidden)]
+                    pub(crate) const #local_cells_ident: bool = #local_cells;
+                    #[doc(hidden)]
+                    pub(crate) const #is_method_ident: bool = #is_method;
+                    #[doc(hidden)]
+                    pub(crate) const #has_receiver_ident: bool = #has_receiver;
+                };
+                all_definitions.push(cfg_gate_items(&cfg_attrs, definitions));
 
                 trait_registers.push(quote! {
+                    #(#cfg_attrs)*
                     value.register_trait_method(<Box<dyn #trait_path> as turbo_tasks::VcValueTrait>::get_trait_type_id(), stringify!(#ident).into(), *#native_function_id_ident);
                 });
             }
@@ -338,6 +500,16 @@ pub fn value_impl(args: TokenStream, input: TokenStream) -> TokenStream {
                 #(#trait_functions)*
             }
 
+            #consts_impl
+
+            // reports a missing consts impl, e.g. when the impl doesn't declare a required const
+            const _: () = {
+                #[allow(dead_code)]
+                fn assert_value_trait_consts #impl_generics () #where_clause {
+                    turbo_tasks::macro_helpers::assert_value_trait_consts::<Box<dyn #trait_path>, #ty>();
+                }
+            };
+
             #(#all_definitions)*
             #(#errors)*
         }
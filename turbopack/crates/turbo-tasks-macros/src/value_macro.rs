@@ -2,19 +2,21 @@ use std::sync::OnceLock;
 
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
-use quote::{quote, quote_spanned, ToTokens};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use regex::Regex;
 use syn::{
+    bracketed,
     parse::{Parse, ParseStream},
     parse_macro_input, parse_quote,
     punctuated::Punctuated,
     spanned::Spanned,
-    Error, Fields, FieldsUnnamed, Generics, Item, ItemEnum, ItemStruct, Lit, LitStr, Meta,
-    MetaNameValue, Result, Token,
+    token, AngleBracketedGenericArguments, Attribute, Error, Fields, FieldsUnnamed,
+    GenericArgument, Generics, Item, ItemEnum, ItemStruct, Lit, LitStr, Meta, MetaNameValue,
+    PathArguments, Result, Token, Type, TypePath, Variant,
 };
 use turbo_tasks_macros_shared::{
     get_register_value_type_ident, get_value_type_id_ident, get_value_type_ident,
-    get_value_type_init_ident,
+    get_value_type_init_ident, SpanDiagnosticExt,
 };
 
 enum IntoMode {
@@ -114,6 +116,18 @@ struct ValueArguments {
     ///
     /// `Some(...)` if enabled, containing the span that enabled the derive.
     resolved: Option<Span>,
+    /// Should we generate a `get` task function for a transparent value wrapping a map?
+    ///
+    /// `Some(...)` if enabled, containing the span that enabled it.
+    map_get: Option<Span>,
+    /// Should we implement `Deref`, `DerefMut`, `IntoIterator` and `FromIterator` for a
+    /// transparent value wrapping a map?
+    ///
+    /// `Some(...)` if enabled, containing the span that enabled it.
+    map_traits: Option<Span>,
+    /// The types a generic value type is instantiated with, e.g. `monomorphize = [Foo, Bar]`.
+    /// Every instantiation is registered as a separate value type.
+    monomorphize: Option<(Span, Vec<Type>)>,
 }
 
 impl Parse for ValueArguments {
@@ -124,10 +138,38 @@ impl Parse for ValueArguments {
             cell_mode: CellMode::Shared,
             manual_eq: false,
             resolved: None,
+            map_get: None,
+            map_traits: None,
             transparent: false,
+            monomorphize: None,
         };
-        let punctuated: Punctuated<Meta, Token![,]> = input.parse_terminated(Meta::parse)?;
-        for meta in punctuated {
+        let mut metas = Vec::new();
+        while !input.is_empty() {
+            // `monomorphize = [...]` isn't a valid `Meta`, so it's parsed separately
+            if input.peek(syn::Ident) && input.peek2(Token![=]) && input.peek3(token::Bracket) {
+                let ident: Ident = input.parse()?;
+                if ident != "monomorphize" {
+                    return Err(Error::new_spanned(&ident, "expected \"monomorphize\""));
+                }
+                input.parse::<Token![=]>()?;
+                let content;
+                bracketed!(content in input);
+                let types: Punctuated<Type, Token![,]> = content.parse_terminated(Type::parse)?;
+                if types.is_empty() {
+                    return Err(Error::new_spanned(
+                        &ident,
+                        "\"monomorphize\" requires at least one type",
+                    ));
+                }
+                result.monomorphize = Some((ident.span(), types.into_iter().collect()));
+            } else {
+                metas.push(input.parse::<Meta>()?);
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        for meta in metas {
             match (
                 meta.path()
                     .get_ident()
@@ -182,12 +224,19 @@ impl Parse for ValueArguments {
                 ("resolved", Meta::Path(path)) => {
                     result.resolved = Some(path.span());
                 }
+                ("map_get", Meta::Path(path)) => {
+                    result.map_get = Some(path.span());
+                }
+                ("map_traits", Meta::Path(path)) => {
+                    result.map_traits = Some(path.span());
+                }
                 (_, meta) => {
                     return Err(Error::new_spanned(
                         &meta,
                         format!(
                             "unexpected {:?}, expected \"shared\", \"into\", \"serialization\", \
-                             \"cell\", \"eq\", \"transparent\"",
+                             \"cell\", \"eq\", \"transparent\", \"resolved\", \"map_get\", \
+                             \"map_traits\", \"monomorphize\"",
                             meta
                         ),
                     ))
@@ -208,65 +257,95 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
         manual_eq,
         transparent,
         resolved,
+        map_get,
+        map_traits,
+        monomorphize,
     } = parse_macro_input!(args as ValueArguments);
 
     let mut inner_type = None;
+    // The only variant of a transparent enum, `None` for a transparent struct.
+    let mut transparent_variant = None;
     if transparent {
-        if let Item::Struct(ItemStruct {
-            attrs,
-            fields: Fields::Unnamed(FieldsUnnamed { unnamed, .. }),
-            ..
-        }) = &mut item
-        {
-            if unnamed.len() == 1 {
-                let field = unnamed.iter().next().unwrap();
-                inner_type = Some(field.ty.clone());
-
-                // generate a type string to add to the docs
-                let inner_type_string = inner_type.to_token_stream().to_string();
-
-                // HACK: proc_macro2 inserts whitespace between every token. It's ugly, so
-                // remove it, assuming these whitespace aren't syntatically important. Using
-                // prettyplease (or similar) would be more correct, but slower and add another
-                // dependency.
-                static WHITESPACE_RE: OnceLock<Regex> = OnceLock::new();
-                // Remove whitespace, as long as there is a non-word character (e.g. `>` or `,`)
-                // on either side. Try not to remove whitespace between `dyn Trait`.
-                let whitespace_re = WHITESPACE_RE.get_or_init(|| {
-                    Regex::new(r"\b \B|\B \b|\B \B").expect("WHITESPACE_RE is valid")
-                });
-                let inner_type_string = whitespace_re.replace_all(&inner_type_string, "");
-
-                // Add a couple blank lines in case there's already a doc comment we're
-                // effectively appending to. If there's not, rustdoc will strip
-                // the leading whitespace.
-                let doc_str = format!(
-                    "\n\nThis is a [transparent value type][turbo_tasks::value#transparent] \
-                     wrapping [`{}`].",
-                    inner_type_string,
-                );
-
-                attrs.push(parse_quote! {
-                    #[doc = #doc_str]
-                });
-            }
+        let transparent_field = match &mut item {
+            Item::Struct(ItemStruct {
+                attrs,
+                fields: Fields::Unnamed(FieldsUnnamed { unnamed, .. }),
+                ..
+            }) if unnamed.len() == 1 => Some((attrs, &unnamed[0], None)),
+            Item::Enum(ItemEnum {
+                attrs, variants, ..
+            }) if variants.len() == 1 => match &variants[0] {
+                Variant {
+                    ident,
+                    fields: Fields::Unnamed(FieldsUnnamed { unnamed, .. }),
+                    ..
+                } if unnamed.len() == 1 => Some((attrs, &unnamed[0], Some(ident.clone()))),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some((attrs, field, variant)) = transparent_field {
+            inner_type = Some(field.ty.clone());
+            transparent_variant = variant;
+
+            // generate a type string to add to the docs
+            let inner_type_string = inner_type.to_token_stream().to_string();
+
+            // HACK: proc_macro2 inserts whitespace between every token. It's ugly, so
+            // remove it, assuming these whitespace aren't syntatically important. Using
+            // prettyplease (or similar) would be more correct, but slower and add another
+            // dependency.
+            static WHITESPACE_RE: OnceLock<Regex> = OnceLock::new();
+            // Remove whitespace, as long as there is a non-word character (e.g. `>` or `,`)
+            // on either side. Try not to remove whitespace between `dyn Trait`.
+            let whitespace_re = WHITESPACE_RE
+                .get_or_init(|| Regex::new(r"\b \B|\B \b|\B \B").expect("WHITESPACE_RE is valid"));
+            let inner_type_string = whitespace_re.replace_all(&inner_type_string, "");
+
+            // Add a couple blank lines in case there's already a doc comment we're
+            // effectively appending to. If there's not, rustdoc will strip
+            // the leading whitespace.
+            let doc_str = format!(
+                "\n\nThis is a [transparent value type][turbo_tasks::value#transparent] wrapping \
+                 [`{}`].",
+                inner_type_string,
+            );
+
+            attrs.push(parse_quote! {
+                #[doc = #doc_str]
+            });
         }
         if inner_type.is_none() {
             item.span()
-                .unwrap()
                 .error(
                     "#[turbo_tasks::value(transparent)] is only valid with single-item unit \
-                     structs",
+                     structs and single-variant enums",
                 )
                 .emit();
         }
     }
 
-    let ident = match &item {
-        Item::Enum(ItemEnum { ident, .. }) => ident,
-        Item::Struct(ItemStruct { ident, .. }) => ident,
+    // `#[serde(...)]` attributes (e.g. `#[serde(default)]` on a field added later) are passed
+    // through to the derived serialization. Without serialization, nothing would accept them.
+    if let SerializationMode::None = serialization_mode {
+        strip_serde_attributes(&mut item);
+    }
+
+    let (ident, vis, generics) = match &item {
+        Item::Enum(ItemEnum {
+            ident,
+            vis,
+            generics,
+            ..
+        }) => (ident, vis, generics),
+        Item::Struct(ItemStruct {
+            ident,
+            vis,
+            generics,
+            ..
+        }) => (ident, vis, generics),
         _ => {
-            item.span().unwrap().error("unsupported syntax").emit();
+            item.span().error("unsupported syntax").emit();
 
             return quote! {
                 #item
@@ -275,24 +354,66 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     };
 
-    let cell_mode = match cell_mode {
+    // A generic value type is instantiated once per `monomorphize`d type. Everything that needs
+    // a concrete type (the `VcValueType` impl, `cell`, ...) is generated for every instance.
+    let instance_types = match (&monomorphize, generics.params.is_empty()) {
+        (None, true) => vec![quote! { #ident }],
+        (None, false) => {
+            generics
+                .span()
+                .error("generic #[turbo_tasks::value] types must be monomorphized")
+                .help(
+                    "list the types the value is instantiated with, e.g. \
+                     #[turbo_tasks::value(monomorphize = [Foo, Bar])]",
+                )
+                .emit();
+            return quote! { #item }.into();
+        }
+        (Some((span, _)), _) if inner_type.is_some() => {
+            span.error("\"monomorphize\" cannot be used together with \"transparent\"")
+                .emit();
+            return quote! { #item }.into();
+        }
+        (Some((span, _)), _)
+            if generics.params.len() != 1 || generics.type_params().count() != 1 =>
+        {
+            span.error(
+                "\"monomorphize\" requires the value type to have exactly one generic type \
+                 parameter",
+            )
+            .emit();
+            return quote! { #item }.into();
+        }
+        (Some((_, types)), _) => types.iter().map(|ty| quote! { #ident<#ty> }).collect(),
+    };
+
+    let cell_mode = |ty: &proc_macro2::TokenStream| match cell_mode {
         CellMode::New => quote! {
-            turbo_tasks::VcCellNewMode<#ident>
+            turbo_tasks::VcCellNewMode<#ty>
         },
         CellMode::Shared => quote! {
-            turbo_tasks::VcCellSharedMode<#ident>
+            turbo_tasks::VcCellSharedMode<#ty>
         },
     };
 
-    let (cell_prefix, cell_access_content, read) = if let Some(inner_type) = &inner_type {
+    // Accesses the value wrapped by a transparent value type, optionally by reference.
+    let transparent_access = |value: proc_macro2::TokenStream,
+                              reference: proc_macro2::TokenStream| {
+        if let Some(variant) = &transparent_variant {
+            quote! {
+                match #value {
+                    #ident::#variant(inner) => inner,
+                }
+            }
+        } else {
+            quote! { #reference #value.0 }
+        }
+    };
+
+    let (cell_prefix, cell_access_content) = if inner_type.is_some() {
         (
             quote! { pub },
-            quote! {
-                content.0
-            },
-            quote! {
-                turbo_tasks::VcTransparentRead::<#ident, #inner_type, #ident>
-            },
+            transparent_access(quote! { content }, quote! {}),
         )
     } else {
         (
@@ -302,11 +423,19 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
                 quote! {}
             },
             quote! { content },
-            quote! {
-                turbo_tasks::VcDefaultRead::<#ident>
-            },
         )
     };
+    let read = |ty: &proc_macro2::TokenStream| {
+        if let Some(inner_type) = &inner_type {
+            quote! {
+                turbo_tasks::VcTransparentRead::<#ty, #inner_type, #ty>
+            }
+        } else {
+            quote! {
+                turbo_tasks::VcDefaultRead::<#ty>
+            }
+        }
+    };
 
     let cell_struct = quote! {
         /// Places a value in a cell of the current task.
@@ -335,18 +464,37 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
             let content = self;
             turbo_tasks::Vc::local_cell_private(#cell_access_content)
         }
+
+        /// Attempts to downcast a `Vc<Box<dyn Trait>>` of a value trait implemented by this type
+        /// to a `Vc<Self>`. This also resolves the `Vc`.
+        ///
+        /// Returns `None` if the underlying value isn't of this type.
+        #vis async fn try_downcast<T>(
+            vc: turbo_tasks::Vc<T>,
+        ) -> ::std::result::Result<
+            ::std::option::Option<turbo_tasks::Vc<Self>>,
+            turbo_tasks::ResolveTypeError,
+        >
+        where
+            T: turbo_tasks::VcValueTrait + ?Sized + Send,
+            Self: turbo_tasks::Upcast<T>,
+        {
+            turbo_tasks::Vc::try_resolve_downcast_type(vc).await
+        }
     };
 
-    let into = if let IntoMode::New | IntoMode::Shared = into_mode {
-        quote! {
-            impl Into<turbo_tasks::Vc<#ident>> for #ident {
-                fn into(self) -> turbo_tasks::Vc<#ident> {
-                    self.cell()
+    let into = |ty: &proc_macro2::TokenStream| {
+        if let IntoMode::New | IntoMode::Shared = into_mode {
+            quote! {
+                impl Into<turbo_tasks::Vc<#ty>> for #ty {
+                    fn into(self) -> turbo_tasks::Vc<#ty> {
+                        self.cell()
+                    }
                 }
             }
+        } else {
+            quote! {}
         }
-    } else {
-        quote! {}
     };
 
     let derive = match serialization_mode {
@@ -369,6 +517,12 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! {
             #[repr(transparent)]
         }
+    } else if monomorphize.is_some() {
+        // `ValueDebug` is implemented through a `#[turbo_tasks::value_impl]`, which doesn't support
+        // generic types.
+        quote! {
+            #[derive(turbo_tasks::debug::ValueDebugFormat)]
+        }
     } else {
         quote! {
             #[derive(
@@ -393,45 +547,46 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
         quote!()
     };
 
-    let new_value_type = match serialization_mode {
+    let new_value_type = |ty: &proc_macro2::TokenStream| match serialization_mode {
         SerializationMode::None => quote! {
-            turbo_tasks::ValueType::new::<#ident>()
+            turbo_tasks::ValueType::new::<#ty>()
         },
         SerializationMode::Auto | SerializationMode::Custom => {
             quote! {
-                turbo_tasks::ValueType::new_with_any_serialization::<#ident>()
+                turbo_tasks::ValueType::new_with_any_serialization::<#ty>()
             }
         }
         SerializationMode::AutoForInput | SerializationMode::CustomForInput => {
             quote! {
-                turbo_tasks::ValueType::new_with_magic_serialization::<#ident>()
+                turbo_tasks::ValueType::new_with_magic_serialization::<#ty>()
             }
         }
     };
 
-    let for_input_marker = match serialization_mode {
+    let for_input_marker = |ty: &proc_macro2::TokenStream| match serialization_mode {
         SerializationMode::None | SerializationMode::Auto | SerializationMode::Custom => quote! {},
         SerializationMode::AutoForInput | SerializationMode::CustomForInput => quote! {
-            impl turbo_tasks::TypedForInput for #ident {}
+            impl turbo_tasks::TypedForInput for #ty {}
         },
     };
 
     let value_debug_impl = if inner_type.is_some() {
         // For transparent values, we defer directly to the inner type's `ValueDebug`
         // implementation.
+        let inner = transparent_access(quote! { self }, quote! { & });
         quote! {
             #[turbo_tasks::value_impl]
             impl turbo_tasks::debug::ValueDebug for #ident {
                 #[turbo_tasks::function]
                 async fn dbg(&self) -> anyhow::Result<turbo_tasks::Vc<turbo_tasks::debug::ValueDebugString>> {
                     use turbo_tasks::debug::ValueDebugFormat;
-                    (&self.0).value_debug_format(usize::MAX).try_to_value_debug_string().await
+                    (#inner).value_debug_format(usize::MAX).try_to_value_debug_string().await
                 }
 
                 #[turbo_tasks::function]
                 async fn dbg_depth(&self, depth: usize) -> anyhow::Result<turbo_tasks::Vc<turbo_tasks::debug::ValueDebugString>> {
                     use turbo_tasks::debug::ValueDebugFormat;
-                    (&self.0).value_debug_format(depth).try_to_value_debug_string().await
+                    (#inner).value_debug_format(depth).try_to_value_debug_string().await
                 }
             }
         }
@@ -439,14 +594,107 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! {}
     };
 
-    let value_type_and_register_code = value_type_and_register(
-        ident,
-        quote! { #ident },
-        None,
-        read,
-        cell_mode,
-        new_value_type,
-    );
+    let map_entry_types = inner_type.as_ref().and_then(map_entry_types);
+    let map_impls = if let (Some(inner_type), Some((key_type, value_type))) =
+        (&inner_type, map_entry_types)
+    {
+        let inner_ref = transparent_access(quote! { self }, quote! { & });
+        let inner_mut = transparent_access(quote! { self }, quote! { &mut });
+        let constructor = if let Some(variant) = &transparent_variant {
+            quote! { #ident::#variant }
+        } else {
+            quote! { #ident }
+        };
+        let map_get_impl = map_get.is_some().then(|| {
+            quote! {
+                #[turbo_tasks::value_impl]
+                impl #ident {
+                    /// Returns the value of the entry for `key`, if any.
+                    #[turbo_tasks::function]
+                    #vis async fn get(
+                        self: turbo_tasks::Vc<Self>,
+                        key: #key_type,
+                    ) -> anyhow::Result<turbo_tasks::Vc<Option<#value_type>>> {
+                        Ok(turbo_tasks::Vc::cell(self.await?.get(&key).cloned()))
+                    }
+                }
+            }
+        });
+        let map_traits_impl = map_traits.is_some().then(|| {
+            quote! {
+                impl std::ops::Deref for #ident {
+                    type Target = #inner_type;
+
+                    fn deref(&self) -> &Self::Target {
+                        #inner_ref
+                    }
+                }
+
+                impl std::ops::DerefMut for #ident {
+                    fn deref_mut(&mut self) -> &mut Self::Target {
+                        #inner_mut
+                    }
+                }
+
+                impl<'a> IntoIterator for &'a #ident {
+                    type Item = <&'a #inner_type as IntoIterator>::Item;
+                    type IntoIter = <&'a #inner_type as IntoIterator>::IntoIter;
+
+                    fn into_iter(self) -> Self::IntoIter {
+                        (&**self).into_iter()
+                    }
+                }
+
+                impl FromIterator<(#key_type, #value_type)> for #ident {
+                    fn from_iter<I: IntoIterator<Item = (#key_type, #value_type)>>(iter: I) -> Self {
+                        #constructor(iter.into_iter().collect())
+                    }
+                }
+            }
+        });
+        quote! {
+            #map_traits_impl
+
+            #map_get_impl
+        }
+    } else {
+        if let Some(span) = map_get {
+            span.error("\"map_get\" requires a transparent value wrapping a map")
+                .emit();
+        }
+        if let Some(span) = map_traits {
+            span.error("\"map_traits\" requires a transparent value wrapping a map")
+                .emit();
+        }
+        quote! {}
+    };
+
+    let value_type_and_register_code = if let Some((_, types)) = &monomorphize {
+        let instances = types
+            .iter()
+            .zip(&instance_types)
+            .map(|(type_arg, ty)| MonomorphizedValueType {
+                type_name: type_arg.to_token_stream().to_string().replace(' ', ""),
+                ty: ty.clone(),
+                read: read(ty),
+                cell_mode: cell_mode(ty),
+                new_value_type: new_value_type(ty),
+            })
+            .collect::<Vec<_>>();
+        monomorphized_value_type_and_register(ident, &instances)
+    } else {
+        let ty = quote! { #ident };
+        value_type_and_register(
+            ident,
+            ty.clone(),
+            None,
+            read(&ty),
+            cell_mode(&ty),
+            new_value_type(&ty),
+        )
+    };
+    let intos = instance_types.iter().map(into);
+    let for_input_markers = instance_types.iter().map(for_input_marker);
 
     let expanded = quote! {
         #derive
@@ -455,22 +703,83 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
         #resolved_derive
         #item
 
-        impl #ident {
-            #cell_struct
-        }
+        #(
+            impl #instance_types {
+                #cell_struct
+            }
+        )*
 
-        #into
+        #(#intos)*
 
         #value_type_and_register_code
 
-        #for_input_marker
+        #(#for_input_markers)*
 
         #value_debug_impl
+
+        #map_impls
     };
 
     expanded.into()
 }
 
+/// Removes the `#[serde(...)]` attributes of the item, its variants and its fields.
+fn strip_serde_attributes(item: &mut Item) {
+    fn strip(attrs: &mut Vec<Attribute>) {
+        attrs.retain(|attr| !attr.path.is_ident("serde"));
+    }
+
+    match item {
+        Item::Struct(ItemStruct { attrs, fields, .. }) => {
+            strip(attrs);
+            fields.iter_mut().for_each(|field| strip(&mut field.attrs));
+        }
+        Item::Enum(ItemEnum {
+            attrs, variants, ..
+        }) => {
+            strip(attrs);
+            for variant in variants {
+                strip(&mut variant.attrs);
+                variant
+                    .fields
+                    .iter_mut()
+                    .for_each(|field| strip(&mut field.attrs));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The key and value types of a map type supported by transparent values, e.g. `IndexMap<K, V>`.
+fn map_entry_types(ty: &Type) -> Option<(&Type, &Type)> {
+    const MAP_TYPES: &[&str] = &[
+        "AutoMap",
+        "BTreeMap",
+        "FxHashMap",
+        "FxIndexMap",
+        "HashMap",
+        "IndexMap",
+    ];
+
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    if !MAP_TYPES.iter().any(|map_type| segment.ident == map_type) {
+        return None;
+    }
+    let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) =
+        &segment.arguments
+    else {
+        return None;
+    };
+    let mut types = args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    Some((types.next()?, types.next()?))
+}
+
 pub fn value_type_and_register(
     ident: &Ident,
     ty: proc_macro2::TokenStream,
@@ -538,3 +847,94 @@ pub fn value_type_and_register(
         }
     }
 }
+
+/// A concrete instantiation of a `monomorphize`d value type.
+struct MonomorphizedValueType {
+    /// The type argument as a string, used to derive the global name of the value type.
+    type_name: String,
+    ty: proc_macro2::TokenStream,
+    read: proc_macro2::TokenStream,
+    cell_mode: proc_macro2::TokenStream,
+    new_value_type: proc_macro2::TokenStream,
+}
+
+/// Like [`value_type_and_register`], but registers a value type for every instantiation of a
+/// `#[turbo_tasks::value(monomorphize = [...])]`, named after the global name of the generic type
+/// suffixed with the type argument, e.g. `my_crate::MyValue<u32>`.
+fn monomorphized_value_type_and_register(
+    ident: &Ident,
+    instances: &[MonomorphizedValueType],
+) -> proc_macro2::TokenStream {
+    let value_type_init_ident = get_value_type_init_ident(ident);
+    let value_type_id_ident = get_value_type_id_ident(ident);
+    let register_value_type_ident = get_register_value_type_ident(ident);
+    let names_ident = format_ident!("{value_type_init_ident}_NAMES");
+    let type_names = instances.iter().map(|instance| &instance.type_name);
+
+    let mut definitions = Vec::with_capacity(instances.len());
+    let mut registrations = Vec::with_capacity(instances.len());
+    for (index, instance) in instances.iter().enumerate() {
+        let MonomorphizedValueType {
+            ty,
+            read,
+            cell_mode,
+            new_value_type,
+            ..
+        } = instance;
+        let instance_init_ident = format_ident!("{value_type_init_ident}_{index}");
+        let instance_id_ident = format_ident!("{value_type_id_ident}_{index}");
+
+        definitions.push(quote! {
+            #[doc(hidden)]
+            static #instance_init_ident: turbo_tasks::macro_helpers::OnceCell<
+                turbo_tasks::ValueType,
+            > = turbo_tasks::macro_helpers::OnceCell::new();
+            #[doc(hidden)]
+            static #instance_id_ident: turbo_tasks::macro_helpers::Lazy<turbo_tasks::ValueTypeId> =
+                turbo_tasks::macro_helpers::Lazy::new(|| {
+                    turbo_tasks::registry::get_value_type_id(#instance_init_ident.get_or_init(|| {
+                        panic!(
+                            concat!(
+                                stringify!(#ty),
+                                " has not been initialized (this should happen via the generated register function)"
+                            )
+                        )
+                    }))
+                });
+
+            unsafe impl turbo_tasks::VcValueType for #ty {
+                type Read = #read;
+                type CellMode = #cell_mode;
+
+                fn get_value_type_id() -> turbo_tasks::ValueTypeId {
+                    *#instance_id_ident
+                }
+            }
+        });
+        registrations.push(quote! {
+            #instance_init_ident.get_or_init(|| {
+                let mut value = #new_value_type;
+                f(&mut value);
+                value
+            }).register(&names[#index]);
+        });
+    }
+
+    quote! {
+        #(#definitions)*
+
+        #[doc(hidden)]
+        static #names_ident: turbo_tasks::macro_helpers::MonomorphizedNames =
+            turbo_tasks::macro_helpers::MonomorphizedNames::new(&[#(#type_names),*]);
+
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        pub(crate) fn #register_value_type_ident(
+            global_name: &'static str,
+            f: impl Fn(&mut turbo_tasks::ValueType),
+        ) {
+            let names = #names_ident.get(global_name);
+            #(#registrations)*
+        }
+    }
+}
@@ -1,16 +1,20 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, TokenStream as TokenStream2};
-use quote::{quote, quote_spanned};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
-    parse_macro_input, parse_quote, spanned::Spanned, ExprPath, ItemTrait, TraitItem,
-    TraitItemMethod,
+    parse_macro_input, parse_quote, punctuated::Punctuated, spanned::Spanned, ExprPath, ItemTrait,
+    Path, PathArguments, Token, TraitBound, TraitBoundModifier, TraitItem, TraitItemMethod,
+    TypeParamBound,
 };
 use turbo_tasks_macros_shared::{
+    get_function_definition_context_ident, get_trait_consts_ident,
     get_trait_default_impl_function_id_ident, get_trait_default_impl_function_ident,
-    get_trait_type_id_ident, get_trait_type_ident, ValueTraitArguments,
+    get_trait_type_id_ident, get_trait_type_ident, SpanDiagnosticExt, ValueTraitArguments,
 };
 
-use crate::func::{DefinitionContext, FunctionArguments, NativeFn, TurboFn};
+use crate::func::{
+    cfg_attributes, cfg_gate_items, DefinitionContext, FunctionArguments, NativeFn, TurboFn,
+};
 
 pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
     let ValueTraitArguments { debug, resolved } = parse_macro_input!(args as ValueTraitArguments);
@@ -33,32 +37,29 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
 
     if unsafety.is_some() {
         item.span()
-            .unwrap()
             .error("unsafe traits are not supported in #[turbo_tasks::value_trait]")
             .emit();
     }
 
     if auto_token.is_some() {
         item.span()
-            .unwrap()
             .error("auto traits are not supported in #[turbo_tasks::value_trait]")
             .emit();
     }
 
     if !generics.params.is_empty() {
         item.span()
-            .unwrap()
             .error("generic traits are not supported in #[turbo_tasks::value_trait]")
             .emit();
     }
 
     if generics.where_clause.is_some() {
         item.span()
-            .unwrap()
             .error("where clauses are not supported in #[turbo_tasks::value_trait]")
             .emit();
     }
 
+    let upcast_supertraits = upcast_supertraits(supertraits, debug);
     let supertraits = supertraits.iter().collect::<Vec<_>>();
 
     let trait_type_ident = get_trait_type_ident(trait_ident);
@@ -67,8 +68,13 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
     let mut trait_methods: Vec<TokenStream2> = Vec::new();
     let mut native_functions = Vec::new();
     let mut items = Vec::with_capacity(raw_items.len());
+    let mut const_items = Vec::new();
 
     for item in raw_items.iter() {
+        if let TraitItem::Const(const_item) = item {
+            const_items.push(const_item);
+            continue;
+        }
         let TraitItem::Method(TraitItemMethod {
             sig,
             default,
@@ -77,8 +83,10 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
         }) = item
         else {
             item.span()
-                .unwrap()
-                .error("only methods are allowed in a #[turbo_tasks::value_trait] trait")
+                .error(
+                    "only methods and associated consts are allowed in a \
+                     #[turbo_tasks::value_trait] trait",
+                )
                 .emit();
             continue;
         };
@@ -91,7 +99,7 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
         let Some(turbo_fn) = TurboFn::new(
             sig,
             DefinitionContext::ValueTrait,
-            FunctionArguments::default(),
+            FunctionArguments::value_trait_method(resolved),
         ) else {
             return quote! {
                 // An error occurred while parsing the function signature.
@@ -99,32 +107,56 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
             .into();
         };
 
-        let turbo_signature = turbo_fn.signature();
-        let arg_types = turbo_fn.input_types();
-        let dynamic_block = turbo_fn.dynamic_block(&trait_type_id_ident);
+        let value_type_bound = turbo_fn.value_type_bound(default.is_some());
+        let mut turbo_signature = turbo_fn.signature();
+        turbo_signature
+            .generics
+            .make_where_clause()
+            .predicates
+            .extend(value_type_bound.clone());
+        let arg_types = turbo_fn.exposed_input_types();
+        let default_function_id_ident = default
+            .as_ref()
+            .map(|_| get_trait_default_impl_function_id_ident(trait_ident, ident));
+        let dynamic_block =
+            turbo_fn.dynamic_block(&trait_type_id_ident, default_function_id_ident.as_ref());
+        let cfg_attrs = cfg_attributes(attrs);
         dynamic_trait_fns.push(quote! {
+            #(#cfg_attrs)*
             #turbo_signature #dynamic_block
         });
 
+        if let (Some(_), Some(span)) = (default, turbo_fn.self_in_return_type()) {
+            // The default implementation is registered once for `Box<dyn Trait>`, so `Self` would
+            // refer to the trait object rather than the type of the value it's called on.
+            span.error(
+                "default implementations of #[turbo_tasks::value_trait] methods cannot return \
+                 `Self`, because they're shared by all implementations",
+            )
+            .help(
+                "remove the default implementation and implement the method in each \
+                 #[turbo_tasks::value_impl], or return a `Vc<Box<dyn Trait>>`",
+            )
+            .emit();
+            continue;
+        }
+
         let default = if let Some(block) = default {
             // TODO(alexkirsz) These should go into their own utilities.
-            let inline_function_ident: Ident =
-                Ident::new(&format!("{}_inline", ident), ident.span());
-            let inline_extension_trait_ident =
-                Ident::new(&format!("{}_{}_inline", trait_ident, ident), ident.span());
+            let inline_function_ident: Ident = format_ident!("{}_inline", ident);
+            let inline_extension_trait_ident = format_ident!("{}_{}_inline", trait_ident, ident);
             let inline_function_path: ExprPath = parse_quote! { <Box<dyn #trait_ident> as #inline_extension_trait_ident>::#inline_function_ident };
-            let mut inline_signature = sig.clone();
-            inline_signature.ident = inline_function_ident;
+            let (inline_signature, inline_block) =
+                turbo_fn.inline_signature_and_block(&inline_function_ident, block);
 
+            // `local_cells` (and other function arguments) are currently unsupported here because:
+            // - The `#[turbo_tasks::function]` macro needs to be present for us to read this
+            //   argument.
+            // - This only makes sense when a default implementation is present.
             let native_function = NativeFn::new(
-                &format!("{trait_ident}::{ident}"),
+                &turbo_fn.native_function_name(format!("{trait_ident}::{ident}")),
                 &inline_function_path,
-                turbo_fn.is_method(),
-                // `inline_cells` is currently unsupported here because:
-                // - The `#[turbo_tasks::function]` macro needs to be present for us to read this
-                //   argument.
-                // - This only makes sense when a default implementation is present.
-                false,
+                &turbo_fn,
             );
 
             let native_function_ident = get_trait_default_impl_function_ident(trait_ident, ident);
@@ -136,12 +168,16 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
             let native_function_id_def = native_function.id_definition(&parse_quote! {
                 #native_function_ident
             });
+            let definition_context_ident =
+                get_function_definition_context_ident(&native_function_ident);
+            let definition_context = turbo_fn.definition_context().function_type();
 
             trait_methods.push(quote! {
+                #(#cfg_attrs)*
                 trait_type.register_default_trait_method::<(#(#arg_types,)*)>(stringify!(#ident).into(), *#native_function_id_ident);
             });
 
-            native_functions.push(quote! {
+            native_functions.push(cfg_gate_items(&cfg_attrs, quote! {
                 #[doc(hidden)]
                 #[allow(non_camel_case_types)]
                 trait #inline_extension_trait_ident: std::marker::Send {
@@ -164,25 +200,34 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
                     const #native_function_id_ident: #native_function_id_ty = #native_function_id_def;
 
                     #(#attrs)*
-                    #inline_signature #block
+                    #inline_signature #inline_block
                 }
 
                 #[doc(hidden)]
                 pub(crate) static #native_function_ident: #native_function_ty = <Box<dyn #trait_ident> as #inline_extension_trait_ident>::#native_function_ident;
                 #[doc(hidden)]
                 pub(crate) static #native_function_id_ident: #native_function_id_ty = <Box<dyn #trait_ident> as #inline_extension_trait_ident>::#native_function_id_ident;
-            });
+                #[doc(hidden)]
+                pub(crate) const #definition_context_ident: &str = #definition_context;
+            }));
 
             Some(turbo_fn.static_block(&native_function_id_ident))
         } else {
             trait_methods.push(quote! {
+                #(#cfg_attrs)*
                 trait_type.register_trait_method::<(#(#arg_types,)*)>(stringify!(#ident).into());
             });
             None
         };
 
+        let mut trait_signature = turbo_fn.trait_signature();
+        trait_signature
+            .generics
+            .make_where_clause()
+            .predicates
+            .extend(value_type_bound);
         items.push(TraitItem::Method(TraitItemMethod {
-            sig: turbo_fn.trait_signature(),
+            sig: trait_signature,
             default,
             attrs: attrs.clone(),
             semi_token: Default::default(),
@@ -198,6 +243,30 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! {}
     };
 
+    // Associated consts would make the trait unusable as `dyn Trait`, so they're declared in a
+    // separate trait, which `#[turbo_tasks::value_impl]` implements for the consts of an impl.
+    // `ValueTraitConsts` lets `#[turbo_tasks::value_impl]` check that every implementation of the
+    // trait implements it, even if the impl declares no consts.
+    let consts_trait = if const_items.is_empty() {
+        quote! {
+            impl<T: ?Sized> turbo_tasks::macro_helpers::ValueTraitConsts<T> for Box<dyn #trait_ident> {}
+        }
+    } else {
+        let consts_trait_ident = get_trait_consts_ident(trait_ident);
+        let doc = format!(" The associated consts of [`{trait_ident}`].");
+        quote! {
+            #[doc = #doc]
+            #vis trait #consts_trait_ident {
+                #(#const_items)*
+            }
+
+            impl<T: #consts_trait_ident + ?Sized> turbo_tasks::macro_helpers::ValueTraitConsts<T>
+                for Box<dyn #trait_ident>
+            {
+            }
+        }
+    };
+
     let mut extended_supertraits = Vec::new();
     if let Some(span) = resolved {
         extended_supertraits.push(quote_spanned! {
@@ -218,6 +287,8 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
             #(#items)*
         }
 
+        #consts_trait
+
         #(#native_functions)*
 
         #[doc(hidden)]
@@ -252,11 +323,68 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
         }
 
         #(
-            unsafe impl turbo_tasks::Dynamic<Box<dyn #supertraits>> for Box<dyn #trait_ident> {}
-            unsafe impl turbo_tasks::Upcast<Box<dyn #supertraits>> for Box<dyn #trait_ident> {}
+            unsafe impl turbo_tasks::Dynamic<Box<dyn #upcast_supertraits>> for Box<dyn #trait_ident> {}
+            unsafe impl turbo_tasks::Upcast<Box<dyn #upcast_supertraits>> for Box<dyn #trait_ident> {}
         )*
 
         #value_debug_impl
     };
     expanded.into()
 }
+
+/// Traits that can't be value traits, but are commonly used as supertraits of one.
+const NON_VALUE_TRAITS: &[&str] = &[
+    "Send",
+    "Sync",
+    "Unpin",
+    "Sized",
+    "Any",
+    "Debug",
+    "Display",
+    "ResolvedValue",
+    "NonLocalValue",
+    "OperationValue",
+    "TraceRawVcs",
+];
+
+/// The supertraits that a `Vc<Box<dyn Trait>>` can be upcast to.
+///
+/// Lifetimes, `?Sized`, generic traits (value traits can't be generic) and well-known traits that
+/// aren't value traits are skipped, as are duplicates and `ValueDebug` when `debug` already adds
+/// the upcast. Supertraits of the supertraits aren't visible to the macro, so they need to be
+/// listed as well to upcast to them.
+fn upcast_supertraits(
+    supertraits: &Punctuated<TypeParamBound, Token![+]>,
+    debug: bool,
+) -> Vec<&Path> {
+    let mut seen = Vec::new();
+    supertraits
+        .iter()
+        .filter_map(|bound| match bound {
+            TypeParamBound::Trait(TraitBound {
+                modifier: TraitBoundModifier::None,
+                lifetimes: None,
+                path,
+                ..
+            }) => Some(path),
+            _ => None,
+        })
+        .filter(|path| {
+            let Some(last) = path.segments.last() else {
+                return false;
+            };
+            if !matches!(last.arguments, PathArguments::None)
+                || NON_VALUE_TRAITS.iter().any(|name| last.ident == name)
+                || (debug && last.ident == "ValueDebug")
+            {
+                return false;
+            }
+            let key = path.to_token_stream().to_string();
+            if seen.contains(&key) {
+                return false;
+            }
+            seen.push(key);
+            true
+        })
+        .collect()
+}
@@ -337,11 +337,13 @@ impl MemoryBackend {
                 fn_type: function_id,
                 this: _,
                 arg: _,
+                arg_hasher: _,
             }
             | CachedTaskType::Native {
                 fn_type: function_id,
                 this: _,
                 arg: _,
+                arg_hasher: _,
             } => {
                 stats.increment_cache_hit(*function_id);
             }
@@ -384,6 +386,7 @@ impl MemoryBackend {
                 fn_type: function_id,
                 this: _,
                 arg: _,
+                arg_hasher: _,
             } => {
                 stats.increment_cache_miss(*function_id);
             }
@@ -751,6 +751,7 @@ impl Task {
                     fn_type: native_fn_id,
                     this,
                     arg,
+                    arg_hasher: _,
                 } => {
                     let func = registry::get_function(*native_fn_id);
                     let span = func.span();
@@ -763,6 +764,7 @@ impl Task {
                     fn_type: native_fn_id,
                     this,
                     arg,
+                    arg_hasher: _,
                 } => {
                     let func = registry::get_function(*native_fn_id);
                     let span = func.resolve_span();
@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/cfg_attributes.rs
\ No newline at end of file
@@ -0,0 +1,64 @@
+#![feature(arbitrary_self_types)]
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, TurboTasks, Vc};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::{register, Registration};
+
+static REGISTRATION: Registration = register!();
+
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+static MAX_RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrency_group_limit() {
+    REGISTRATION.ensure_registered();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.set_concurrency_group_limit("limited", 2);
+    tt.run_once(async {
+        let values = (0..8).map(limited).try_join().await?;
+        assert_eq!(values.iter().map(|v| **v).sum::<u32>(), 28);
+        Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(MAX_RUNNING.load(Ordering::SeqCst), 2);
+}
+
+#[turbo_tasks::function(concurrency_group = "limited")]
+fn limited(value: u32) -> Vc<u32> {
+    let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+    MAX_RUNNING.fetch_max(running, Ordering::SeqCst);
+    // the permit is only held while the task is polled, so this blocks the worker on purpose
+    std::thread::sleep(Duration::from_millis(20));
+    RUNNING.fetch_sub(1, Ordering::SeqCst);
+    Vc::cell(value)
+}
+
+#[tokio::test]
+async fn test_concurrency_group_recursion() {
+    REGISTRATION.ensure_registered();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.set_concurrency_group_limit("recursive", 1);
+    // every task waits for a task of the same group, deeper than the limit
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        tt.run_once(async { Ok(*recursive(4).await?) }),
+    )
+    .await
+    .expect("tasks waiting for tasks of their group shouldn't deadlock");
+    assert_eq!(result.unwrap(), 4);
+}
+
+#[turbo_tasks::function(concurrency_group = "recursive")]
+async fn recursive(depth: u32) -> Result<Vc<u32>> {
+    if depth == 0 {
+        return Ok(Vc::cell(0));
+    }
+    Ok(Vc::cell(*recursive(depth - 1).await? + 1))
+}
@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/eager.rs
\ No newline at end of file
@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/function_arguments.rs
\ No newline at end of file
@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/io_enforcement.rs
\ No newline at end of file
@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/mixed_value_impl.rs
\ No newline at end of file
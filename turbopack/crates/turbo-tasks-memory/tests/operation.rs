@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/operation.rs
\ No newline at end of file
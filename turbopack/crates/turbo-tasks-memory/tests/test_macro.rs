@@ -0,0 +1,27 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_testing::{register, Registration};
+
+static REGISTRATION: Registration = register!();
+
+fn register() {
+    REGISTRATION.ensure_registered();
+}
+
+#[turbo_tasks::function]
+fn double(value: u32) -> Vc<u32> {
+    Vc::cell(value * 2)
+}
+
+#[turbo_tasks::test(register = register)]
+async fn runs_task_functions() {
+    assert_eq!(*double(21).await?, 42);
+}
+
+#[turbo_tasks::test(register = [register])]
+async fn returns_result() -> Result<()> {
+    assert_eq!(*double(2).await?, 4);
+    Ok(())
+}
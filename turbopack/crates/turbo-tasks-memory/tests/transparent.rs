@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/transparent.rs
\ No newline at end of file
@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/value_serde_attributes.rs
\ No newline at end of file
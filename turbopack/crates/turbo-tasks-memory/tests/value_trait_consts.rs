@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/value_trait_consts.rs
\ No newline at end of file
@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/value_trait_supertraits.rs
\ No newline at end of file
@@ -49,6 +49,8 @@ async fn all_in_one() {
                 .await?,
             42
         );
+        assert_eq!(*Number::try_downcast(c_erased).await?.unwrap().await?, 42);
+        assert!(NumberB::try_downcast(c_erased).await?.is_none());
 
         let b_erased_other: Vc<Box<dyn Add>> = Vc::upcast(Vc::<NumberB>::cell(10));
         let c_erased_invalid: Vc<Box<dyn Add>> = a_erased.add(b_erased_other);
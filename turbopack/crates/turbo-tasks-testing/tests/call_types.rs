@@ -1,7 +1,7 @@
 #![feature(arbitrary_self_types)]
 
 use anyhow::Result;
-use turbo_tasks::Vc;
+use turbo_tasks::{downcast_task_error, RcStr, Vc, VcValueType};
 use turbo_tasks_testing::{register, run, Registration};
 
 static REGISTRATION: Registration = register!();
@@ -109,6 +109,20 @@ async fn trait_methods() {
     run(&REGISTRATION, || async {
         assert_eq!(*Value::static_trait_method().await?, 42);
         assert_eq!(*Value::async_static_trait_method().await?, 42);
+        assert_eq!(*Value::default_static_trait_method().await?, 42);
+        assert_eq!(*Value::default_async_static_trait_method().await?, 42);
+        assert_eq!(*generic_static_trait_method::<Value>().await?, 42);
+        assert_eq!(Value::new_trait_value(44).await?.0, 44);
+
+        // without a value to dispatch on, trait objects use the default implementation
+        assert_eq!(
+            *<Box<dyn ValueTrait>>::default_static_trait_method().await?,
+            42
+        );
+        assert_eq!(
+            *<Box<dyn ValueTrait>>::default_async_static_trait_method().await?,
+            42
+        );
 
         let value = Value(43).cell();
         assert_eq!(*value.trait_method().await?, 43);
@@ -133,12 +147,21 @@ async fn trait_methods() {
         assert_eq!(*trait_value.async_trait_method().await?, 43);
         assert_eq!(*trait_value.default_trait_method().await?, 42);
         assert_eq!(*trait_value.default_async_trait_method().await?, 42);
+
+        // default implementations dispatch calls on `self` to the implementation of the value
+        assert_eq!(*trait_value.default_sibling_trait_method().await?, 85);
+        let other_value: Vc<Box<dyn ValueTrait>> = Vc::upcast(OtherValue(1).cell());
+        assert_eq!(*other_value.default_sibling_trait_method().await?, 8);
         anyhow::Ok(())
     })
     .await
     .unwrap()
 }
 
+fn generic_static_trait_method<T: ValueTrait + VcValueType>() -> Vc<u32> {
+    T::static_trait_method()
+}
+
 #[turbo_tasks::function]
 fn wrap_value(v: Vc<Value>) -> Vc<Value> {
     v
@@ -159,6 +182,7 @@ trait ValueTrait {
     async fn default_async_static_trait_method() -> Result<Vc<u32>> {
         Ok(Vc::cell(42))
     }
+    fn new_trait_value(value: u32) -> Vc<Self>;
     fn trait_method(&self) -> Vc<u32>;
     fn async_trait_method(&self) -> Result<Vc<u32>>;
     fn default_trait_method(self: Vc<Self>) -> Vc<u32> {
@@ -167,6 +191,11 @@ trait ValueTrait {
     async fn default_async_trait_method(self: Vc<Self>) -> Result<Vc<u32>> {
         Ok(Vc::cell(42))
     }
+    async fn default_sibling_trait_method(self: Vc<Self>) -> Result<Vc<u32>> {
+        Ok(Vc::cell(
+            *self.trait_method().await? + *self.default_trait_method().await?,
+        ))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -181,6 +210,11 @@ impl ValueTrait for Value {
         Ok(Vc::cell(42))
     }
 
+    #[turbo_tasks::function]
+    fn new_trait_value(value: u32) -> Vc<Self> {
+        Value(value).cell()
+    }
+
     #[turbo_tasks::function]
     fn trait_method(&self) -> Vc<u32> {
         Vc::cell(self.0)
@@ -191,3 +225,88 @@ impl ValueTrait for Value {
         Ok(Vc::cell(self.0))
     }
 }
+
+#[turbo_tasks::value]
+struct OtherValue(u32);
+
+#[turbo_tasks::value_impl]
+impl ValueTrait for OtherValue {
+    #[turbo_tasks::function]
+    fn static_trait_method() -> Vc<u32> {
+        Vc::cell(7)
+    }
+
+    #[turbo_tasks::function]
+    async fn async_static_trait_method() -> Result<Vc<u32>> {
+        Ok(Vc::cell(7))
+    }
+
+    #[turbo_tasks::function]
+    fn new_trait_value(value: u32) -> Vc<Self> {
+        OtherValue(value).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn trait_method(&self) -> Vc<u32> {
+        Vc::cell(self.0)
+    }
+
+    #[turbo_tasks::function]
+    async fn async_trait_method(&self) -> Result<Vc<u32>> {
+        Ok(Vc::cell(self.0))
+    }
+
+    #[turbo_tasks::function]
+    fn default_trait_method(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(7)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    Empty,
+    Invalid(char),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty input"),
+            ParseError::Invalid(c) => write!(f, "invalid character {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[turbo_tasks::function]
+fn parse_digit(input: RcStr) -> Result<Vc<u32>, ParseError> {
+    let c = input.chars().next().ok_or(ParseError::Empty)?;
+    Ok(Vc::cell(c.to_digit(10).ok_or(ParseError::Invalid(c))?))
+}
+
+#[turbo_tasks::function]
+async fn double_digit(input: RcStr) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*parse_digit(input).await? * 2))
+}
+
+#[tokio::test]
+async fn typed_errors() {
+    run(&REGISTRATION, || async {
+        assert_eq!(*parse_digit("7".into()).await?, 7);
+        let err = parse_digit("".into()).await.unwrap_err();
+        assert_eq!(
+            downcast_task_error::<ParseError>(&err),
+            Some(&ParseError::Empty)
+        );
+        // the typed error is kept when it's passed on by another task
+        let err = double_digit("x".into()).await.unwrap_err();
+        assert_eq!(
+            downcast_task_error::<ParseError>(&err),
+            Some(&ParseError::Invalid('x'))
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
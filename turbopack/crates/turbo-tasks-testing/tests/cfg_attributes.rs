@@ -0,0 +1,82 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[tokio::test]
+async fn cfg_gated_functions() {
+    run(&REGISTRATION, || async {
+        assert_eq!(*configured_value().await?, 1);
+
+        let value = Value::new(2);
+        assert_eq!(*value.configured_value().await?, 3);
+        assert_eq!(*value.trait_value().await?, 4);
+        assert_eq!(*value.default_trait_value().await?, 5);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+/// Only one of the two definitions is compiled, so their generated items must not collide.
+#[turbo_tasks::function]
+#[cfg(not(any()))]
+fn configured_value() -> Vc<u32> {
+    Vc::cell(1)
+}
+
+#[turbo_tasks::function]
+#[cfg(any())]
+fn configured_value() -> Vc<u32> {
+    Vc::cell(0)
+}
+
+#[turbo_tasks::value]
+struct Value(u32);
+
+#[turbo_tasks::value_impl]
+impl Value {
+    /// Creates a new value.
+    #[turbo_tasks::function]
+    fn new(value: u32) -> Vc<Self> {
+        Value(value).cell()
+    }
+
+    #[turbo_tasks::function]
+    #[cfg(not(any()))]
+    async fn configured_value(self: Vc<Self>) -> Result<Vc<u32>> {
+        Ok(Vc::cell(self.await?.0 + 1))
+    }
+
+    #[turbo_tasks::function]
+    #[cfg(any())]
+    fn configured_value(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(0)
+    }
+}
+
+#[turbo_tasks::value_trait]
+trait ValueTrait {
+    /// Returns the value, offset by a per-configuration amount.
+    fn trait_value(self: Vc<Self>) -> Vc<u32>;
+
+    fn default_trait_value(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(5)
+    }
+
+    #[cfg(any())]
+    fn disabled_trait_value(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(0)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueTrait for Value {
+    #[turbo_tasks::function]
+    async fn trait_value(self: Vc<Self>) -> Result<Vc<u32>> {
+        Ok(Vc::cell(self.await?.0 + 2))
+    }
+}
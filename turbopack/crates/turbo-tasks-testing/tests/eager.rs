@@ -0,0 +1,77 @@
+#![feature(arbitrary_self_types)]
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use anyhow::Result;
+use tokio::time::{sleep, timeout, Duration};
+use turbo_tasks::{run_once, ReadConsistency, Vc};
+use turbo_tasks_testing::{register, Registration};
+
+static REGISTRATION: Registration = register!();
+
+static CREATE_CHILDREN: AtomicBool = AtomicBool::new(false);
+static EAGER_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+static LAZY_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[turbo_tasks::function(eager)]
+fn eager_child() -> Vc<u32> {
+    EAGER_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    Vc::cell(1)
+}
+
+#[turbo_tasks::function]
+fn lazy_child() -> Vc<u32> {
+    LAZY_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    Vc::cell(2)
+}
+
+/// Creates both children without reading them, once `CREATE_CHILDREN` is set, and waits for the
+/// eager one to be executed.
+#[turbo_tasks::function]
+async fn create_children() -> Result<Vc<u32>> {
+    if CREATE_CHILDREN.load(Ordering::SeqCst) {
+        // the lazy child is created first, so it would be executed first if it was scheduled
+        let _ = lazy_child();
+        let _ = eager_child();
+        timeout(Duration::from_secs(10), async {
+            while EAGER_EXECUTIONS.load(Ordering::SeqCst) == 0 {
+                sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await?;
+    }
+    Ok(Vc::cell(0))
+}
+
+#[tokio::test]
+async fn test_eager() -> Result<()> {
+    assert!(EAGER_CHILD_FUNCTION.function_meta.eager);
+    assert!(!LAZY_CHILD_FUNCTION.function_meta.eager);
+
+    REGISTRATION.ensure_registered();
+    let tt = REGISTRATION.create_turbo_tasks("eager", true);
+
+    // Tasks connected to an active parent (e.g. the task of `run_once` while it's running) are
+    // executed anyway, so the children are created by a task that isn't active anymore.
+    let task = run_once(tt.clone(), async {
+        let output = create_children();
+        output.await?;
+        Ok(Vc::into_raw(output).get_task_id())
+    })
+    .await?;
+
+    // The `run_once` task is done, so invalidating the task doesn't schedule it again, and an
+    // untracked read executes it without making it active.
+    CREATE_CHILDREN.store(true, Ordering::SeqCst);
+    tt.invalidate(task);
+    if let Err(listener) = tt.try_read_task_output_untracked(task, ReadConsistency::Eventual)? {
+        timeout(Duration::from_secs(10), listener).await?;
+    }
+
+    // the eager child was executed although its output was never read, the lazy child wasn't
+    assert_eq!(EAGER_EXECUTIONS.load(Ordering::SeqCst), 1);
+    assert_eq!(LAZY_EXECUTIONS.load(Ordering::SeqCst), 0);
+
+    tt.stop_and_wait().await;
+    Ok(())
+}
@@ -0,0 +1,1489 @@
+#![feature(arbitrary_self_types)]
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Debug,
+    hash::Hasher,
+    num::{NonZeroU32, NonZeroU64, NonZeroUsize},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    macro_helpers::tracing,
+    profile::{set_profile_hook, ProfilePhase},
+    registry,
+    task::{CowStrTaskInput, FlattenTaskInput, FromTaskInput},
+    test_helpers, IoMarkers, RcStr, ResolvedVc, TaskInput, TaskPriority, Vc, VcValueType,
+};
+use turbo_tasks_testing::{register, run, run_without_cache_check, Registration};
+
+static REGISTRATION: Registration = register!();
+
+static DERIVE_NAME_CALLED: AtomicBool = AtomicBool::new(false);
+
+fn derive_name(inputs: &(u32,)) -> String {
+    DERIVE_NAME_CALLED.store(true, Ordering::SeqCst);
+    format!("named_by_input({})", inputs.0)
+}
+
+#[turbo_tasks::function(name_fn = derive_name)]
+fn named_by_input(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[tokio::test]
+async fn test_name_fn() -> Result<()> {
+    run(&REGISTRATION, || async {
+        assert_eq!(*named_by_input(42).await?, 42);
+        assert!(DERIVE_NAME_CALLED.load(Ordering::SeqCst));
+        Ok(())
+    })
+    .await
+}
+
+static COUNTING_HASHER_FINISHES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default)]
+struct CountingHasher(u64);
+
+impl Hasher for CountingHasher {
+    fn finish(&self) -> u64 {
+        COUNTING_HASHER_FINISHES.fetch_add(1, Ordering::SeqCst);
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(*byte);
+        }
+    }
+}
+
+#[turbo_tasks::function(hasher = CountingHasher)]
+fn custom_hashed(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[tokio::test]
+async fn test_hasher() -> Result<()> {
+    run(&REGISTRATION, || async {
+        assert_eq!(*custom_hashed(42).await?, 42);
+        assert!(COUNTING_HASHER_FINISHES.load(Ordering::SeqCst) > 0);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn replacement_value(value: u32) -> Vc<u32> {
+    Vc::cell(value + 1)
+}
+
+#[turbo_tasks::function(deprecated, replacement = replacement_value)]
+fn deprecated_value(value: u32) -> Vc<u32> {}
+
+#[turbo_tasks::function]
+async fn replacement_sum(values: &[ResolvedVc<u32>], offset: ResolvedVc<u32>) -> Result<Vc<u32>> {
+    let mut sum = *offset.await?;
+    for value in values {
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[turbo_tasks::function(deprecated, replacement = replacement_sum)]
+fn deprecated_sum(values: &[ResolvedVc<u32>], offset: ResolvedVc<u32>) -> Vc<u32> {}
+
+#[tokio::test]
+async fn test_deprecated_replacement() -> Result<()> {
+    run(&REGISTRATION, || async {
+        #[allow(deprecated)]
+        let forwarded = deprecated_value(41);
+        assert_eq!(*forwarded.await?, 42);
+
+        // the resolved arguments are forwarded as the `Vc`s the replacement accepts
+        #[allow(deprecated)]
+        let forwarded = deprecated_sum(&[Vc::cell(1), Vc::cell(2)], Vc::cell(3));
+        assert_eq!(*forwarded.await?, 6);
+        Ok(())
+    })
+    .await
+}
+
+/// A type that's stored as a `u32` task input and doubled when converted back.
+struct Doubled(u32);
+
+impl FromTaskInput for Doubled {
+    type TaskInput = u32;
+
+    fn from_task_input(from: u32) -> Self {
+        Doubled(from * 2)
+    }
+}
+
+#[turbo_tasks::function(direct_from_task_input)]
+fn directly_converted(value: Doubled) -> Vc<u32> {
+    Vc::cell(value.0)
+}
+
+#[tokio::test]
+async fn test_direct_from_task_input() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // the exposed function accepts `<Doubled as FromTaskInput>::TaskInput`
+        assert_eq!(*directly_converted(21).await?, 42);
+        Ok(())
+    })
+    .await
+}
+
+static RESOLVE_COUNTED_RESOLVES: AtomicUsize = AtomicUsize::new(0);
+
+/// A plain (already resolved) input that counts how often it's resolved.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct ResolveCounted(u32);
+
+impl TaskInput for ResolveCounted {
+    async fn resolve(&self) -> Result<Self> {
+        RESOLVE_COUNTED_RESOLVES.fetch_add(1, Ordering::SeqCst);
+        Ok(self.clone())
+    }
+}
+
+#[turbo_tasks::function]
+fn unresolved_value(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[turbo_tasks::function(parallel_inputs)]
+async fn parallel_sum(a: Vc<u32>, plain: ResolveCounted, b: Vc<u32>) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*a.await? + plain.0 + *b.await?))
+}
+
+#[tokio::test]
+async fn test_parallel_inputs() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // the outputs of other tasks are unresolved, so the inputs have to be resolved first
+        let sum = parallel_sum(unresolved_value(1), ResolveCounted(2), unresolved_value(3));
+        assert_eq!(*sum.await?, 6);
+        // only the `Vc` inputs are joined, the plain input is passed through as-is
+        assert_eq!(RESOLVE_COUNTED_RESOLVES.load(Ordering::SeqCst), 0);
+        Ok(())
+    })
+    .await
+}
+
+static PROFILED_PHASES: Mutex<Vec<(&str, ProfilePhase)>> = Mutex::new(Vec::new());
+
+fn record_profiled_phase(metric: &'static str, phase: ProfilePhase, _duration: u64) {
+    PROFILED_PHASES.lock().unwrap().push((metric, phase));
+}
+
+#[turbo_tasks::function(profile)]
+async fn profiled(value: Vc<u32>) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*value.await? * 2))
+}
+
+#[tokio::test]
+async fn test_profile() -> Result<()> {
+    set_profile_hook(record_profiled_phase).unwrap();
+    run(&REGISTRATION, || async {
+        assert_eq!(*profiled(unresolved_value(21)).await?, 42);
+        let phases = PROFILED_PHASES.lock().unwrap().clone();
+        assert!(phases.contains(&(
+            "function_arguments::profiled::resolve_inputs",
+            ProfilePhase::ResolveInputs
+        )));
+        assert!(phases.contains(&("function_arguments::profiled::body", ProfilePhase::Body)));
+        let metrics: HashSet<_> = phases.iter().map(|(metric, _)| *metric).collect();
+        assert_eq!(metrics.len(), 2);
+        Ok(())
+    })
+    .await
+}
+
+static TRANSIENT_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+static PERMANENT_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.to_string() == "transient"
+}
+
+#[turbo_tasks::function(retry(count = 3, on = is_transient))]
+async fn fails_transiently(value: u32) -> Result<Vc<u32>> {
+    if TRANSIENT_ATTEMPTS.fetch_add(1, Ordering::SeqCst) < 2 {
+        anyhow::bail!("transient");
+    }
+    Ok(Vc::cell(value))
+}
+
+#[turbo_tasks::function(retry(count = 3, on = is_transient))]
+async fn fails_permanently(value: u32) -> Result<Vc<u32>> {
+    PERMANENT_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+    anyhow::bail!("permanent: {value}");
+}
+
+#[tokio::test]
+async fn test_retry_on() -> Result<()> {
+    // the attempts are counted across executions, so this only runs once
+    run_without_cache_check(&REGISTRATION, async {
+        assert_eq!(*fails_transiently(42).await?, 42);
+        assert_eq!(TRANSIENT_ATTEMPTS.load(Ordering::SeqCst), 3);
+
+        // the predicate rejects the error, so it's returned without retrying
+        assert!(fails_permanently(42).await.is_err());
+        assert_eq!(PERMANENT_ATTEMPTS.load(Ordering::SeqCst), 1);
+        anyhow::Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn boxed_len(text: Box<str>, bytes: Box<[u8]>, boxed: Box<Vc<u32>>) -> Vc<u32> {
+    let _: Box<Vc<u32>> = boxed;
+    Vc::cell((text.len() + bytes.len()) as u32)
+}
+
+#[tokio::test]
+async fn test_boxed_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // `Box<str>` and `Box<[u8]>` are passed through as-is
+        let f: fn(Box<str>, Box<[u8]>, Box<Vc<u32>>) -> Vc<u32> = boxed_len;
+        let len = f("abc".into(), vec![1, 2].into(), Box::new(Vc::cell(0)));
+        assert_eq!(*len.await?, 5);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn repeat(text: RcStr, times: u32) -> Vc<RcStr> {
+    Vc::cell(text.repeat(times as usize).into())
+}
+
+#[tokio::test]
+async fn test_invalid_argument_names_argument() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let repeat_function: &'static turbo_tasks::NativeFunction = &REPEAT_FUNCTION;
+        let invalid_arguments = (RcStr::from("a"), 2u64);
+        let Err(_err) = repeat_function.execute(None, &invalid_arguments).await else {
+            panic!("expected the invalid argument to be rejected");
+        };
+        // the argument is only named in debug builds
+        #[cfg(debug_assertions)]
+        assert_eq!(
+            _err.to_string(),
+            "Invalid type for argument `times`, expected u32 got u64"
+        );
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn prerequisite() -> Vc<u32> {
+    Vc::cell(1)
+}
+
+#[turbo_tasks::function(schedule_after = prerequisite)]
+fn scheduled_after_prerequisite() -> Vc<u32> {
+    Vc::cell(2)
+}
+
+#[test]
+fn test_schedule_after() {
+    let schedule_after = SCHEDULED_AFTER_PREREQUISITE_FUNCTION
+        .function_meta
+        .schedule_after
+        .expect("schedule_after should be recorded");
+    assert!(std::ptr::eq(schedule_after(), &*PREREQUISITE_FUNCTION));
+    assert!(PREREQUISITE_FUNCTION.function_meta.schedule_after.is_none());
+}
+
+#[turbo_tasks::function(concurrency_group = "io")]
+fn read_in_group() -> Vc<u32> {
+    Vc::cell(1)
+}
+
+#[turbo_tasks::function(concurrency_group = "io")]
+fn write_in_group() -> Vc<u32> {
+    Vc::cell(2)
+}
+
+#[test]
+fn test_concurrency_group() {
+    assert_eq!(
+        READ_IN_GROUP_FUNCTION.function_meta.concurrency_group,
+        Some("io")
+    );
+    assert_eq!(
+        READ_IN_GROUP_FUNCTION.function_meta.concurrency_group,
+        WRITE_IN_GROUP_FUNCTION.function_meta.concurrency_group
+    );
+    assert!(PREREQUISITE_FUNCTION
+        .function_meta
+        .concurrency_group
+        .is_none());
+}
+
+#[turbo_tasks::function(fs)]
+fn reads_file() -> Vc<u32> {
+    Vc::cell(1)
+}
+
+#[turbo_tasks::function(fs, network)]
+fn downloads_file() -> Vc<u32> {
+    Vc::cell(2)
+}
+
+#[test]
+fn test_io_markers() {
+    let reads_file = READS_FILE_FUNCTION.function_meta.io_markers;
+    assert_eq!(
+        reads_file,
+        IoMarkers {
+            fs: true,
+            network: false
+        }
+    );
+    assert_eq!(reads_file.as_str(), Some("fs"));
+    let downloads_file = DOWNLOADS_FILE_FUNCTION.function_meta.io_markers;
+    assert_eq!(downloads_file.as_str(), Some("fs,network"));
+    assert!(PREREQUISITE_FUNCTION.function_meta.io_markers.is_empty());
+}
+
+#[turbo_tasks::function(monomorphize = [u32, RcStr])]
+fn monomorphized_identity<T: VcValueType>(value: Vc<T>) -> Vc<T> {
+    value
+}
+
+#[tokio::test]
+async fn test_monomorphize() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let number = monomorphized_identity(Vc::<u32>::cell(42));
+        assert_eq!(*number.await?, 42);
+        let text = monomorphized_identity(Vc::<RcStr>::cell("text".into()));
+        assert_eq!(&*text.await?, "text");
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(monomorphize = [u32, RcStr])]
+fn debug_string<T: TaskInput>(value: T) -> Vc<RcStr> {
+    Vc::cell(format!("{value:?}").into())
+}
+
+#[tokio::test]
+async fn test_monomorphize_task_input() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // a generic argument is passed as-is to the instantiation for its type
+        assert_eq!(&*debug_string(42u32).await?, "42");
+        assert_eq!(&*debug_string(RcStr::from("text")).await?, "\"text\"");
+        Ok(())
+    })
+    .await
+}
+
+#[derive(FlattenTaskInput)]
+struct Dimensions {
+    width: u32,
+    height: u32,
+}
+
+#[turbo_tasks::function]
+fn area(#[turbo_tasks(flatten)] dimensions: Dimensions, scale: u32) -> Vc<u32> {
+    Vc::cell(dimensions.width * dimensions.height * scale)
+}
+
+#[tokio::test]
+async fn test_flatten() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let dimensions = Dimensions {
+            width: 2,
+            height: 3,
+        };
+        assert_eq!(*area(dimensions, 2).await?, 12);
+        // the fields are passed as separate task inputs
+        let fields: <Dimensions as FlattenTaskInput>::Fields = (2, 3);
+        assert_eq!(Dimensions::from_fields(fields).into_fields(), (2, 3));
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+async fn destructured_sum(
+    (value, offset): (ResolvedVc<u32>, u32),
+    #[turbo_tasks(flatten)] Dimensions { width, height }: Dimensions,
+) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*value.await? + offset + width * height))
+}
+
+#[tokio::test]
+async fn test_destructuring_patterns() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let dimensions = Dimensions {
+            width: 2,
+            height: 3,
+        };
+        assert_eq!(*destructured_sum((Vc::cell(1), 2), dimensions).await?, 9);
+        Ok(())
+    })
+    .await
+}
+
+/// Records the `function` and `threshold` fields of warnings.
+struct SlowExecutionWarnings {
+    warnings: Arc<Mutex<Vec<(String, u64)>>>,
+}
+
+impl tracing::Subscriber for SlowExecutionWarnings {
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        *metadata.level() == tracing::Level::WARN
+    }
+
+    fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        #[derive(Default)]
+        struct Visitor {
+            function: Option<String>,
+            threshold: Option<u64>,
+        }
+
+        impl tracing::field::Visit for Visitor {
+            fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                if field.name() == "function" {
+                    self.function = Some(value.to_owned());
+                }
+            }
+
+            fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+                if field.name() == "threshold" {
+                    self.threshold = Some(value);
+                }
+            }
+
+            fn record_debug(&mut self, _field: &tracing::field::Field, _value: &dyn Debug) {}
+        }
+
+        let mut visitor = Visitor::default();
+        event.record(&mut visitor);
+        if let (Some(function), Some(threshold)) = (visitor.function, visitor.threshold) {
+            self.warnings.lock().unwrap().push((function, threshold));
+        }
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+#[turbo_tasks::function(warn_after = "1ms")]
+fn slow_execution(value: u32) -> Vc<u32> {
+    std::thread::sleep(Duration::from_millis(5));
+    Vc::cell(value)
+}
+
+#[turbo_tasks::function(warn_after = "10s")]
+fn fast_execution(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[tokio::test]
+async fn test_warn_after() -> Result<()> {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    // `#[tokio::test]` runs the tasks on this thread, so its default subscriber sees the warnings
+    // of their bodies
+    let _subscriber_guard = tracing::subscriber::set_default(SlowExecutionWarnings {
+        warnings: warnings.clone(),
+    });
+    let passes = Arc::new(AtomicUsize::new(0));
+    run(&REGISTRATION, move || {
+        let warnings = warnings.clone();
+        let pass = passes.fetch_add(1, Ordering::SeqCst);
+        async move {
+            let before = warnings.lock().unwrap().len();
+            assert_eq!(*slow_execution(1).await?, 1);
+            assert_eq!(*fast_execution(2).await?, 2);
+            let new_warnings = warnings.lock().unwrap()[before..].to_vec();
+            // the second pass reuses the cached tasks of the first, so the bodies don't run
+            let expected = if pass == 1 {
+                vec![]
+            } else {
+                vec![("function_arguments::slow_execution".to_owned(), 1_000)]
+            };
+            assert_eq!(new_warnings, expected);
+            Ok(())
+        }
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn sum_non_zero(a: NonZeroU32, b: NonZeroU64, c: NonZeroUsize, d: Option<NonZeroU32>) -> Vc<u64> {
+    Vc::cell(a.get() as u64 + b.get() + c.get() as u64 + d.map_or(0, |d| d.get() as u64))
+}
+
+#[tokio::test]
+async fn test_non_zero_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // niche-optimized scalars are passed through unchanged, including inside of an `Option`
+        let f: fn(NonZeroU32, NonZeroU64, NonZeroUsize, Option<NonZeroU32>) -> Vc<u64> =
+            sum_non_zero;
+        let a = NonZeroU32::new(1).unwrap();
+        let b = NonZeroU64::new(2).unwrap();
+        let c = NonZeroUsize::new(3).unwrap();
+        assert_eq!(*f(a, b, c, NonZeroU32::new(4)).await?, 10);
+        assert_eq!(*f(a, b, c, None).await?, 6);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn resolved_identity(value: ResolvedVc<u32>) -> Vc<u32> {
+    // the input is bound as-is, so the returned `Vc` is the resolved one we were called with
+    assert!(value.is_resolved());
+    *value
+}
+
+#[tokio::test]
+async fn test_resolved_vc_argument() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let input = Vc::<u32>::cell(42).to_resolved().await?;
+        let output = resolved_identity(input).resolve().await?;
+        assert_eq!(output, *input);
+        assert_eq!(*output.await?, 42);
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_unresolved_vc_argument() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // `ResolvedVc` arguments accept a plain `Vc`, which is resolved before the body runs
+        let input = cached_double(21);
+        assert!(!input.is_resolved());
+        let output = resolved_identity(input).resolve().await?;
+        assert_eq!(output, input.resolve().await?);
+        assert_eq!(*output.await?, 42);
+        Ok(())
+    })
+    .await
+}
+
+static VC_RESOLVES: AtomicUsize = AtomicUsize::new(0);
+
+/// A `Vc` input that counts how often it's resolved.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct ResolveCountedVc(Vc<u32>);
+
+impl TaskInput for ResolveCountedVc {
+    fn is_resolved(&self) -> bool {
+        self.0.is_resolved()
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        VC_RESOLVES.fetch_add(1, Ordering::SeqCst);
+        Ok(ResolveCountedVc(self.0.resolve().await?))
+    }
+}
+
+#[turbo_tasks::function]
+async fn resolved_sum(value: ResolvedVc<u32>, counted: ResolveCountedVc) -> Result<Vc<u32>> {
+    assert!(value.is_resolved());
+    assert!(counted.0.is_resolved());
+    // the arguments were resolved once before the body runs, binding them doesn't resolve again
+    assert_eq!(VC_RESOLVES.load(Ordering::SeqCst), 1);
+    Ok(Vc::cell(*value.await? + *counted.0.await?))
+}
+
+#[tokio::test]
+async fn test_resolved_vc_argument_resolves_once() -> Result<()> {
+    run_without_cache_check(&REGISTRATION, async {
+        let sum = resolved_sum(cached_double(1), ResolveCountedVc(cached_double(2)));
+        assert_eq!(*sum.await?, 6);
+        assert_eq!(VC_RESOLVES.load(Ordering::SeqCst), 1);
+
+        // already resolved arguments aren't resolved again
+        let value = cached_double(1).to_resolved().await?;
+        let counted = ResolveCountedVc(cached_double(2).resolve().await?);
+        assert_eq!(*resolved_sum(value, counted).await?, 6);
+        assert_eq!(VC_RESOLVES.load(Ordering::SeqCst), 1);
+        anyhow::Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(cache = false)]
+fn uncached_double(value: u32) -> Vc<u32> {
+    Vc::cell(value * 2)
+}
+
+#[turbo_tasks::function(cache = true)]
+fn cached_double(value: u32) -> Vc<u32> {
+    Vc::cell(value * 2)
+}
+
+#[tokio::test]
+async fn test_uncached() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // `cache = false` is the same as `no_cache`, the body runs in the calling task
+        let uncached = uncached_double(21).await?;
+        assert_eq!(
+            Vc::into_raw(uncached).get_task_id(),
+            test_helpers::current_task_for_testing()
+        );
+        assert_eq!(*uncached.await?, 42);
+        assert_eq!(*cached_double(21).await?, 42);
+        Ok(())
+    })
+    .await
+}
+
+mod nested {
+    use turbo_tasks::Vc;
+
+    #[turbo_tasks::function]
+    pub fn nested_identity(value: u32) -> Vc<u32> {
+        Vc::cell(value)
+    }
+
+    #[turbo_tasks::function(static_vis = "pub", module_path = "function_arguments::api")]
+    pub fn reexported_identity(value: u32) -> Vc<u32> {
+        Vc::cell(value)
+    }
+}
+
+mod api {
+    pub use super::nested::{
+        reexported_identity, REEXPORTED_IDENTITY_FUNCTION, REEXPORTED_IDENTITY_FUNCTION_ID,
+        REEXPORTED_IDENTITY_FUNCTION_MODULE_PATH,
+    };
+}
+
+#[test]
+fn test_module_path() {
+    assert_eq!(
+        nested::NESTED_IDENTITY_FUNCTION_MODULE_PATH,
+        "function_arguments::nested"
+    );
+    assert_eq!(
+        api::REEXPORTED_IDENTITY_FUNCTION_MODULE_PATH,
+        "function_arguments::api"
+    );
+    assert_eq!(CACHED_DOUBLE_FUNCTION_MODULE_PATH, "function_arguments");
+}
+
+#[turbo_tasks::function]
+fn is_borrowed(value: Cow<'static, str>, expected: RcStr) -> Vc<bool> {
+    assert_eq!(value, expected.as_str());
+    Vc::cell(matches!(value, Cow::Borrowed(_)))
+}
+
+#[tokio::test]
+async fn test_cow_str_argument() -> Result<()> {
+    // `Cow<'static, str>` arguments are stored as a `CowStrTaskInput`
+    IS_BORROWED_FUNCTION
+        .arg_meta
+        .check(&(CowStrTaskInput::Borrowed("foo"), RcStr::from("foo")))?;
+    run(&REGISTRATION, || async {
+        assert!(*is_borrowed(Cow::Borrowed("foo"), "foo".into()).await?);
+        assert!(!*is_borrowed(Cow::Owned("bar".to_owned()), "bar".into()).await?);
+        Ok(())
+    })
+    .await
+}
+
+fn register() {
+    REGISTRATION.ensure_registered();
+}
+
+#[turbo_tasks::function(gen_smoke_test = register)]
+fn smoke_tested(value: u32, name: RcStr, values: Vec<u32>) -> Vc<u32> {
+    Vc::cell(value + name.len() as u32 + values.iter().sum::<u32>())
+}
+
+#[test]
+fn test_gen_smoke_test() {
+    // the generated `#[turbo_tasks::test]` runs as part of this file, it's only referenced here
+    let _ = smoke_tested_smoke_test;
+}
+
+#[turbo_tasks::function(count_calls)]
+fn counted(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[tokio::test]
+async fn test_count_calls() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let before = counted_call_count();
+        assert_eq!(*counted(1).await?, 1);
+        assert_eq!(*counted(2).await?, 2);
+        // every call is counted, including the ones that are cached
+        assert_eq!(*counted(1).await?, 1);
+        assert_eq!(counted_call_count() - before, 3);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(track_duration)]
+async fn sleeping(millis: u64) -> Result<Vc<u64>> {
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    Ok(Vc::cell(millis))
+}
+
+#[tokio::test]
+async fn test_track_duration() -> Result<()> {
+    // the last duration is global, and cached calls don't update it, so this runs once, on a
+    // fresh instance
+    SLEEPING_FUNCTION_LAST_DURATION.store(0, Ordering::Relaxed);
+    run_without_cache_check(&REGISTRATION, async {
+        assert_eq!(sleeping_last_duration(), Duration::ZERO);
+        assert_eq!(*sleeping(50).await?, 50);
+        assert!(sleeping_last_duration() >= Duration::from_millis(50));
+        assert_eq!(*sleeping(0).await?, 0);
+        assert!(sleeping_last_duration() < Duration::from_millis(50));
+        anyhow::Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(lazy_body)]
+async fn lazily_summed(values: Vec<ResolvedVc<u32>>) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for value in values {
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[tokio::test]
+async fn test_lazy_body() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let values = vec![Vc::cell(1), Vc::cell(2), Vc::cell(3)];
+        assert_eq!(*lazily_summed(values).await?, 6);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(resolve_return)]
+fn resolved_double(value: u32) -> Vc<u32> {
+    Vc::cell(value * 2)
+}
+
+#[tokio::test]
+async fn test_resolve_return() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let output: Vc<u32> = resolved_double(21).await?;
+        assert!(output.is_resolved());
+        assert_eq!(*output.await?, 42);
+        Ok(())
+    })
+    .await
+}
+
+const ARRAY_LEN: usize = 3;
+
+#[turbo_tasks::function]
+async fn sum_array(values: [ResolvedVc<u32>; ARRAY_LEN]) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for value in values {
+        assert!(value.is_resolved());
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[tokio::test]
+async fn test_array_argument() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // the exposed function accepts unresolved `Vc`s, the length is kept
+        let f: fn([Vc<u32>; ARRAY_LEN]) -> Vc<u32> = sum_array;
+        assert_eq!(*f([Vc::cell(1), Vc::cell(2), Vc::cell(3)]).await?, 6);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn arc_str_len(value: Arc<str>) -> Vc<u32> {
+    Vc::cell(value.len() as u32)
+}
+
+#[turbo_tasks::function]
+async fn sum_arc_slice(values: Arc<[ResolvedVc<u32>]>) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for value in values.iter() {
+        assert!(value.is_resolved());
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[tokio::test]
+async fn test_arc_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // `Arc<str>` is passed through, the elements of `Arc<[T]>` are expanded
+        let f: fn(Arc<str>) -> Vc<u32> = arc_str_len;
+        assert_eq!(*f("shared".into()).await?, 6);
+        let f: fn(Arc<[Vc<u32>]>) -> Vc<u32> = sum_arc_slice;
+        assert_eq!(*f([Vc::cell(1), Vc::cell(2), Vc::cell(3)].into()).await?, 6);
+        Ok(())
+    })
+    .await
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TaskInput, Serialize, Deserialize)]
+struct SharedConfig {
+    names: Vec<RcStr>,
+}
+
+#[turbo_tasks::function]
+fn shared_config_len(config: Arc<SharedConfig>) -> Vc<u32> {
+    Vc::cell(config.names.len() as u32)
+}
+
+#[turbo_tasks::function]
+async fn sum_arc_tuple(value: Arc<(u32, ResolvedVc<u32>)>) -> Result<Vc<u32>> {
+    assert!(value.1.is_resolved());
+    Ok(Vc::cell(value.0 + *value.1.await?))
+}
+
+#[tokio::test]
+async fn test_arc_value_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // `Arc`s are compared by their contents, so equal configs share a task
+        let config = Arc::new(SharedConfig {
+            names: vec!["a".into(), "b".into()],
+        });
+        let first = shared_config_len(config.clone());
+        let second = shared_config_len(Arc::new((*config).clone()));
+        assert_eq!(first.resolve().await?, second.resolve().await?);
+        assert_eq!(*first.await?, 2);
+
+        let f: fn(Arc<(u32, Vc<u32>)>) -> Vc<u32> = sum_arc_tuple;
+        assert_eq!(*f(Arc::new((1, Vc::cell(2)))).await?, 3);
+        Ok(())
+    })
+    .await
+}
+
+fn default_length() -> Vc<u32> {
+    Vc::cell(0)
+}
+
+#[turbo_tasks::function(fallback = default_length)]
+async fn parse_length(value: RcStr) -> Result<Vc<u32>> {
+    Ok(Vc::cell(value.parse()?))
+}
+
+#[tokio::test]
+async fn test_fallback() -> Result<()> {
+    run(&REGISTRATION, || async {
+        assert_eq!(*parse_length("42".into()).await?, 42);
+        // the error is replaced by the result of the fallback
+        assert_eq!(*parse_length("not a number".into()).await?, 0);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(trace_level = "debug")]
+fn traced(
+    #[turbo_tasks(span)] important: u32,
+    #[turbo_tasks(span = "trace")] verbose: u32,
+    ignored: u32,
+) -> Vc<u32> {
+    Vc::cell(important + verbose + ignored)
+}
+
+/// Collects the names and values of the fields recorded on spans, up to a maximum level.
+struct SpanFieldRecorder {
+    max_level: tracing::Level,
+    fields: Arc<Mutex<Vec<&'static str>>>,
+    values: Arc<Mutex<Vec<String>>>,
+}
+
+impl SpanFieldRecorder {
+    fn record_fields(&self, values: &tracing::span::Record<'_>) {
+        struct Visitor<'a>(&'a mut Vec<&'static str>, &'a mut Vec<String>);
+
+        impl tracing::field::Visit for Visitor<'_> {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn Debug) {
+                if field.name() != "name" {
+                    self.0.push(field.name());
+                    self.1.push(format!("{value:?}"));
+                }
+            }
+        }
+
+        values.record(&mut Visitor(
+            &mut self.fields.lock().unwrap(),
+            &mut self.values.lock().unwrap(),
+        ));
+    }
+}
+
+impl tracing::Subscriber for SpanFieldRecorder {
+    fn register_callsite(
+        &self,
+        _metadata: &'static tracing::Metadata<'static>,
+    ) -> tracing::subscriber::Interest {
+        tracing::subscriber::Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        *metadata.level() <= self.max_level
+    }
+
+    fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        self.record_fields(&tracing::span::Record::new(span.values()));
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+        self.record_fields(values);
+    }
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, _event: &tracing::Event<'_>) {}
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+#[tokio::test]
+async fn test_span_fields() -> Result<()> {
+    run(&REGISTRATION, || async {
+        for (max_level, expected) in [
+            (tracing::Level::INFO, &[][..]),
+            (tracing::Level::DEBUG, &["important"][..]),
+            (tracing::Level::TRACE, &["important", "verbose"][..]),
+        ] {
+            let fields = Arc::new(Mutex::new(Vec::new()));
+            let recorder = SpanFieldRecorder {
+                max_level,
+                fields: fields.clone(),
+                values: Default::default(),
+            };
+            let output = tracing::subscriber::with_default(recorder, || traced(1, 2, 3));
+            assert_eq!(*output.await?, 6);
+            assert_eq!(*fields.lock().unwrap(), expected, "at {max_level}");
+        }
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(trace_level = "debug")]
+fn traced_host(
+    #[turbo_tasks(trace_as = url.split_once("://").map(|(_, host)| host))] url: RcStr,
+) -> Vc<u32> {
+    Vc::cell(url.len() as u32)
+}
+
+#[tokio::test]
+async fn test_trace_as() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let fields = Arc::new(Mutex::new(Vec::new()));
+        let values = Arc::new(Mutex::new(Vec::new()));
+        let recorder = SpanFieldRecorder {
+            max_level: tracing::Level::DEBUG,
+            fields: fields.clone(),
+            values: values.clone(),
+        };
+        let output = tracing::subscriber::with_default(recorder, || {
+            traced_host("https://vercel.com".into())
+        });
+        assert_eq!(*output.await?, 18);
+        assert_eq!(*fields.lock().unwrap(), ["url"]);
+        // the derived value is recorded instead of the argument
+        assert_eq!(*values.lock().unwrap(), ["Some(\"vercel.com\")"]);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn untracked_label(value: u32, #[turbo_tasks(untracked)] label: RcStr) -> Vc<RcStr> {
+    Vc::cell(format!("{label}: {value}").into())
+}
+
+#[tokio::test]
+async fn test_untracked() -> Result<()> {
+    run(&REGISTRATION, || async {
+        assert_eq!(&*untracked_label(1, "first".into()).await?, "first: 1");
+        // the untracked argument isn't part of the cache key, so the first call's task is reused
+        assert_eq!(&*untracked_label(1, "second".into()).await?, "first: 1");
+        assert_eq!(&*untracked_label(2, "second".into()).await?, "second: 2");
+        Ok(())
+    })
+    .await
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TaskInput, Serialize, Deserialize)]
+struct LabelOptions {
+    prefix: RcStr,
+    uppercase: bool,
+    comment: RcStr,
+}
+
+#[turbo_tasks::function]
+fn keyed_label(
+    value: u32,
+    #[turbo_tasks(key(|options| (&options.prefix, options.uppercase)))] options: LabelOptions,
+) -> Vc<RcStr> {
+    let label = format!("{}{value}", options.prefix);
+    Vc::cell(
+        if options.uppercase {
+            label.to_uppercase()
+        } else {
+            label
+        }
+        .into(),
+    )
+}
+
+#[tokio::test]
+async fn test_key_projection() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let options = |prefix: &str, comment: &str| LabelOptions {
+            prefix: prefix.into(),
+            uppercase: false,
+            comment: comment.into(),
+        };
+        assert_eq!(&*keyed_label(1, options("a", "first")).await?, "a1");
+        // only the projection is part of the cache key, so the first call's task is reused
+        assert_eq!(
+            keyed_label(1, options("a", "first")).resolve().await?,
+            keyed_label(1, options("a", "second")).resolve().await?
+        );
+        assert_eq!(&*keyed_label(1, options("b", "second")).await?, "b1");
+        let uppercase = LabelOptions {
+            uppercase: true,
+            ..options("a", "first")
+        };
+        assert_eq!(&*keyed_label(1, uppercase).await?, "A1");
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(trace_level = "debug")]
+fn debug_skipped_len(#[turbo_tasks(span, debug_skip)] secret: RcStr) -> Vc<u32> {
+    Vc::cell(secret.len() as u32)
+}
+
+#[tokio::test]
+async fn test_debug_skip() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let fields = Arc::new(Mutex::new(Vec::new()));
+        let values = Arc::new(Mutex::new(Vec::new()));
+        let recorder = SpanFieldRecorder {
+            max_level: tracing::Level::DEBUG,
+            fields: fields.clone(),
+            values: values.clone(),
+        };
+        let output =
+            tracing::subscriber::with_default(recorder, || debug_skipped_len("hunter2".into()));
+        assert_eq!(*output.await?, 7);
+        assert_eq!(*fields.lock().unwrap(), ["secret"]);
+        // the argument is still passed to the task, but isn't formatted
+        assert_eq!(*values.lock().unwrap(), [".."]);
+        Ok(())
+    })
+    .await
+}
+
+static NO_CACHE_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[turbo_tasks::function(no_cache)]
+fn next_execution() -> Vc<u32> {
+    Vc::cell(NO_CACHE_EXECUTIONS.fetch_add(1, Ordering::SeqCst) as u32)
+}
+
+#[tokio::test]
+async fn test_no_cache() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let first = next_execution().await?;
+        // the body runs directly in the calling task, no task is created for the call
+        assert_eq!(
+            Vc::into_raw(first).get_task_id(),
+            test_helpers::current_task_for_testing()
+        );
+        let first = *first.await?;
+        // so nothing is cached, and every call executes the body again
+        let second = *next_execution().await?.await?;
+        assert!(second > first);
+        assert!(*next_execution().await?.await? > second);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+async fn join_index_map(
+    #[turbo_tasks(map_value)] values: IndexMap<RcStr, ResolvedVc<u32>>,
+) -> Result<Vc<RcStr>> {
+    let mut entries = Vec::with_capacity(values.len());
+    for (key, value) in values {
+        assert!(value.is_resolved());
+        entries.push(format!("{key}={}", *value.await?));
+    }
+    Ok(Vc::cell(entries.join(",").into()))
+}
+
+#[tokio::test]
+async fn test_map_value() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let values = IndexMap::from([
+            (RcStr::from("b"), Vc::cell(2)),
+            (RcStr::from("a"), Vc::cell(1)),
+        ]);
+        // the entries are sorted by key
+        assert_eq!(&**join_index_map(values).await?, "a=1,b=2");
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+fn sum_hash_map(#[turbo_tasks(map_value)] values: HashMap<u32, u32>) -> Vc<u32> {
+    Vc::cell(values.values().sum())
+}
+
+#[tokio::test]
+async fn test_map_value_order() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let forward: HashMap<u32, u32> = (0..32).map(|i| (i, i)).collect();
+        let backward: HashMap<u32, u32> = (0..32).rev().map(|i| (i, i)).collect();
+        assert_eq!(forward, backward);
+        // equal maps are the same task input, regardless of their iteration order
+        let forward = sum_hash_map(forward);
+        let backward = sum_hash_map(backward);
+        assert_eq!(Vc::into_raw(forward), Vc::into_raw(backward));
+        assert_eq!(*forward.await?, 496);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+async fn join_btree_map(values: BTreeMap<RcStr, (u32, ResolvedVc<u32>)>) -> Result<Vc<RcStr>> {
+    let mut entries = Vec::with_capacity(values.len());
+    for (key, (offset, value)) in values {
+        assert!(value.is_resolved());
+        entries.push(format!("{key}={}", offset + *value.await?));
+    }
+    Ok(Vc::cell(entries.join(",").into()))
+}
+
+#[tokio::test]
+async fn test_btree_map_argument() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let values = BTreeMap::from([
+            (RcStr::from("b"), (10, Vc::cell(2))),
+            (RcStr::from("a"), (10, Vc::cell(1))),
+        ]);
+        assert_eq!(&**join_btree_map(values).await?, "a=11,b=12");
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(version = 1)]
+fn versioned_v1(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[turbo_tasks::function(version = 2)]
+fn versioned_v2(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[tokio::test]
+async fn test_version() -> Result<()> {
+    assert_eq!(VERSIONED_V1_FUNCTION.function_meta.version, 1);
+    assert_eq!(VERSIONED_V2_FUNCTION.function_meta.version, 2);
+    assert_eq!(CACHED_DOUBLE_FUNCTION.function_meta.version, 0);
+    run(&REGISTRATION, || async {
+        assert_eq!(*versioned_v2(3).await?, 3);
+        let v1_name =
+            registry::get_function_global_name(registry::get_function_id(&VERSIONED_V1_FUNCTION));
+        let v2_name =
+            registry::get_function_global_name(registry::get_function_id(&VERSIONED_V2_FUNCTION));
+        assert!(v1_name.ends_with("@v1"), "{v1_name}");
+        assert!(v2_name.ends_with("@v2"), "{v2_name}");
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(readonly)]
+async fn larger(a: Vc<u32>, b: Vc<u32>) -> Result<Vc<u32>> {
+    // returns one of the existing cells without creating a new one
+    Ok(if *a.await? >= *b.await? { a } else { b })
+}
+
+#[tokio::test]
+async fn test_readonly() -> Result<()> {
+    assert!(LARGER_FUNCTION.function_meta.readonly);
+    assert!(!CACHED_DOUBLE_FUNCTION.function_meta.readonly);
+    run(&REGISTRATION, || async {
+        assert_eq!(*larger(Vc::cell(1), Vc::cell(2)).await?, 2);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(args_struct)]
+async fn weighted_sum(
+    a: u32,
+    b: u32,
+    (weight_a, weight_b): (u32, u32),
+    offset: Vc<u32>,
+) -> Result<Vc<u32>> {
+    Ok(Vc::cell(a * weight_a + b * weight_b + *offset.await?))
+}
+
+#[turbo_tasks::function(args_struct)]
+fn first_of_pair((arg1, _): (u32, u32)) -> Vc<u32> {
+    Vc::cell(arg1)
+}
+
+#[tokio::test]
+async fn test_args_struct() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let args = WeightedSumArgs::builder()
+            .a(1)
+            .b(2)
+            .arg3((3, 4))
+            .offset(Vc::cell(5))
+            .build()?;
+        assert_eq!(*weighted_sum(args.clone()).await?, 16);
+        assert_eq!(*weighted_sum(WeightedSumArgs { a: 2, ..args }).await?, 19);
+
+        let missing = WeightedSumArgs::builder().a(1).b(2).build().unwrap_err();
+        assert_eq!(
+            missing.to_string(),
+            "the argument `arg3` of `weighted_sum` is not set"
+        );
+
+        // the generated field name doesn't clash with the names bound by the pattern
+        let args = FirstOfPairArgs::builder()._arg1((1, 2)).build()?;
+        assert_eq!(*first_of_pair(args).await?, 1);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+async fn join_borrowed(separator: &str, parts: &[RcStr]) -> Result<Vc<RcStr>> {
+    Ok(Vc::cell(parts.join(separator).into()))
+}
+
+#[tokio::test]
+async fn test_borrowed_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let parts: Vec<RcStr> = vec!["a".into(), "b".into()];
+        assert_eq!(&*join_borrowed(", ", &parts).await?, "a, b");
+        // the owned task inputs are equal for equal borrowed arguments
+        assert_eq!(
+            join_borrowed("-", &parts).resolve().await?,
+            join_borrowed(&String::from("-"), &parts[..])
+                .resolve()
+                .await?
+        );
+        // the elements of a slice of `ResolvedVc`s are passed as `Vc`s
+        assert_eq!(*sum_borrowed(&[Vc::cell(1), Vc::cell(2)]).await?, 3);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+async fn sum_borrowed(values: &[ResolvedVc<u32>]) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for value in values {
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[turbo_tasks::function]
+async fn greeting(name: impl Into<RcStr>, punctuation: impl AsRef<str>) -> Result<Vc<RcStr>> {
+    let name: RcStr = name.into();
+    let punctuation: &str = punctuation.as_ref();
+    Ok(Vc::cell(format!("Hello, {name}{punctuation}").into()))
+}
+
+#[tokio::test]
+async fn test_impl_trait_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        assert_eq!(&*greeting("world", "!").await?, "Hello, world!");
+        // the arguments are converted to the same task inputs
+        assert_eq!(
+            greeting("world", "!").resolve().await?,
+            greeting(String::from("world"), String::from("!"))
+                .resolve()
+                .await?
+        );
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+async fn sum_slice(values: &[Vc<u32>]) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for value in values {
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[turbo_tasks::function]
+async fn sum_iter(values: impl IntoIterator<Item = Vc<u32>>) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for value in values {
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[tokio::test]
+async fn test_collection_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let values: Vec<Vc<u32>> = vec![Vc::cell(1), Vc::cell(2), Vc::cell(3)];
+        assert_eq!(*sum_slice(&values).await?, 6);
+        assert_eq!(*sum_slice(&values[1..]).await?, 5);
+        assert_eq!(*sum_iter(values.iter().copied()).await?, 6);
+        assert_eq!(*sum_iter([Vc::cell(4), Vc::cell(5)]).await?, 9);
+        // both are converted to the same `Vec<Vc<u32>>` task input
+        assert_eq!(
+            sum_iter(values.clone()).resolve().await?,
+            sum_iter(values.iter().copied()).resolve().await?
+        );
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function]
+async fn sorted_digits(
+    mut values: Vec<u32>,
+    #[turbo_tasks(untracked)] mut extra: u32,
+) -> Result<Vc<u32>> {
+    extra += 1;
+    values.push(extra);
+    values.sort_unstable();
+    Ok(Vc::cell(
+        values.iter().fold(0, |digits, value| digits * 10 + value),
+    ))
+}
+
+#[tokio::test]
+async fn test_mut_bindings() -> Result<()> {
+    run(&REGISTRATION, || async {
+        assert_eq!(*sorted_digits(vec![3, 1], 1).await?, 123);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(also_untracked, count_calls)]
+async fn greeting_length(name: RcStr, extra: u32) -> Result<Vc<u32>> {
+    Ok(Vc::cell(name.len() as u32 + extra))
+}
+
+#[tokio::test]
+async fn test_also_untracked() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let before = greeting_length_call_count();
+        assert_eq!(*greeting_length("hello".into(), 1).await?, 6);
+        // the untracked variant runs the body directly, so it isn't counted as a call
+        assert_eq!(
+            *greeting_length_untracked("hello".into(), 2).await?.await?,
+            7
+        );
+        assert_eq!(greeting_length_call_count() - before, 1);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(also_untracked)]
+async fn add_to_resolved(value: ResolvedVc<u32>, extra: u32) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*value.await? + extra))
+}
+
+#[tokio::test]
+async fn test_also_untracked_resolves_arguments() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // a task's output isn't resolved, the untracked variant resolves it like a task would
+        let value = greeting_length("hello".into(), 1);
+        assert_eq!(*add_to_resolved_untracked(value, 1).await?.await?, 7);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(name = "arguments/renamed")]
+fn renamed(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[tokio::test]
+async fn test_name() -> Result<()> {
+    run(&REGISTRATION, || async {
+        assert_eq!(RENAMED_FUNCTION.name, "arguments/renamed");
+        assert_eq!(*renamed(3).await?, 3);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::function(priority = high)]
+fn urgent(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[turbo_tasks::function(priority = low)]
+fn background(value: u32) -> Vc<u32> {
+    Vc::cell(value)
+}
+
+#[test]
+fn test_priority() {
+    assert_eq!(URGENT_FUNCTION.function_meta.priority, TaskPriority::High);
+    assert_eq!(
+        BACKGROUND_FUNCTION.function_meta.priority,
+        TaskPriority::Low
+    );
+    assert_eq!(
+        COUNTED_FUNCTION.function_meta.priority,
+        TaskPriority::Normal
+    );
+}
@@ -3,7 +3,7 @@
 use std::sync::{Arc, Mutex};
 
 use indexmap::{IndexMap, IndexSet};
-use turbo_tasks::{debug::ValueDebug, Invalidator, ReadRef, TaskId, Vc};
+use turbo_tasks::{debug::ValueDebug, Invalidator, RcStr, ReadRef, TaskId, TaskInput, Vc};
 use turbo_tasks_testing::{register, run, Registration};
 
 static REGISTRATION: Registration = register!();
@@ -174,6 +174,31 @@ async fn test_read_ref_round_trip() {
     .unwrap()
 }
 
+#[tokio::test]
+async fn test_monomorphized_value() {
+    run(&REGISTRATION, || async move {
+        let number = Keyed { key: 1, value: 2 }.cell();
+        let text = keyed_by_name("one".into(), 3);
+        assert_eq!(number.await?.key, 1);
+        assert_eq!(text.await?.key, "one");
+        assert_eq!(text.await?.value, 3);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[turbo_tasks::value(monomorphize = [u32, RcStr])]
+struct Keyed<T: TaskInput> {
+    key: T,
+    value: u32,
+}
+
+#[turbo_tasks::function]
+fn keyed_by_name(key: RcStr, value: u32) -> Vc<Keyed<RcStr>> {
+    Keyed { key, value }.cell()
+}
+
 #[turbo_tasks::value(eq = "manual")]
 #[derive(Default)]
 struct State {
@@ -0,0 +1,55 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{
+    io_enforcement::{check_io, set_io_enforcement, IoEnforcement, IoKind},
+    Vc,
+};
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[tokio::test]
+async fn io_enforcement() {
+    run(&REGISTRATION, || async {
+        set_io_enforcement(IoEnforcement::Panic);
+
+        assert_eq!(*read_file(1).await?, 1);
+        assert_eq!(*read_file_sync(2).await?, 2);
+        assert!(read_unmarked(3).await.is_err());
+        assert!(fetch_from_fs_function(4).await.is_err());
+
+        // IO outside of a function body isn't checked
+        check_io(IoKind::Network, "Network request");
+
+        set_io_enforcement(IoEnforcement::Off);
+        assert_eq!(*read_unmarked(5).await?, 5);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[turbo_tasks::function(fs)]
+async fn read_file(value: u32) -> Result<Vc<u32>> {
+    check_io(IoKind::Filesystem, "Filesystem access");
+    Ok(Vc::cell(value))
+}
+
+#[turbo_tasks::function(fs)]
+fn read_file_sync(value: u32) -> Vc<u32> {
+    check_io(IoKind::Filesystem, "Filesystem access");
+    Vc::cell(value)
+}
+
+#[turbo_tasks::function]
+async fn read_unmarked(value: u32) -> Result<Vc<u32>> {
+    check_io(IoKind::Filesystem, "Filesystem access");
+    Ok(Vc::cell(value))
+}
+
+#[turbo_tasks::function(fs)]
+async fn fetch_from_fs_function(value: u32) -> Result<Vc<u32>> {
+    check_io(IoKind::Network, "Network request");
+    Ok(Vc::cell(value))
+}
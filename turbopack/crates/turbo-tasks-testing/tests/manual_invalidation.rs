@@ -0,0 +1,71 @@
+#![feature(arbitrary_self_types)]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::Result;
+use turbo_tasks::{ResolvedVc, Vc};
+use turbo_tasks_testing::{register, run_without_cache_check, Registration};
+
+static REGISTRATION: Registration = register!();
+
+static EXTERNAL_VALUE: AtomicU32 = AtomicU32::new(1);
+
+#[tokio::test]
+async fn manual_invalidation() {
+    // the external value isn't reset, so this only runs once
+    run_without_cache_check(&REGISTRATION, async {
+        let output = read_external(10);
+        assert_eq!(*output.strongly_consistent().await?, 11);
+
+        // the change isn't tracked, so the task is still cached
+        EXTERNAL_VALUE.store(2, Ordering::SeqCst);
+        assert_eq!(*output.strongly_consistent().await?, 11);
+
+        read_external_invalidator(10).await?.invalidate();
+        assert_eq!(*output.strongly_consistent().await?, 12);
+
+        // a call with other arguments is a different task
+        EXTERNAL_VALUE.store(3, Ordering::SeqCst);
+        read_external_invalidator(20).await?.invalidate();
+        assert_eq!(*output.strongly_consistent().await?, 12);
+        assert_eq!(*read_external(20).strongly_consistent().await?, 23);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[turbo_tasks::function(invalidation = manual)]
+async fn read_external(offset: u32) -> Result<Vc<u32>> {
+    Ok(Vc::cell(EXTERNAL_VALUE.load(Ordering::SeqCst) + offset))
+}
+
+static OTHER_EXTERNAL_VALUE: AtomicU32 = AtomicU32::new(1);
+
+#[tokio::test]
+async fn manual_invalidation_with_vc_argument() {
+    run_without_cache_check(&REGISTRATION, async {
+        let output = add_external(ten());
+        assert_eq!(*output.strongly_consistent().await?, 11);
+
+        OTHER_EXTERNAL_VALUE.store(2, Ordering::SeqCst);
+        // `ten()` isn't resolved, the invalidator resolves it to find the task of the call
+        add_external_invalidator(ten()).await?.invalidate();
+        assert_eq!(*output.strongly_consistent().await?, 12);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[turbo_tasks::function]
+fn ten() -> Vc<u32> {
+    Vc::cell(10)
+}
+
+#[turbo_tasks::function(invalidation = manual)]
+async fn add_external(value: ResolvedVc<u32>) -> Result<Vc<u32>> {
+    Ok(Vc::cell(
+        OTHER_EXTERNAL_VALUE.load(Ordering::SeqCst) + *value.await?,
+    ))
+}
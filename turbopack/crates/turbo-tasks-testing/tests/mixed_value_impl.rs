@@ -0,0 +1,42 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[turbo_tasks::value]
+struct Counter(u32);
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    /// Items without `#[turbo_tasks::function]` are passed through unchanged.
+    const STEP: u32 = 2;
+
+    fn stepped(value: u32) -> u32 {
+        value + Self::STEP
+    }
+
+    #[turbo_tasks::function]
+    fn new(value: u32) -> Vc<Self> {
+        Counter(value).cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn next(self: Vc<Self>) -> Result<Vc<u32>> {
+        Ok(Vc::cell(Self::stepped(self.await?.0)))
+    }
+}
+
+#[tokio::test]
+async fn plain_items_in_value_impl() {
+    run(&REGISTRATION, || async {
+        assert_eq!(Counter::STEP, 2);
+        assert_eq!(Counter::stepped(1), 3);
+        assert_eq!(*Counter::new(40).next().await?, 42);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
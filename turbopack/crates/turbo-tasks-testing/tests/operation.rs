@@ -0,0 +1,46 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{OperationVc, Vc};
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[tokio::test]
+async fn operation_output() {
+    run(&REGISTRATION, || async {
+        let operation = multiply(6, 7);
+        assert_eq!(*operation.read_strongly_consistent().await?, 42);
+        assert_eq!(*operation.resolve_strongly_consistent().await?.await?, 42);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn operation_input() {
+    run(&REGISTRATION, || async {
+        let operation = add_one(multiply(6, 7));
+        assert_eq!(*operation.read_strongly_consistent().await?, 43);
+        assert_eq!(*connect_operation(operation).await?, 43);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[turbo_tasks::function(operation)]
+fn multiply(a: u32, b: u32) -> Vc<u32> {
+    Vc::cell(a * b)
+}
+
+#[turbo_tasks::function(operation)]
+async fn add_one(operation: OperationVc<u32>) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*operation.connect().await? + 1))
+}
+
+#[turbo_tasks::function]
+fn connect_operation(operation: OperationVc<u32>) -> Vc<u32> {
+    operation.connect()
+}
@@ -35,3 +35,88 @@ async fn test_cell_construction() -> Result<()> {
     })
     .await
 }
+
+#[turbo_tasks::function]
+async fn sum_resolved_pairs(pairs: Vec<(ResolvedVc<u32>, u32)>) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for (resolved, value) in pairs {
+        sum += *resolved.await? + value;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[tokio::test]
+async fn test_resolved_vc_in_vec_of_tuples_argument() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // the exposed function accepts unresolved `Vc`s in place of `ResolvedVc`s
+        let pairs: Vec<(Vc<u32>, u32)> = vec![(Vc::cell(1), 2), (Vc::cell(3), 4)];
+        assert_eq!(*sum_resolved_pairs(pairs).await?, 10);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::value_trait]
+trait Number {
+    fn value(self: Vc<Self>) -> Vc<u32>;
+}
+
+#[turbo_tasks::value_impl]
+impl Number for Wrapper {
+    #[turbo_tasks::function]
+    fn value(&self) -> Vc<u32> {
+        Vc::cell(self.0)
+    }
+}
+
+#[turbo_tasks::function]
+async fn sum_numbers(numbers: Vec<ResolvedVc<Box<dyn Number>>>) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for number in numbers {
+        assert!(number.is_resolved());
+        sum += *(*number).value().await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[tokio::test]
+async fn test_resolved_vc_trait_objects_in_vec_argument() -> Result<()> {
+    run(&REGISTRATION, || async {
+        let numbers: Vec<Vc<Box<dyn Number>>> =
+            vec![Vc::upcast(Wrapper(1).cell()), Vc::upcast(Wrapper(2).cell())];
+        assert_eq!(*sum_numbers(numbers).await?, 3);
+        Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::value_impl]
+impl Wrapper {
+    #[turbo_tasks::function]
+    fn resolved_receiver(self: ResolvedVc<Self>) -> Vc<bool> {
+        Vc::cell(self.is_resolved())
+    }
+
+    #[turbo_tasks::function]
+    async fn async_resolved_receiver(self: ResolvedVc<Self>, offset: u32) -> Result<Vc<u32>> {
+        assert!(self.is_resolved());
+        Ok(Vc::cell(self.await?.0 + offset))
+    }
+}
+
+#[turbo_tasks::function]
+fn unresolved_wrapper(value: u32) -> Vc<Wrapper> {
+    Wrapper(value).cell()
+}
+
+#[tokio::test]
+async fn test_resolved_vc_receiver() -> Result<()> {
+    run(&REGISTRATION, || async {
+        // the output of another task is unresolved, so it's resolved before the method is called
+        let wrapper = unresolved_wrapper(42);
+        assert!(*wrapper.resolved_receiver().await?);
+        assert_eq!(*wrapper.async_resolved_receiver(1).await?, 43);
+        Ok(())
+    })
+    .await
+}
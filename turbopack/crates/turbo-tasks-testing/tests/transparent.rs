@@ -0,0 +1,70 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{debug::ValueDebug, RcStr, Vc};
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[turbo_tasks::value(transparent)]
+enum Wrapped {
+    Number(u32),
+}
+
+#[turbo_tasks::value(transparent, map_get, map_traits)]
+struct Names(IndexMap<RcStr, RcStr>);
+
+#[turbo_tasks::function]
+fn names() -> Vc<Names> {
+    Names::from_iter([("a".into(), "alpha".into()), ("b".into(), "beta".into())]).cell()
+}
+
+#[turbo_tasks::function]
+async fn joined_names(names: Vc<Names>) -> Result<Vc<RcStr>> {
+    let names = names.await?;
+    let mut joined = String::new();
+    for (key, name) in &*names {
+        joined.push_str(&format!("{key}={name};"));
+    }
+    Ok(Vc::cell(joined.into()))
+}
+
+#[tokio::test]
+async fn transparent_enum() {
+    run(&REGISTRATION, || async {
+        let wrapped: Vc<Wrapped> = Vc::cell(42);
+        assert_eq!(*wrapped.await?, 42);
+        assert_eq!(format!("{:?}", wrapped.dbg().await?), "42");
+
+        let wrapped = Wrapped::Number(43).cell();
+        assert_eq!(*wrapped.await?, 43);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn transparent_map() {
+    run(&REGISTRATION, || async {
+        let mut map = Names::from_iter([("a".into(), "alpha".into())]);
+        map.insert("b".into(), "beta".into());
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            (&map)
+                .into_iter()
+                .map(|(key, _)| key.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+
+        let names = names();
+        assert_eq!(&*joined_names(names).await?, "a=alpha;b=beta;");
+        assert_eq!(*names.get("a".into()).await?, Some(RcStr::from("alpha")));
+        assert_eq!(*names.get("c".into()).await?, None);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,56 @@
+#![feature(arbitrary_self_types)]
+
+use turbo_tasks::{RcStr, Vc};
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[turbo_tasks::value]
+struct Settings {
+    #[serde(rename = "n")]
+    name: RcStr,
+    // fields added after values were serialized
+    #[serde(default = "default_retries")]
+    retries: u32,
+    #[serde(default)]
+    tags: Vec<RcStr>,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+#[turbo_tasks::value(serialization = "none")]
+struct Unserialized {
+    #[serde(default)]
+    value: u32,
+}
+
+#[test]
+fn serde_attributes_are_passed_through() {
+    let settings = Settings {
+        name: "fast".into(),
+        retries: 1,
+        tags: vec!["a".into()],
+    };
+    assert_eq!(
+        serde_json::to_string(&settings).unwrap(),
+        r#"{"n":"fast","retries":1,"tags":["a"]}"#
+    );
+
+    let settings: Settings = serde_json::from_str(r#"{"n":"slow"}"#).unwrap();
+    assert_eq!(settings.name, "slow");
+    assert_eq!(settings.retries, 3);
+    assert!(settings.tags.is_empty());
+}
+
+#[tokio::test]
+async fn serde_attributes_without_serialization() {
+    run(&REGISTRATION, || async {
+        let value: Vc<Unserialized> = Unserialized { value: 7 }.cell();
+        assert_eq!(value.await?.value, 7);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,56 @@
+#![feature(arbitrary_self_types)]
+
+use turbo_tasks::Vc;
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[turbo_tasks::value_trait]
+trait Shape {
+    const SIDES: u32;
+    const NAME: &'static str = "shape";
+
+    fn sides(self: Vc<Self>) -> Vc<u32>;
+}
+
+#[turbo_tasks::value]
+struct Square;
+
+#[turbo_tasks::value_impl]
+impl Shape for Square {
+    const SIDES: u32 = 4;
+    const NAME: &'static str = "square";
+
+    #[turbo_tasks::function]
+    fn sides(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(Self::SIDES)
+    }
+}
+
+#[turbo_tasks::value]
+struct Triangle;
+
+#[turbo_tasks::value_impl]
+impl Shape for Triangle {
+    const SIDES: u32 = 3;
+
+    #[turbo_tasks::function]
+    fn sides(self: Vc<Self>) -> Vc<u32> {
+        Vc::cell(Self::SIDES)
+    }
+}
+
+#[tokio::test]
+async fn value_trait_consts() {
+    assert_eq!(<Square as ShapeConsts>::NAME, "square");
+    assert_eq!(<Triangle as ShapeConsts>::NAME, "shape");
+    run(&REGISTRATION, || async {
+        let shape: Vc<Box<dyn Shape>> = Vc::upcast(Square.cell());
+        assert_eq!(*shape.sides().await?, 4);
+        let shape: Vc<Box<dyn Shape>> = Vc::upcast(Triangle.cell());
+        assert_eq!(*shape.sides().await?, 3);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,82 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{debug::ValueDebug, RcStr, Vc};
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+#[turbo_tasks::value_trait]
+trait Named {
+    fn name(self: Vc<Self>) -> Vc<RcStr>;
+}
+
+#[turbo_tasks::value_trait]
+trait Labeled: Named {
+    fn label(self: Vc<Self>) -> Vc<RcStr>;
+}
+
+// `Named` is listed again, so that an item can be upcast to it directly. The other bounds can't
+// be upcast to and are skipped.
+#[turbo_tasks::value_trait]
+trait Item: Labeled + Named + ValueDebug + Send + 'static {
+    fn pages(self: Vc<Self>) -> Vc<u32>;
+}
+
+#[turbo_tasks::value]
+struct Book {
+    title: RcStr,
+    pages: u32,
+}
+
+#[turbo_tasks::value_impl]
+impl Named for Book {
+    #[turbo_tasks::function]
+    fn name(&self) -> Vc<RcStr> {
+        Vc::cell(self.title.clone())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Labeled for Book {
+    #[turbo_tasks::function]
+    async fn label(self: Vc<Self>) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(format!("Book: {}", self.name().await?).into()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Item for Book {
+    #[turbo_tasks::function]
+    fn pages(&self) -> Vc<u32> {
+        Vc::cell(self.pages)
+    }
+}
+
+#[tokio::test]
+async fn value_trait_supertraits() {
+    run(&REGISTRATION, || async {
+        let item: Vc<Box<dyn Item>> = Vc::upcast(
+            Book {
+                title: "Dune".into(),
+                pages: 412,
+            }
+            .cell(),
+        );
+        assert_eq!(*item.pages().await?, 412);
+
+        let labeled: Vc<Box<dyn Labeled>> = Vc::upcast(item);
+        assert_eq!(&*labeled.label().await?, "Book: Dune");
+
+        let named: Vc<Box<dyn Named>> = Vc::upcast(item);
+        assert_eq!(&*named.name().await?, "Dune");
+        let named: Vc<Box<dyn Named>> = Vc::upcast(labeled);
+        assert_eq!(&*named.name().await?, "Dune");
+
+        let debug: Vc<Box<dyn ValueDebug>> = Vc::upcast(item);
+        assert!(debug.dbg().await?.as_str().contains("Dune"));
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -2,7 +2,7 @@ use std::{
     borrow::Cow,
     fmt::{self, Debug, Display, Write},
     future::Future,
-    hash::BuildHasherDefault,
+    hash::{BuildHasherDefault, Hash, Hasher},
     pin::Pin,
     sync::Arc,
     time::Duration,
@@ -13,7 +13,6 @@ use auto_hash_map::AutoMap;
 use rustc_hash::FxHasher;
 use tracing::Span;
 
-pub use crate::id::{BackendJobId, ExecutionId};
 use crate::{
     event::EventListener,
     magic_any::MagicAny,
@@ -26,6 +25,10 @@ use crate::{
     FunctionId, RawVc, ReadRef, SharedReference, TaskId, TaskIdSet, TaskPersistence, TraitRef,
     TraitTypeId, ValueTypeId, VcRead, VcValueTrait, VcValueType,
 };
+pub use crate::{
+    id::{BackendJobId, ExecutionId},
+    native_function::ArgHasher,
+};
 
 type TransientTaskRoot =
     Box<dyn Fn() -> Pin<Box<dyn Future<Output = Result<RawVc>> + Send>> + Send + Sync>;
@@ -56,13 +59,15 @@ impl Debug for TransientTaskType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum CachedTaskType {
     /// A normal task execution a native (rust) function
     Native {
         fn_type: FunctionId,
         this: Option<RawVc>,
         arg: Box<dyn MagicAny>,
+        /// The function's [`ArgHasher`], see `#[turbo_tasks::function(hasher = ...)]`.
+        arg_hasher: ArgHasher,
     },
 
     /// A resolve task, which resolves arguments and calls the function with
@@ -71,6 +76,8 @@ pub enum CachedTaskType {
         fn_type: FunctionId,
         this: Option<RawVc>,
         arg: Box<dyn MagicAny>,
+        /// The function's [`ArgHasher`], see `#[turbo_tasks::function(hasher = ...)]`.
+        arg_hasher: ArgHasher,
     },
 
     /// A trait method resolve task. It resolves the first (`self`) argument and
@@ -85,6 +92,41 @@ pub enum CachedTaskType {
     },
 }
 
+impl Hash for CachedTaskType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Native {
+                fn_type,
+                this,
+                arg,
+                arg_hasher,
+            }
+            | Self::ResolveNative {
+                fn_type,
+                this,
+                arg,
+                arg_hasher,
+            } => {
+                fn_type.hash(state);
+                this.hash(state);
+                arg_hasher.hash(&**arg, state);
+            }
+            Self::ResolveTrait {
+                trait_type,
+                method_name,
+                this,
+                arg,
+            } => {
+                trait_type.hash(state);
+                method_name.hash(state);
+                this.hash(state);
+                arg.hash(state);
+            }
+        }
+    }
+}
+
 impl Display for CachedTaskType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.get_name())
@@ -163,7 +205,9 @@ mod ser {
             S: ser::Serializer,
         {
             match self {
-                CachedTaskType::Native { fn_type, this, arg } => {
+                CachedTaskType::Native {
+                    fn_type, this, arg, ..
+                } => {
                     let mut s = serializer.serialize_tuple(5)?;
                     s.serialize_element::<u8>(&0)?;
                     s.serialize_element(&FunctionAndArg::Borrowed {
@@ -175,7 +219,9 @@ mod ser {
                     s.serialize_element(&())?;
                     s.end()
                 }
-                CachedTaskType::ResolveNative { fn_type, this, arg } => {
+                CachedTaskType::ResolveNative {
+                    fn_type, this, arg, ..
+                } => {
                     let mut s = serializer.serialize_tuple(5)?;
                     s.serialize_element::<u8>(&1)?;
                     s.serialize_element(&FunctionAndArg::Borrowed {
@@ -246,7 +292,12 @@ mod ser {
                             let () = seq
                                 .next_element()?
                                 .ok_or_else(|| serde::de::Error::invalid_length(4, &self))?;
-                            Ok(CachedTaskType::Native { fn_type, this, arg })
+                            Ok(CachedTaskType::Native {
+                                fn_type,
+                                this,
+                                arg,
+                                arg_hasher: registry::get_function(fn_type).arg_meta.hasher(),
+                            })
                         }
                         1 => {
                             let FunctionAndArg::Owned { fn_type, arg } = seq
@@ -264,7 +315,12 @@ mod ser {
                             let () = seq
                                 .next_element()?
                                 .ok_or_else(|| serde::de::Error::invalid_length(4, &self))?;
-                            Ok(CachedTaskType::ResolveNative { fn_type, this, arg })
+                            Ok(CachedTaskType::ResolveNative {
+                                fn_type,
+                                this,
+                                arg,
+                                arg_hasher: registry::get_function(fn_type).arg_meta.hasher(),
+                            })
                         }
                         2 => {
                             let trait_type = seq
@@ -312,11 +368,13 @@ impl CachedTaskType {
                 fn_type: native_fn,
                 this: _,
                 arg: _,
+                arg_hasher: _,
             } => Cow::Borrowed(&registry::get_function(*native_fn).name),
             Self::ResolveNative {
                 fn_type: native_fn,
                 this: _,
                 arg: _,
+                arg_hasher: _,
             } => format!("*{}", registry::get_function(*native_fn).name).into(),
             Self::ResolveTrait {
                 trait_type: trait_id,
@@ -751,6 +809,7 @@ pub(crate) mod tests {
                 fn_type: *MOCK_FUNC_TASK_FUNCTION_ID,
                 this: None,
                 arg: Box::new(()),
+                arg_hasher: ArgHasher::default(),
             }
             .get_name(),
             "mock_func_task",
@@ -766,4 +825,58 @@ pub(crate) mod tests {
             "*MockTrait::mock_method_task",
         );
     }
+
+    /// The `Hash` impl of `CachedTaskType` before functions could override how their arguments
+    /// are hashed.
+    #[derive(Hash)]
+    enum DerivedHashCachedTaskType {
+        Native {
+            fn_type: FunctionId,
+            this: Option<RawVc>,
+            arg: Box<dyn MagicAny>,
+        },
+        ResolveNative {
+            fn_type: FunctionId,
+            this: Option<RawVc>,
+            arg: Box<dyn MagicAny>,
+        },
+    }
+
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_default_arg_hasher_matches_derived_hash() {
+        crate::register();
+        let this = Some(RawVc::TaskOutput(unsafe { TaskId::new_unchecked(1) }));
+        assert_eq!(
+            hash_of(&CachedTaskType::Native {
+                fn_type: *MOCK_FUNC_TASK_FUNCTION_ID,
+                this,
+                arg: Box::new(42u32),
+                arg_hasher: ArgHasher::default(),
+            }),
+            hash_of(&DerivedHashCachedTaskType::Native {
+                fn_type: *MOCK_FUNC_TASK_FUNCTION_ID,
+                this,
+                arg: Box::new(42u32),
+            }),
+        );
+        assert_eq!(
+            hash_of(&CachedTaskType::ResolveNative {
+                fn_type: *MOCK_FUNC_TASK_FUNCTION_ID,
+                this: None,
+                arg: Box::new(("foo", 42u32)),
+                arg_hasher: ArgHasher::default(),
+            }),
+            hash_of(&DerivedHashCachedTaskType::ResolveNative {
+                fn_type: *MOCK_FUNC_TASK_FUNCTION_ID,
+                this: None,
+                arg: Box::new(("foo", 42u32)),
+            }),
+        );
+    }
 }
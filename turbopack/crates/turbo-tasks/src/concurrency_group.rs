@@ -0,0 +1,91 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+use tokio::sync::Semaphore;
+use tokio_util::sync::PollSemaphore;
+
+/// The permits of a concurrency group (see
+/// [`FunctionMeta::concurrency_group`][crate::FunctionMeta::concurrency_group]).
+pub struct ConcurrencyGroup {
+    semaphore: Arc<Semaphore>,
+    limit: usize,
+}
+
+impl ConcurrencyGroup {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            limit,
+        }
+    }
+
+    pub fn semaphore(&self) -> Arc<Semaphore> {
+        self.semaphore.clone()
+    }
+
+    /// Resizes the semaphore, so that the permits held by running tasks still count towards the
+    /// new limit.
+    pub fn set_limit(&mut self, limit: usize) {
+        if limit > self.limit {
+            self.semaphore.add_permits(limit - self.limit);
+        } else if limit < self.limit {
+            let excess = self.limit - limit;
+            let forgotten = self.semaphore.forget_permits(excess);
+            if forgotten < excess {
+                // The remaining permits are held by running tasks. The semaphore is fair, so the
+                // queued acquisition takes them before any other task gets a permit.
+                let semaphore = self.semaphore.clone();
+                let remaining = u32::try_from(excess - forgotten).unwrap_or(u32::MAX);
+                tokio::spawn(async move {
+                    if let Ok(permits) = semaphore.acquire_many_owned(remaining).await {
+                        permits.forget();
+                    }
+                });
+            }
+        }
+        self.limit = limit;
+    }
+}
+
+pin_project! {
+    /// Polls the future of a task's execution only while holding a permit of its concurrency
+    /// group. The permit is released whenever the future is pending, so a task waiting for other
+    /// tasks (e.g. of the same group) doesn't take up a slot of the group.
+    pub struct ConcurrencyGroupFuture<F> {
+        semaphore: Option<PollSemaphore>,
+        #[pin]
+        future: F,
+    }
+}
+
+impl<F: Future> ConcurrencyGroupFuture<F> {
+    pub fn new(semaphore: Option<Arc<Semaphore>>, future: F) -> Self {
+        Self {
+            semaphore: semaphore.map(PollSemaphore::new),
+            future,
+        }
+    }
+}
+
+impl<F: Future> Future for ConcurrencyGroupFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _permit = match this.semaphore {
+            Some(semaphore) => match semaphore.poll_acquire(cx) {
+                Poll::Ready(permit) => {
+                    Some(permit.expect("concurrency group semaphores are never closed"))
+                }
+                Poll::Pending => return Poll::Pending,
+            },
+            None => None,
+        };
+        this.future.poll(cx)
+    }
+}
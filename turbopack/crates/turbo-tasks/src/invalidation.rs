@@ -22,9 +22,15 @@ use crate::{
 /// Get an [`Invalidator`] that can be used to invalidate the current task
 /// based on external events.
 pub fn get_invalidator() -> Invalidator {
+    get_task_invalidator(current_task("turbo_tasks::get_invalidator()"))
+}
+
+/// Get an [`Invalidator`] that can be used to invalidate the given task based
+/// on external events.
+pub(crate) fn get_task_invalidator(task: TaskId) -> Invalidator {
     let handle = Handle::current();
     Invalidator {
-        task: current_task("turbo_tasks::get_invalidator()"),
+        task,
         turbo_tasks: with_turbo_tasks(Arc::downgrade),
         handle,
     }
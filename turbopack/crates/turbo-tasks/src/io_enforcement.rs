@@ -0,0 +1,96 @@
+//! An opt-in debug mode that checks that functions performing IO are marked with
+//! `#[turbo_tasks::function(fs)]` or `#[turbo_tasks::function(network)]`.
+//!
+//! In debug builds, the bodies of turbo-tasks functions run within a scope holding their
+//! [`IoMarkers`]. IO hooks (e.g. the filesystem operations of `turbo-tasks-fs` and the requests of
+//! `turbo-tasks-fetch`) call [`check_io`], which reports IO of a kind the running function isn't
+//! marked with.
+//!
+//! IO performed outside of a function body, e.g. in a spawned tokio task, isn't checked. Release
+//! builds don't check any IO.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use tokio::task_local;
+
+use crate::IoMarkers;
+
+/// How [`check_io`] reports IO within a function that isn't marked with the kind of the IO.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IoEnforcement {
+    /// IO isn't checked.
+    #[default]
+    Off = 0,
+    /// Logs a warning.
+    Log = 1,
+    /// Panics, which fails the task.
+    Panic = 2,
+}
+
+static IO_ENFORCEMENT: AtomicU8 = AtomicU8::new(IoEnforcement::Off as u8);
+
+/// Sets how unmarked IO is reported, for all turbo-tasks instances in the process.
+pub fn set_io_enforcement(enforcement: IoEnforcement) {
+    IO_ENFORCEMENT.store(enforcement as u8, Ordering::Relaxed);
+}
+
+/// The current [`IoEnforcement`] mode.
+pub fn io_enforcement() -> IoEnforcement {
+    match IO_ENFORCEMENT.load(Ordering::Relaxed) {
+        1 => IoEnforcement::Log,
+        2 => IoEnforcement::Panic,
+        _ => IoEnforcement::Off,
+    }
+}
+
+/// A kind of IO, matching one of the [`IoMarkers`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IoKind {
+    Filesystem,
+    Network,
+}
+
+impl IoKind {
+    /// The name of the function argument marking this kind of IO.
+    fn marker(self) -> &'static str {
+        match self {
+            IoKind::Filesystem => "fs",
+            IoKind::Network => "network",
+        }
+    }
+}
+
+task_local! {
+    /// The name and IO markers of the function whose body is currently running.
+    pub(crate) static CURRENT_IO_MARKERS: (&'static str, IoMarkers);
+}
+
+/// Reports IO of the given kind, described by `operation`, when it happens within the body of a
+/// function that isn't marked with that kind. Does nothing when [`IoEnforcement::Off`].
+pub fn check_io(kind: IoKind, operation: &str) {
+    let enforcement = io_enforcement();
+    if enforcement == IoEnforcement::Off {
+        return;
+    }
+    let Ok((name, markers)) = CURRENT_IO_MARKERS.try_with(|current| *current) else {
+        return;
+    };
+    let marked = match kind {
+        IoKind::Filesystem => markers.fs,
+        IoKind::Network => markers.network,
+    };
+    if marked {
+        return;
+    }
+    let marker = kind.marker();
+    match enforcement {
+        IoEnforcement::Off => {}
+        IoEnforcement::Log => tracing::warn!(
+            "{operation} in `{name}`, which isn't marked with `#[turbo_tasks::function({marker})]`"
+        ),
+        IoEnforcement::Panic => panic!(
+            "{operation} in `{name}`, which isn't marked with `#[turbo_tasks::function({marker})]`"
+        ),
+    }
+}
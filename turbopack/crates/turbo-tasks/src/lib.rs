@@ -39,6 +39,7 @@ pub mod backend;
 mod capture_future;
 mod collectibles;
 mod completion;
+mod concurrency_group;
 pub mod debug;
 mod display;
 pub mod duration_span;
@@ -48,6 +49,7 @@ pub mod graph;
 mod id;
 mod id_factory;
 mod invalidation;
+pub mod io_enforcement;
 mod join_iter_ext;
 #[doc(hidden)]
 pub mod macro_helpers;
@@ -59,6 +61,7 @@ mod once_map;
 mod output;
 pub mod persisted_graph;
 pub mod primitives;
+pub mod profile;
 mod raw_vc;
 mod rcstr;
 mod read_ref;
@@ -98,22 +101,22 @@ pub use manager::{
     turbo_tasks, CurrentCellRef, ReadConsistency, TaskPersistence, TurboTasks, TurboTasksApi,
     TurboTasksBackendApi, TurboTasksBackendApiExt, TurboTasksCallApi, Unused, UpdateInfo,
 };
-pub use native_function::{FunctionMeta, NativeFunction};
+pub use native_function::{FunctionMeta, IoMarkers, NativeFunction, TaskPriority};
 pub use output::OutputContent;
 pub use raw_vc::{CellId, RawVc, ReadRawVcFuture, ResolveTypeError};
 pub use read_ref::ReadRef;
 use rustc_hash::FxHasher;
 pub use serialization_invalidation::SerializationInvalidator;
 pub use state::{State, TransientState};
-pub use task::{task_input::TaskInput, SharedReference};
+pub use task::{downcast_task_error, task_input::TaskInput, SharedReference, TaskError};
 pub use trait_ref::{IntoTraitRef, TraitRef};
-pub use turbo_tasks_macros::{function, value, value_impl, value_trait, TaskInput};
+pub use turbo_tasks_macros::{function, test, value, value_impl, value_trait, TaskInput};
 pub use value::{TransientInstance, TransientValue, Value};
 pub use value_type::{TraitMethod, TraitType, ValueType};
 pub use vc::{
-    Dynamic, ResolvedValue, ResolvedVc, TypedForInput, Upcast, ValueDefault, Vc, VcCast,
-    VcCellNewMode, VcCellSharedMode, VcDefaultRead, VcRead, VcTransparentRead, VcValueTrait,
-    VcValueTraitCast, VcValueType, VcValueTypeCast,
+    Dynamic, OperationValue, OperationVc, ResolvedValue, ResolvedVc, TypedForInput, Upcast,
+    ValueDefault, Vc, VcCast, VcCellNewMode, VcCellSharedMode, VcDefaultRead, VcRead,
+    VcTransparentRead, VcValueTrait, VcValueTraitCast, VcValueType, VcValueTypeCast,
 };
 
 pub use crate::rcstr::RcStr;
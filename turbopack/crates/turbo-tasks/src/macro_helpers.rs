@@ -1,5 +1,8 @@
 //! Runtime helpers for [turbo-tasks-macro].
+use std::{future::Future, marker::PhantomData, time::Duration};
+
 pub use async_trait::async_trait;
+pub use futures;
 pub use once_cell::sync::{Lazy, OnceCell};
 pub use serde;
 pub use tracing;
@@ -9,8 +12,14 @@ pub use super::{
     manager::{find_cell_by_type, notify_scheduled_tasks, spawn_detached_for_testing},
 };
 use crate::{
-    debug::ValueDebugFormatString, task::TaskOutput, RawVc, ResolvedValue, TaskInput,
-    TaskPersistence, Vc,
+    debug::ValueDebugFormatString,
+    invalidation::get_task_invalidator,
+    io_enforcement::CURRENT_IO_MARKERS,
+    manager::{with_turbo_tasks, TurboTasksCallApi},
+    registry,
+    task::{FromTaskInput, TaskOutput},
+    FunctionId, Invalidator, IoMarkers, NativeFunction, OperationValue, RawVc, ResolvedValue,
+    TaskInput, TaskPersistence, Vc, VcValueType,
 };
 
 #[inline(never)]
@@ -50,6 +59,269 @@ where
 {
 }
 
+/// Checks that an argument of a `#[turbo_tasks::function(operation)]` can't be used to read a value
+/// without depending on the operation that created it.
+pub fn assert_argument_is_operation_value<Argument: OperationValue + ?Sized>() {}
+
+/// Checks that the `Self` type of a method taking `self` in a `#[turbo_tasks::value_impl]` is a
+/// `#[turbo_tasks::value]`, which is required for the call to be dispatched.
+pub fn assert_self_is_value_type<T>()
+where
+    T: VcValueType,
+{
+}
+
+/// Converts the arguments of a `#[turbo_tasks::function]` with [`FromTaskInput`] when the
+/// argument type implements it, and with an identity conversion otherwise.
+///
+/// This relies on inherent associated functions taking precedence over trait associated functions
+/// (autoref-based specialization). The [`AutoFromTaskInputIdentity`] trait must be in scope at the
+/// call site.
+pub struct AutoFromTaskInput<T>(PhantomData<T>);
+
+impl<T> AutoFromTaskInput<T>
+where
+    T: FromTaskInput,
+{
+    pub fn from_task_input(from: T::TaskInput) -> T {
+        T::from_task_input(from)
+    }
+}
+
+/// The fallback of [`AutoFromTaskInput`] for types that don't implement [`FromTaskInput`].
+pub trait AutoFromTaskInputIdentity<T> {
+    fn from_task_input(from: T) -> T {
+        from
+    }
+}
+
+impl<T> AutoFromTaskInputIdentity<T> for AutoFromTaskInput<T> {}
+
+/// Returns the given future unchanged. Pins down the output type of an `async` block, so that the
+/// `?` operator can be used inside of it.
+pub fn typed_future<T, F>(future: F) -> F
+where
+    F: Future<Output = T>,
+{
+    future
+}
+
+/// Like [`typed_future`], but also requires the future to be `Send`. Used by
+/// `#[turbo_tasks::function(assert_send)]` to report non-`Send` bodies at the function.
+pub fn assert_send_future<T, F>(future: F) -> F
+where
+    F: Future<Output = T> + Send,
+{
+    future
+}
+
+/// Constructs the future of a body only when it's first polled, and boxes it. Used by
+/// `#[turbo_tasks::function(lazy_body)]`, so that the future allocated when the task is scheduled
+/// only holds the inputs instead of the body's whole state.
+pub async fn lazy_body<T, B, F>(body: B) -> T
+where
+    B: FnOnce() -> F,
+    F: Future<Output = T>,
+{
+    Box::pin(body()).await
+}
+
+/// The entries of a `#[turbo_tasks(map_value)]` argument, sorted by key. The iteration order of a
+/// hash map is unspecified, so equal maps would be different task inputs otherwise.
+pub fn sorted_map_entries<K: Ord, V>(map: impl IntoIterator<Item = (K, V)>) -> Vec<(K, V)> {
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+}
+
+/// Applies the projection of a `#[turbo_tasks(key(...))]` argument to its task input. Passing a
+/// closure through this function lets its argument type be inferred.
+pub fn project_task_input_key<'a, T, K>(value: &'a T, key: impl FnOnce(&'a T) -> K) -> K {
+    key(value)
+}
+
+/// Runs the body of the function `name` with its [`IoMarkers`] in scope, so that
+/// [`check_io`][crate::io_enforcement::check_io] can report unmarked IO.
+pub async fn io_markers_scope<F: Future>(
+    name: &'static str,
+    markers: IoMarkers,
+    future: F,
+) -> F::Output {
+    CURRENT_IO_MARKERS.scope((name, markers), future).await
+}
+
+/// Like [`io_markers_scope`], for the body of a sync function.
+pub fn io_markers_sync_scope<T>(
+    name: &'static str,
+    markers: IoMarkers,
+    body: impl FnOnce() -> T,
+) -> T {
+    CURRENT_IO_MARKERS.sync_scope((name, markers), body)
+}
+
+/// Runs the body of a turbo-tasks function with its [`IoMarkers`] in scope in debug builds, see
+/// [`io_markers_scope`]. Release builds run the body as is.
+#[cfg(debug_assertions)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! io_markers_scope {
+    ($name:expr, $markers:expr, $output:ty, async $body:block) => {
+        $crate::macro_helpers::io_markers_scope(
+            $name,
+            $markers,
+            $crate::macro_helpers::typed_future::<$output, _>(async move $body),
+        )
+        .await
+    };
+    ($name:expr, $markers:expr, $output:ty, $body:block) => {
+        $crate::macro_helpers::io_markers_sync_scope($name, $markers, move || -> $output $body)
+    };
+}
+
+#[cfg(not(debug_assertions))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! io_markers_scope {
+    ($name:expr, $markers:expr, $output:ty, async $body:block) => {
+        $body
+    };
+    ($name:expr, $markers:expr, $output:ty, $body:block) => {
+        $body
+    };
+}
+
+/// Logs a warning if a function annotated with `#[turbo_tasks::function(warn_after = "...")]` took
+/// longer than its threshold. Both durations are in microseconds.
+pub fn warn_slow_execution(name: &'static str, duration: u64, threshold: u64) {
+    if duration > threshold {
+        tracing::warn!(
+            function = name,
+            duration,
+            threshold,
+            "{name} took {:?}, exceeding its warn_after threshold of {:?}",
+            Duration::from_micros(duration),
+            Duration::from_micros(threshold),
+        );
+    }
+}
+
+/// Returns an error if the turbo-tasks instance was stopped. Checked before each input is resolved
+/// in a resolver generated by `#[turbo_tasks::function(cancellable)]`, so that a cancelled task
+/// stops resolving its inputs promptly.
+pub fn bail_if_cancelled() -> anyhow::Result<()> {
+    if with_turbo_tasks(|tt| tt.is_stopped()) {
+        anyhow::bail!("the task was cancelled while resolving its inputs");
+    }
+    Ok(())
+}
+
+/// Downcasts the type-erased inputs of a function in a resolver generated by
+/// `#[turbo_tasks::function(parallel_inputs)]`.
+pub fn downcast_inputs<T: MagicAny>(inputs: &dyn MagicAny) -> &T {
+    let Some(downcast) = inputs.downcast_ref::<T>() else {
+        #[cfg(debug_assertions)]
+        panic!(
+            "Invalid inputs type, expected {} got {}",
+            std::any::type_name::<T>(),
+            inputs.magic_type_name()
+        );
+        #[cfg(not(debug_assertions))]
+        panic!("Invalid inputs type");
+    };
+    downcast
+}
+
+/// Implemented by `#[turbo_tasks::value_trait]` for `Box<dyn Trait>` and every type `T`
+/// implementing the trait's consts trait (e.g. `TraitConsts`), or every type if the trait has no
+/// associated consts.
+pub trait ValueTraitConsts<T: ?Sized> {}
+
+/// Asserts that `T` implements the consts trait of the value trait `Trait`. Used by
+/// `#[turbo_tasks::value_impl]`, which only implements the consts trait for impls declaring
+/// consts, so that an impl missing a required const doesn't compile. If all consts have defaults,
+/// an impl without consts needs an empty impl of the consts trait next to it.
+pub fn assert_value_trait_consts<Trait, T>()
+where
+    Trait: ValueTraitConsts<T> + ?Sized,
+    T: ?Sized,
+{
+}
+
+/// Gets an [`Invalidator`] for the task of calling `func` with `inputs`, creating the task if it
+/// doesn't exist yet. Used by the `<name>_invalidator()` functions generated for
+/// `#[turbo_tasks::function(invalidation = manual)]`.
+///
+/// The inputs must be resolved: the task called with unresolved inputs only resolves them, and
+/// invalidating it wouldn't re-execute the function. The generated functions resolve them first.
+pub fn task_invalidator(
+    func: FunctionId,
+    inputs: Box<dyn MagicAny>,
+    persistence: TaskPersistence,
+) -> Invalidator {
+    debug_assert!(
+        registry::get_function(func).arg_meta.is_resolved(&*inputs),
+        "the inputs of a task invalidator must be resolved"
+    );
+    let task = with_turbo_tasks(|tt| tt.native_call(func, inputs, persistence)).get_task_id();
+    get_task_invalidator(task)
+}
+
+/// The native functions of a `#[turbo_tasks::function(monomorphize = [...])]`, one per type the
+/// function is instantiated with. This takes the place of the function's native function, so that
+/// registering it registers every instantiation.
+pub struct MonomorphizedFunctions {
+    functions: &'static [&'static Lazy<NativeFunction>],
+    names: MonomorphizedNames,
+}
+
+impl MonomorphizedFunctions {
+    pub const fn new(
+        type_names: &'static [&'static str],
+        functions: &'static [&'static Lazy<NativeFunction>],
+    ) -> Self {
+        Self {
+            functions,
+            names: MonomorphizedNames::new(type_names),
+        }
+    }
+
+    /// Registers every instantiation under `global_name` suffixed with its type, e.g.
+    /// `my_crate::my_task<u32>`.
+    pub fn register(&'static self, global_name: &str) {
+        for (function, name) in self.functions.iter().zip(self.names.get(global_name)) {
+            Lazy::force(function).register(name);
+        }
+    }
+}
+
+/// The global names of the instantiations of a `monomorphize`d function or value type, e.g.
+/// `my_crate::MyValue<u32>`. They're built once, on the first registration, and live as long as
+/// the static holding them.
+pub struct MonomorphizedNames {
+    type_names: &'static [&'static str],
+    names: OnceCell<Box<[String]>>,
+}
+
+impl MonomorphizedNames {
+    pub const fn new(type_names: &'static [&'static str]) -> Self {
+        Self {
+            type_names,
+            names: OnceCell::new(),
+        }
+    }
+
+    /// The names in the order of the type names. `global_name` is the same on every call, as
+    /// it's the global name of the function or value type.
+    pub fn get(&'static self, global_name: &str) -> &'static [String] {
+        self.names.get_or_init(|| {
+            self.type_names
+                .iter()
+                .map(|type_name| format!("{global_name}<{type_name}>"))
+                .collect()
+        })
+    }
+}
+
 #[macro_export]
 macro_rules! stringify_path {
     ($path:path) => {
@@ -1,6 +1,7 @@
 use std::{
     any::Any,
     borrow::Cow,
+    collections::{hash_map::Entry, HashMap},
     future::Future,
     hash::BuildHasherDefault,
     mem::take,
@@ -19,7 +20,7 @@ use auto_hash_map::AutoMap;
 use futures::FutureExt;
 use rustc_hash::FxHasher;
 use serde::{Deserialize, Serialize};
-use tokio::{runtime::Handle, select, task_local};
+use tokio::{runtime::Handle, select, sync::Semaphore, task_local};
 use tokio_util::task::TaskTracker;
 use tracing::{info_span, instrument, trace_span, Instrument, Level};
 use turbo_tasks_malloc::TurboMalloc;
@@ -30,6 +31,7 @@ use crate::{
         TransientTaskType, TypedCellContent,
     },
     capture_future::{self, CaptureFuture},
+    concurrency_group::{ConcurrencyGroup, ConcurrencyGroupFuture},
     event::{Event, EventListener},
     id::{
         BackendJobId, ExecutionId, FunctionId, LocalCellId, LocalTaskId, TraitTypeId,
@@ -45,8 +47,8 @@ use crate::{
     trait_helpers::get_trait_method,
     util::StaticOrArc,
     vc::ReadVcFuture,
-    Completion, FunctionMeta, InvalidationReason, InvalidationReasonSet, SharedReference, TaskId,
-    TaskIdSet, ValueTypeId, Vc, VcRead, VcValueTrait, VcValueType,
+    Completion, FunctionMeta, InvalidationReason, InvalidationReasonSet, NativeFunction,
+    SharedReference, TaskId, TaskIdSet, ValueTypeId, Vc, VcRead, VcValueTrait, VcValueType,
 };
 
 pub trait TurboTasksCallApi: Sync + Send {
@@ -197,6 +199,12 @@ pub trait TurboTasksApi: TurboTasksCallApi + Sync + Send {
     ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
 
     fn stop_and_wait(&self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    /// Has [`TurboTasksApi::stop_and_wait`] been called? Tasks that are still running are
+    /// considered cancelled. Implementations that can't be stopped keep the default.
+    fn is_stopped(&self) -> bool {
+        false
+    }
 }
 
 /// A wrapper around a value that is unused.
@@ -368,6 +376,9 @@ pub struct TurboTasks<B: Backend + 'static> {
     currently_scheduled_foreground_jobs: AtomicUsize,
     currently_scheduled_background_jobs: AtomicUsize,
     scheduled_tasks: AtomicUsize,
+    /// Bounds the number of executing tasks of each concurrency group, see
+    /// [`FunctionMeta::concurrency_group`].
+    concurrency_groups: Mutex<HashMap<&'static str, ConcurrencyGroup>>,
     start: Mutex<Option<Instant>>,
     aggregated_update: Mutex<(Option<(Duration, usize)>, InvalidationReasonSet)>,
     event: Event,
@@ -482,6 +493,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
             currently_scheduled_background_jobs: AtomicUsize::new(0),
             currently_scheduled_foreground_jobs: AtomicUsize::new(0),
             scheduled_tasks: AtomicUsize::new(0),
+            concurrency_groups: Default::default(),
             start: Default::default(),
             aggregated_update: Default::default(),
             event: Event::new(|| "TurboTasks::event".to_string()),
@@ -564,30 +576,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
         arg: Box<dyn MagicAny>,
         persistence: TaskPersistence,
     ) -> RawVc {
-        let task_type = CachedTaskType::Native {
-            fn_type: func,
-            this: None,
-            arg,
-        };
-        match persistence {
-            TaskPersistence::LocalCells => {
-                todo!("bgw: local tasks");
-            }
-            TaskPersistence::Transient => {
-                RawVc::TaskOutput(self.backend.get_or_create_transient_task(
-                    task_type,
-                    current_task("turbo_function calls"),
-                    self,
-                ))
-            }
-            TaskPersistence::Persistent => {
-                RawVc::TaskOutput(self.backend.get_or_create_persistent_task(
-                    task_type,
-                    current_task("turbo_function calls"),
-                    self,
-                ))
-            }
-        }
+        self.native_fn_call(func, registry::get_function(func), None, arg, persistence)
     }
 
     pub(crate) fn this_call(
@@ -596,31 +585,63 @@ impl<B: Backend + 'static> TurboTasks<B> {
         this: RawVc,
         arg: Box<dyn MagicAny>,
         persistence: TaskPersistence,
+    ) -> RawVc {
+        self.native_fn_call(
+            func,
+            registry::get_function(func),
+            Some(this),
+            arg,
+            persistence,
+        )
+    }
+
+    /// Calls a function with resolved arguments. `native_fn` is the function registered for
+    /// `func`, so callers that already looked it up don't need to do so again.
+    fn native_fn_call(
+        &self,
+        func: FunctionId,
+        native_fn: &'static NativeFunction,
+        this: Option<RawVc>,
+        arg: Box<dyn MagicAny>,
+        persistence: TaskPersistence,
     ) -> RawVc {
         let task_type = CachedTaskType::Native {
             fn_type: func,
-            this: Some(this),
+            this,
             arg,
+            arg_hasher: native_fn.arg_meta.hasher(),
         };
-        match persistence {
+        let task_id = match persistence {
             TaskPersistence::LocalCells => {
                 todo!("bgw: local tasks");
             }
-            TaskPersistence::Transient => {
-                RawVc::TaskOutput(self.backend.get_or_create_transient_task(
-                    task_type,
-                    current_task("turbo_function calls"),
-                    self,
-                ))
-            }
-            TaskPersistence::Persistent => {
-                RawVc::TaskOutput(self.backend.get_or_create_persistent_task(
-                    task_type,
-                    current_task("turbo_function calls"),
-                    self,
-                ))
-            }
+            TaskPersistence::Transient => self.backend.get_or_create_transient_task(
+                task_type,
+                current_task("turbo_function calls"),
+                self,
+            ),
+            TaskPersistence::Persistent => self.backend.get_or_create_persistent_task(
+                task_type,
+                current_task("turbo_function calls"),
+                self,
+            ),
+        };
+        self.schedule_if_eager(&native_fn.function_meta, task_id);
+        RawVc::TaskOutput(task_id)
+    }
+
+    /// Starts executing the task of a function with `#[turbo_tasks::function(eager)]` right
+    /// away, instead of when its output is first read.
+    fn schedule_if_eager(&self, function_meta: &FunctionMeta, task_id: TaskId) {
+        if !function_meta.eager {
+            return;
         }
+        // Reading the output schedules the task if it's dirty. The output isn't awaited, so the
+        // listener (or the output, if it's already done) is dropped. An error here is the task's
+        // error, which is returned again to whoever reads the output, so it's not reported twice.
+        let _ =
+            self.backend
+                .try_read_task_output_untracked(task_id, ReadConsistency::Eventual, self);
     }
 
     pub fn dynamic_call(
@@ -630,8 +651,9 @@ impl<B: Backend + 'static> TurboTasks<B> {
         persistence: TaskPersistence,
     ) -> RawVc {
         // TODO(bgw): Don't create a full turbo task if this is a function using local_cells
-        if registry::get_function(func).arg_meta.is_resolved(&*arg) {
-            return self.native_call(func, arg, persistence);
+        let native_fn = registry::get_function(func);
+        if native_fn.arg_meta.is_resolved(&*arg) {
+            return self.native_fn_call(func, native_fn, None, arg, persistence);
         }
         match persistence {
             TaskPersistence::LocalCells => {
@@ -643,6 +665,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
                         fn_type: func,
                         this: None,
                         arg,
+                        arg_hasher: native_fn.arg_meta.hasher(),
                     },
                     current_task("turbo_function calls"),
                     self,
@@ -654,6 +677,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
                         fn_type: func,
                         this: None,
                         arg,
+                        arg_hasher: native_fn.arg_meta.hasher(),
                     },
                     current_task("turbo_function calls"),
                     self,
@@ -669,13 +693,15 @@ impl<B: Backend + 'static> TurboTasks<B> {
         arg: Box<dyn MagicAny>,
         persistence: TaskPersistence,
     ) -> RawVc {
-        if this.is_resolved() && registry::get_function(func).arg_meta.is_resolved(&*arg) {
-            return self.this_call(func, this, arg, persistence);
+        let native_fn = registry::get_function(func);
+        if this.is_resolved() && native_fn.arg_meta.is_resolved(&*arg) {
+            return self.native_fn_call(func, native_fn, Some(this), arg, persistence);
         }
         let task_type = CachedTaskType::ResolveNative {
             fn_type: func,
             this: Some(this),
             arg,
+            arg_hasher: native_fn.arg_meta.hasher(),
         };
         match persistence {
             TaskPersistence::LocalCells => {
@@ -756,6 +782,14 @@ impl<B: Backend + 'static> TurboTasks<B> {
         #[cfg(feature = "tokio_tracing")]
         let description = self.backend.get_task_description(task_id);
 
+        let function_meta = self
+            .backend
+            .try_get_function_id(task_id)
+            .map(|func_id| &get_function(func_id).function_meta);
+        let concurrency_group = function_meta
+            .and_then(|meta| meta.concurrency_group)
+            .map(|group| self.concurrency_group_semaphore(group));
+
         let this = self.pin();
         let future = async move {
             let mut schedule_again = true;
@@ -765,12 +799,8 @@ impl<B: Backend + 'static> TurboTasks<B> {
                     task_id,
                     Box::new(backend_state),
                 )));
-                let local_task_state = CurrentLocalTaskState::new(
-                    this.execution_id_factory.get(),
-                    this.backend
-                        .try_get_function_id(task_id)
-                        .map(|func_id| &get_function(func_id).function_meta),
-                );
+                let local_task_state =
+                    CurrentLocalTaskState::new(this.execution_id_factory.get(), function_meta);
                 let single_execution_future = async {
                     if this.stopped.load(Ordering::Acquire) {
                         return false;
@@ -783,6 +813,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
                     };
 
                     async {
+                        let future = ConcurrencyGroupFuture::new(concurrency_group.clone(), future);
                         let (result, duration, memory_usage) =
                             CaptureFuture::new(AssertUnwindSafe(future).catch_unwind()).await;
 
@@ -840,6 +871,30 @@ impl<B: Backend + 'static> TurboTasks<B> {
         tokio::task::spawn(future);
     }
 
+    /// Sets the number of tasks of a concurrency group (see
+    /// [`FunctionMeta::concurrency_group`]) that may execute at the same time. It defaults to the
+    /// available parallelism. Lowering the limit takes effect as the running tasks release their
+    /// permits, which requires a tokio runtime.
+    pub fn set_concurrency_group_limit(&self, group: &'static str, limit: usize) {
+        match self.concurrency_groups.lock().unwrap().entry(group) {
+            Entry::Occupied(mut entry) => entry.get_mut().set_limit(limit),
+            Entry::Vacant(entry) => {
+                entry.insert(ConcurrencyGroup::new(limit));
+            }
+        }
+    }
+
+    fn concurrency_group_semaphore(&self, group: &'static str) -> Arc<Semaphore> {
+        self.concurrency_groups
+            .lock()
+            .unwrap()
+            .entry(group)
+            .or_insert_with(|| {
+                ConcurrencyGroup::new(thread::available_parallelism().map_or(1, usize::from))
+            })
+            .semaphore()
+    }
+
     fn begin_primary_job(&self) {
         if self
             .currently_scheduled_tasks
@@ -1437,6 +1492,10 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
             this.stop_and_wait().await;
         })
     }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
 }
 
 impl<B: Backend + 'static> TurboTasksBackendApi<B> for TurboTasks<B> {
@@ -1972,6 +2031,10 @@ impl From<CurrentCellRef> for RawVc {
 }
 
 pub fn find_cell_by_type(ty: ValueTypeId) -> CurrentCellRef {
+    debug_assert!(
+        !is_readonly_task(),
+        "a task of a #[turbo_tasks::function(readonly)] must not create cells"
+    );
     CURRENT_GLOBAL_TASK_STATE.with(|ts| {
         let current_task = current_task("celling turbo_tasks values");
         let mut ts = ts.write().unwrap();
@@ -1990,6 +2053,13 @@ pub(crate) fn try_get_function_meta() -> Option<&'static FunctionMeta> {
     CURRENT_LOCAL_TASK_STATE.with(|ts| ts.function_meta)
 }
 
+/// Is the current task executing a `#[turbo_tasks::function(readonly)]`?
+fn is_readonly_task() -> bool {
+    CURRENT_LOCAL_TASK_STATE
+        .try_with(|ts| ts.function_meta.is_some_and(|meta| meta.readonly))
+        .unwrap_or(false)
+}
+
 pub(crate) fn create_local_cell(value: TypedSharedReference) -> (ExecutionId, LocalCellId) {
     let execution_id = CURRENT_LOCAL_TASK_STATE.with(|ts| ts.execution_id);
     let raw_local_cell_id = CURRENT_GLOBAL_TASK_STATE.with(|ts| {
@@ -1,4 +1,8 @@
-use std::{fmt::Debug, hash::Hash, pin::Pin};
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    pin::Pin,
+};
 
 use anyhow::{Context, Result};
 use futures::Future;
@@ -16,18 +20,65 @@ use crate::{
     RawVc, TaskInput,
 };
 
-type ResolveFunctor =
+/// Resolves a function's (type-erased) inputs. See [`ArgMeta::with_resolver`].
+pub type ResolveFunctor =
     for<'a> fn(
         &'a dyn MagicAny,
     ) -> Pin<Box<dyn Future<Output = Result<Box<dyn MagicAny>>> + Send + 'a>>;
 
+type IsTypeFunctor = fn(&dyn MagicAny) -> bool;
+
 type IsResolvedFunctor = fn(&dyn MagicAny) -> bool;
 
+type HashFunctor = fn(&dyn MagicAny, &mut dyn Hasher);
+
+/// How the arguments of a function are hashed as part of its task cache key, see
+/// [`ArgMeta::with_hasher`]. It's taken from the function's [`ArgMeta`] when a
+/// [`CachedTaskType`][crate::backend::CachedTaskType] is created, so hashing the task type
+/// doesn't need to look up the function.
+#[derive(Clone, Copy, Default)]
+pub struct ArgHasher(Option<HashFunctor>);
+
+impl ArgHasher {
+    pub fn hash<H: Hasher>(&self, value: &dyn MagicAny, state: &mut H) {
+        match self.0 {
+            Some(hash) => hash(value, state),
+            // the same as the derived `Hash` impl of a `Box<dyn MagicAny>`
+            None => value.hash(state),
+        }
+    }
+}
+
+impl Debug for ArgHasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() {
+            "CustomArgHasher"
+        } else {
+            "DefaultArgHasher"
+        })
+    }
+}
+
+/// The hasher is determined by the function, so it's the same for task types with equal function
+/// ids.
+impl PartialEq for ArgHasher {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.is_some() == other.0.is_some()
+    }
+}
+
+impl Eq for ArgHasher {}
+
 pub struct ArgMeta {
     serializer: MagicAnySerializeSeed,
     deserializer: MagicAnyDeserializeSeed,
+    #[cfg(debug_assertions)]
+    type_name: &'static str,
+    is_type: IsTypeFunctor,
+    names: &'static [&'static str],
     is_resolved: IsResolvedFunctor,
     resolve: ResolveFunctor,
+    hasher: ArgHasher,
 }
 
 impl ArgMeta {
@@ -35,36 +86,95 @@ impl ArgMeta {
     where
         T: TaskInput + Serialize + for<'de> Deserialize<'de> + 'static,
     {
-        fn downcast<T>(value: &dyn MagicAny) -> &T
+        fn downcast<T>(value: &dyn MagicAny) -> Result<&T>
         where
             T: MagicAny,
         {
-            value
-                .downcast_ref::<T>()
-                .with_context(|| {
-                    #[cfg(debug_assertions)]
-                    return format!(
-                        "Invalid argument type, expected {} got {}",
-                        std::any::type_name::<T>(),
-                        value.magic_type_name()
-                    );
-                    #[cfg(not(debug_assertions))]
-                    return "Invalid argument type";
-                })
-                .unwrap()
+            value.downcast_ref::<T>().with_context(|| {
+                #[cfg(debug_assertions)]
+                return format!(
+                    "Invalid argument type, expected {} got {}",
+                    std::any::type_name::<T>(),
+                    value.magic_type_name()
+                );
+                #[cfg(not(debug_assertions))]
+                return "Invalid argument type";
+            })
         }
         Self {
             serializer: MagicAnySerializeSeed::new::<T>(),
             deserializer: MagicAnyDeserializeSeed::new::<T>(),
-            is_resolved: |value| downcast::<T>(value).is_resolved(),
+            #[cfg(debug_assertions)]
+            type_name: std::any::type_name::<T>(),
+            is_type: |value| value.downcast_ref::<T>().is_some(),
+            names: &[],
+            // A value of the wrong type isn't resolved, so that `resolve` reports the error
+            is_resolved: |value| value.downcast_ref::<T>().is_some_and(T::is_resolved),
             resolve: |value| {
                 Box::pin(async {
-                    let value = downcast::<T>(value);
+                    let value = downcast::<T>(value)?;
                     let resolved = value.resolve().await?;
                     Ok(Box::new(resolved) as Box<dyn MagicAny>)
                 })
             },
+            hasher: ArgHasher::default(),
+        }
+    }
+
+    /// Hashes the arguments with a fresh `H` and feeds only the resulting digest into the task
+    /// cache key. Used by `#[turbo_tasks::function(hasher = ...)]`.
+    pub fn with_hasher<H>(mut self) -> Self
+    where
+        H: Hasher + Default,
+    {
+        let hash: HashFunctor = |value, state| {
+            let mut hasher = H::default();
+            value.magic_hash(&mut hasher);
+            state.write_u64(hasher.finish());
+        };
+        self.hasher = ArgHasher(Some(hash));
+        self
+    }
+
+    /// Replaces how the arguments are resolved. Used by
+    /// `#[turbo_tasks::function(parallel_inputs)]` to resolve `Vc` arguments concurrently.
+    pub fn with_resolver(mut self, resolve: ResolveFunctor) -> Self {
+        self.resolve = resolve;
+        self
+    }
+
+    /// Names the arguments (excluding `self`) in error messages about arguments of the wrong type.
+    pub fn with_names(mut self, names: &'static [&'static str]) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Checks that `value` has the expected argument type. The error names the first argument
+    /// that doesn't match, if it can be determined.
+    pub fn check(&self, value: &dyn MagicAny) -> Result<()> {
+        if (self.is_type)(value) {
+            return Ok(());
+        }
+        #[cfg(debug_assertions)]
+        {
+            let actual_type_name = value.magic_type_name();
+            if let Some((index, expected, actual)) =
+                mismatched_tuple_element(self.type_name, actual_type_name)
+            {
+                if let Some(name) = self.names.get(index) {
+                    anyhow::bail!(
+                        "Invalid type for argument `{name}`, expected {expected} got {actual}"
+                    );
+                }
+            }
+            anyhow::bail!(
+                "Invalid argument type, expected {} got {}",
+                self.type_name,
+                actual_type_name
+            );
         }
+        #[cfg(not(debug_assertions))]
+        anyhow::bail!("Invalid argument type");
     }
 
     pub fn deserialization_seed(&self) -> MagicAnyDeserializeSeed {
@@ -82,6 +192,91 @@ impl ArgMeta {
     pub async fn resolve(&self, value: &dyn MagicAny) -> Result<Box<dyn MagicAny>> {
         (self.resolve)(value).await
     }
+
+    pub fn hasher(&self) -> ArgHasher {
+        self.hasher
+    }
+}
+
+/// Splits the type names of two tuples into their elements and returns the index and type names
+/// of the first elements that differ.
+#[cfg(debug_assertions)]
+fn mismatched_tuple_element<'a>(
+    expected: &'a str,
+    actual: &'a str,
+) -> Option<(usize, &'a str, &'a str)> {
+    fn elements(tuple: &str) -> Option<Vec<&str>> {
+        let inner = tuple.strip_prefix('(')?.strip_suffix(')')?;
+        let mut elements = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (index, char) in inner.char_indices() {
+            match char {
+                '<' | '(' | '[' => depth += 1,
+                '>' | ')' | ']' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    elements.push(inner[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        let last = inner[start..].trim();
+        if !last.is_empty() {
+            elements.push(last);
+        }
+        Some(elements)
+    }
+
+    let expected = elements(expected)?;
+    let actual = elements(actual)?;
+    if expected.len() != actual.len() {
+        return None;
+    }
+    expected
+        .into_iter()
+        .zip(actual)
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+        .map(|(index, (expected, actual))| (index, expected, actual))
+}
+
+/// The kinds of IO that a function performs directly, set with `#[turbo_tasks::function(fs)]` and
+/// `#[turbo_tasks::function(network)]`. Functions that only perform IO transitively through other
+/// tasks aren't marked.
+///
+/// Unmarked IO can be reported at runtime with [`crate::io_enforcement`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IoMarkers {
+    pub fs: bool,
+    pub network: bool,
+}
+
+impl IoMarkers {
+    pub fn is_empty(&self) -> bool {
+        !self.fs && !self.network
+    }
+
+    /// A short description of the markers for tracing, e.g. `"fs,network"`, or `None` without
+    /// markers.
+    pub fn as_str(&self) -> Option<&'static str> {
+        match (self.fs, self.network) {
+            (false, false) => None,
+            (true, false) => Some("fs"),
+            (false, true) => Some("network"),
+            (true, true) => Some("fs,network"),
+        }
+    }
+}
+
+/// The scheduling priority of a function's tasks. Tasks are spawned in the order they're
+/// scheduled, so it's only recorded for now.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
 }
 
 #[derive(Debug)]
@@ -90,6 +285,32 @@ pub struct FunctionMeta {
     /// cached across task executions. Cells can be converted to their non-local
     /// versions by calling `Vc::resolve`.
     pub local_cells: bool,
+    /// A function whose tasks should preferably be scheduled before this function's tasks. This
+    /// is a soft ordering hint that is only recorded for now.
+    pub schedule_after: Option<fn() -> &'static NativeFunction>,
+    /// The version of the function's logic, set with `#[turbo_tasks::function(version = N)]`. A
+    /// non-zero version is part of the function's global name, so results persisted by other
+    /// versions aren't reused.
+    pub version: u32,
+    /// Whether the function is a pure reader, set with `#[turbo_tasks::function(readonly)]`. Its
+    /// tasks must not create cells, which is asserted in debug builds.
+    pub readonly: bool,
+    /// The name of a concurrency group shared with other functions, set with
+    /// `#[turbo_tasks::function(concurrency_group = "name")]`. The scheduler bounds the number of
+    /// executing tasks of all functions in a group, see `TurboTasks::set_concurrency_group_limit`.
+    /// A task only holds on to its slot while it's polled, not while it waits for other tasks, so
+    /// functions of a group can wait for tasks of the same group.
+    pub concurrency_group: Option<&'static str>,
+    /// Whether the function's tasks start executing as soon as they're created, set with
+    /// `#[turbo_tasks::function(eager)]`. Other tasks only execute once their output is read (or
+    /// they're connected to an active parent).
+    pub eager: bool,
+    /// The scheduling priority of the function's tasks, set with
+    /// `#[turbo_tasks::function(priority = high)]` or `priority = low`.
+    pub priority: TaskPriority,
+    /// The kinds of IO that the function performs directly. They're recorded as the `io` field of
+    /// the span of the function's executions.
+    pub io_markers: IoMarkers,
 }
 
 /// A native (rust) turbo-tasks function. It's used internally by
@@ -158,6 +379,11 @@ impl NativeFunction {
 
     /// Executed the function
     pub fn execute(&'static self, this: Option<RawVc>, arg: &dyn MagicAny) -> NativeTaskFuture {
+        // The implementation rejects arguments of the wrong type too, this only names them
+        #[cfg(debug_assertions)]
+        if let Err(err) = self.arg_meta.check(arg) {
+            return Box::pin(async { Err(err) });
+        }
         match (self.implementation).functor(this, arg) {
             Ok(functor) => functor,
             Err(err) => Box::pin(async { Err(err) }),
@@ -165,7 +391,12 @@ impl NativeFunction {
     }
 
     pub fn span(&'static self) -> Span {
-        tracing::trace_span!("turbo_tasks::function", name = self.name.as_str())
+        match self.function_meta.io_markers.as_str() {
+            Some(io) => {
+                tracing::trace_span!("turbo_tasks::function", name = self.name.as_str(), io)
+            }
+            None => tracing::trace_span!("turbo_tasks::function", name = self.name.as_str()),
+        }
     }
 
     pub fn resolve_span(&'static self) -> Span {
@@ -175,6 +406,28 @@ impl NativeFunction {
     pub fn register(&'static self, global_name: &'static str) {
         register_function(global_name, self);
     }
+
+    /// Hashes this function's arguments with `H` when computing task cache keys. See
+    /// [`ArgMeta::with_hasher`].
+    pub fn with_arg_hasher<H>(mut self) -> Self
+    where
+        H: Hasher + Default,
+    {
+        self.arg_meta = self.arg_meta.with_hasher::<H>();
+        self
+    }
+
+    /// Names this function's arguments in errors. See [`ArgMeta::with_names`].
+    pub fn with_arg_names(mut self, names: &'static [&'static str]) -> Self {
+        self.arg_meta = self.arg_meta.with_names(names);
+        self
+    }
+
+    /// Resolves this function's arguments with `resolve`. See [`ArgMeta::with_resolver`].
+    pub fn with_arg_resolver(mut self, resolve: ResolveFunctor) -> Self {
+        self.arg_meta = self.arg_meta.with_resolver(resolve);
+        self
+    }
 }
 
 impl PartialEq for &'static NativeFunction {
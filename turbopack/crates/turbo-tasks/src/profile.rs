@@ -0,0 +1,34 @@
+//! Timing hooks for functions annotated with `#[turbo_tasks::function(profile)]`.
+
+use once_cell::sync::OnceCell;
+
+/// A part of a profiled function call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProfilePhase {
+    /// Resolving the function's inputs. Only measured for calls with unresolved inputs.
+    ResolveInputs,
+    /// Executing the function's body.
+    Body,
+}
+
+/// Receives the metric name of the measured phase, the phase itself and its duration in
+/// microseconds. The metric name is the path of the profiled function followed by the phase, e.g.
+/// `my_crate::my_function::resolve_inputs` or `my_crate::my_function::body`, so the overhead of
+/// resolving the inputs can be told apart from the computation.
+pub type ProfileHook = fn(&'static str, ProfilePhase, u64);
+
+static PROFILE_HOOK: OnceCell<ProfileHook> = OnceCell::new();
+
+/// Sets the hook that receives the timings of profiled functions. The hook can only be set once,
+/// so this returns the given hook as an error if one was already set.
+pub fn set_profile_hook(hook: ProfileHook) -> Result<(), ProfileHook> {
+    PROFILE_HOOK.set(hook)
+}
+
+/// Reports a timing to the profile hook, if one is set. Called by the code generated for profiled
+/// functions.
+pub fn report(metric: &'static str, phase: ProfilePhase, duration: u64) {
+    if let Some(hook) = PROFILE_HOOK.get() {
+        hook(metric, phase, duration);
+    }
+}
@@ -67,6 +67,13 @@ fn get_thing_id<
 }
 
 pub fn register_function(global_name: &'static str, func: &'static NativeFunction) {
+    // The global name identifies the function in persisted task types, so including the version
+    // makes results of other versions stale.
+    let global_name: &'static str = match func.function_meta.version {
+        0 => global_name,
+        _ if FUNCTIONS_BY_VALUE.contains_key(func) => return,
+        version => Box::leak(format!("{global_name}@v{version}").into_boxed_str()),
+    };
     register_thing(
         global_name,
         func,
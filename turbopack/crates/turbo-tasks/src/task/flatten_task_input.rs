@@ -0,0 +1,18 @@
+use crate::TaskInput;
+
+/// Trait to implement in order for a type to be accepted as a
+/// `#[turbo_tasks(flatten)]` argument of a
+/// [`#[turbo_tasks::function]`][crate::function].
+///
+/// The fields of a flattened argument are passed as separate task inputs, so the type itself
+/// doesn't need to implement [`TaskInput`]. This is usually implemented with
+/// [`#[derive(FlattenTaskInput)]`][macro@crate::task::FlattenTaskInput].
+pub trait FlattenTaskInput: Sized {
+    /// A tuple of the fields.
+    type Fields: TaskInput;
+
+    fn into_fields(self) -> Self::Fields;
+    fn from_fields(fields: Self::Fields) -> Self;
+}
+
+pub use turbo_tasks_macros::FlattenTaskInput;
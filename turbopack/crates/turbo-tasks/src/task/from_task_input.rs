@@ -0,0 +1,231 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    num::{NonZeroU32, NonZeroU64, NonZeroUsize},
+    sync::Arc,
+};
+
+use crate::{
+    RcStr, ResolvedVc, TaskId, TaskInput, TransientInstance, TransientValue, Value, ValueTypeId, Vc,
+};
+
+/// Converts an argument of a [`#[turbo_tasks::function]`][crate::function] from the type that's
+/// stored as the task's input back into the type declared on the original function.
+///
+/// The exposed function generated by the macro accepts the stored type (e.g. `Vc<T>` for a
+/// `ResolvedVc<T>` argument), so callers don't have to resolve their inputs. The framework
+/// resolves all inputs before the task executes, so these conversions are cheap.
+///
+/// Types that don't need a conversion fall back to the identity conversion in the macro, see
+/// [`AutoFromTaskInput`][crate::macro_helpers::AutoFromTaskInput].
+pub trait FromTaskInput: Sized {
+    type TaskInput: TaskInput;
+
+    fn from_task_input(from: Self::TaskInput) -> Self;
+}
+
+macro_rules! impl_identity_from_task_input {
+    ($($t:ty),*) => {
+        $(
+            impl FromTaskInput for $t {
+                type TaskInput = $t;
+
+                fn from_task_input(from: Self::TaskInput) -> Self {
+                    from
+                }
+            }
+        )*
+    };
+}
+
+impl_identity_from_task_input! {
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    i32,
+    u64,
+    usize,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroUsize,
+    RcStr,
+    TaskId,
+    ValueTypeId,
+    Box<str>,
+    Box<[u8]>,
+    Arc<str>,
+    Cow<'static, str>
+}
+
+impl<T> FromTaskInput for Vc<T>
+where
+    T: Send,
+{
+    type TaskInput = Vc<T>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from
+    }
+}
+
+impl<T> FromTaskInput for ResolvedVc<T>
+where
+    T: Send,
+{
+    type TaskInput = Vc<T>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        debug_assert!(
+            from.is_resolved(),
+            "inputs are resolved before the task function is called"
+        );
+        ResolvedVc { node: from }
+    }
+}
+
+impl<T> FromTaskInput for Value<T>
+where
+    Value<T>: TaskInput,
+{
+    type TaskInput = Value<T>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from
+    }
+}
+
+impl<T> FromTaskInput for TransientValue<T>
+where
+    TransientValue<T>: TaskInput,
+{
+    type TaskInput = TransientValue<T>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from
+    }
+}
+
+impl<T> FromTaskInput for TransientInstance<T>
+where
+    TransientInstance<T>: TaskInput,
+{
+    type TaskInput = TransientInstance<T>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from
+    }
+}
+
+impl<T> FromTaskInput for Vec<T>
+where
+    T: FromTaskInput,
+{
+    type TaskInput = Vec<T::TaskInput>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from.into_iter().map(T::from_task_input).collect()
+    }
+}
+
+impl<T, const N: usize> FromTaskInput for [T; N]
+where
+    T: FromTaskInput,
+{
+    type TaskInput = [T::TaskInput; N];
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from.map(T::from_task_input)
+    }
+}
+
+impl<T> FromTaskInput for Box<T>
+where
+    T: FromTaskInput,
+{
+    type TaskInput = Box<T::TaskInput>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        Box::new(T::from_task_input(*from))
+    }
+}
+
+/// The contents are only cloned if they're still shared with the task's input. Arguments that
+/// don't contain a `ResolvedVc` are passed to the body as-is by the macro, without a conversion.
+impl<T> FromTaskInput for Arc<T>
+where
+    T: FromTaskInput,
+{
+    type TaskInput = Arc<T::TaskInput>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        Arc::new(T::from_task_input(Arc::unwrap_or_clone(from)))
+    }
+}
+
+impl<T> FromTaskInput for Arc<[T]>
+where
+    T: FromTaskInput,
+{
+    type TaskInput = Arc<[T::TaskInput]>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from.iter().cloned().map(T::from_task_input).collect()
+    }
+}
+
+impl<T> FromTaskInput for Option<T>
+where
+    T: FromTaskInput,
+{
+    type TaskInput = Option<T::TaskInput>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from.map(T::from_task_input)
+    }
+}
+
+/// Only the values are converted, the keys must be task inputs on their own, as they determine
+/// the order of the entries.
+impl<K, V> FromTaskInput for BTreeMap<K, V>
+where
+    K: TaskInput + Ord,
+    V: FromTaskInput,
+{
+    type TaskInput = BTreeMap<K, V::TaskInput>;
+
+    fn from_task_input(from: Self::TaskInput) -> Self {
+        from.into_iter()
+            .map(|(key, value)| (key, V::from_task_input(value)))
+            .collect()
+    }
+}
+
+macro_rules! tuple_impls {
+    ( $( $name:ident )+ ) => {
+        impl<$($name: FromTaskInput),+> FromTaskInput for ($($name,)+) {
+            type TaskInput = ($($name::TaskInput,)+);
+
+            #[allow(non_snake_case)]
+            fn from_task_input(from: Self::TaskInput) -> Self {
+                let ($($name,)+) = from;
+                ($($name::from_task_input($name),)+)
+            }
+        }
+    };
+}
+
+// Implement `FromTaskInput` for all tuples of 1 to 12 elements, matching `TaskInput`.
+tuple_impls! { A }
+tuple_impls! { A B }
+tuple_impls! { A B C }
+tuple_impls! { A B C D }
+tuple_impls! { A B C D E }
+tuple_impls! { A B C D E F }
+tuple_impls! { A B C D E F G }
+tuple_impls! { A B C D E F G H }
+tuple_impls! { A B C D E F G H I }
+tuple_impls! { A B C D E F G H I J }
+tuple_impls! { A B C D E F G H I J K }
+tuple_impls! { A B C D E F G H I J K L }
@@ -25,8 +25,8 @@ use std::{future::Future, marker::PhantomData, pin::Pin};
 
 use anyhow::Result;
 
-use super::{TaskInput, TaskOutput};
-use crate::{magic_any::MagicAny, RawVc, Vc, VcRead, VcValueType};
+use super::{FromTaskInput, TaskInput, TaskOutput};
+use crate::{magic_any::MagicAny, RawVc, ResolvedVc, Vc, VcRead, VcValueType};
 
 pub type NativeTaskFuture = Pin<Box<dyn Future<Output = Result<RawVc>> + Send>>;
 
@@ -157,6 +157,12 @@ impl TaskFnMode for MethodMode {}
 pub struct AsyncMethodMode;
 impl TaskFnMode for AsyncMethodMode {}
 
+pub struct ResolvedFunctionMode;
+impl TaskFnMode for ResolvedFunctionMode {}
+
+pub struct AsyncResolvedFunctionMode;
+impl TaskFnMode for AsyncResolvedFunctionMode {}
+
 macro_rules! task_inputs_impl {
     ( $( $arg:ident )* ) => {
         impl<$($arg,)*> TaskInputs for ($($arg,)*)
@@ -273,6 +279,29 @@ macro_rules! task_fn_impl {
             }
         }
 
+        impl<F, Output, Recv, $($arg,)*> TaskFnInputFunctionWithThis<ResolvedFunctionMode, Recv, ($($arg,)*)> for F
+        where
+            Recv: Sync + Send + 'static,
+            $($arg: TaskInput + 'static,)*
+            F: Fn(ResolvedVc<Recv>, $($arg,)*) -> Output + Send + Sync + Clone + 'static,
+            Output: TaskOutput + 'static,
+        {
+            #[allow(non_snake_case)]
+            fn functor(&self, this: RawVc, arg: &dyn MagicAny) -> Result<NativeTaskFuture> {
+                let task_fn = self.clone();
+                let recv = ResolvedVc::<Recv>::from_task_input(Vc::<Recv>::from(this));
+
+                let ($($arg,)*) = get_args::<($($arg,)*)>(arg)?;
+                $(
+                    let $arg = $arg.clone();
+                )*
+
+                Ok(Box::pin(async move {
+                    Output::try_into_raw_vc((task_fn)(recv, $($arg,)*))
+                }))
+            }
+        }
+
         pub trait $async_fn_trait<A0, $($arg,)*>: Fn(A0, $($arg,)*) -> Self::OutputFuture {
             type OutputFuture: Future<Output = <Self as $async_fn_trait<A0, $($arg,)*>>::Output> + Send;
             type Output: TaskOutput;
@@ -333,6 +362,28 @@ macro_rules! task_fn_impl {
                 }))
             }
         }
+
+        impl<F, Recv, $($arg,)*> TaskFnInputFunctionWithThis<AsyncResolvedFunctionMode, Recv, ($($arg,)*)> for F
+        where
+            Recv: Sync + Send + 'static,
+            $($arg: TaskInput + 'static,)*
+            F: $async_fn_trait<ResolvedVc<Recv>, $($arg,)*> + Clone + Send + Sync + 'static,
+        {
+            #[allow(non_snake_case)]
+            fn functor(&self, this: RawVc, arg: &dyn MagicAny) -> Result<NativeTaskFuture> {
+                let task_fn = self.clone();
+                let recv = ResolvedVc::<Recv>::from_task_input(Vc::<Recv>::from(this));
+
+                let ($($arg,)*) = get_args::<($($arg,)*)>(arg)?;
+                $(
+                    let $arg = $arg.clone();
+                )*
+
+                Ok(Box::pin(async move {
+                    <F as $async_fn_trait<ResolvedVc<Recv>, $($arg,)*>>::Output::try_into_raw_vc((task_fn)(recv, $($arg,)*).await)
+                }))
+            }
+        }
     };
 }
 
@@ -406,6 +457,17 @@ mod tests {
             todo!()
         }
 
+        fn with_resolved_recv(_a: ResolvedVc<i32>) -> crate::Vc<i32> {
+            todo!()
+        }
+
+        async fn async_with_resolved_recv_and_str(
+            _a: ResolvedVc<i32>,
+            _s: RcStr,
+        ) -> crate::Vc<i32> {
+            todo!()
+        }
+
         fn accepts_task_fn<F>(_task_fn: F)
         where
             F: TaskFn,
@@ -478,6 +540,10 @@ mod tests {
         accepts_task_fn(task_fn);
         let task_fn = Struct::inherent_method.into_task_fn_with_this();
         accepts_task_fn(task_fn);
+        let task_fn = with_resolved_recv.into_task_fn_with_this();
+        accepts_task_fn(task_fn);
+        let task_fn = async_with_resolved_recv_and_str.into_task_fn_with_this();
+        accepts_task_fn(task_fn);
 
         /*
         let task_fn = <Struct as BoxAsyncTrait>::box_async_method.into_task_fn();
@@ -1,9 +1,16 @@
+pub(crate) mod flatten_task_input;
+pub(crate) mod from_task_input;
 pub(crate) mod function;
 pub(crate) mod shared_reference;
 pub(crate) mod task_input;
 pub(crate) mod task_output;
 
+pub use flatten_task_input::FlattenTaskInput;
+pub use from_task_input::FromTaskInput;
 pub use function::{AsyncFunctionMode, FunctionMode, IntoTaskFn, TaskFn};
 pub use shared_reference::SharedReference;
-pub use task_input::TaskInput;
-pub use task_output::TaskOutput;
+pub use task_input::{
+    CowStrTaskInput, DebugSkippedTaskInput, KeyedTaskInput, TaskInput, TaskInputKey,
+    UntrackedTaskInput,
+};
+pub use task_output::{downcast_task_error, TaskError, TaskOutput};
@@ -1,10 +1,21 @@
-use std::{any::Any, fmt::Debug, future::Future, hash::Hash};
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::Debug,
+    future::Future,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    num::{NonZeroU32, NonZeroU64, NonZeroUsize},
+    sync::Arc,
+};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    MagicAny, RcStr, ResolvedVc, TaskId, TransientInstance, TransientValue, Value, ValueTypeId, Vc,
+    MagicAny, OperationVc, RcStr, ResolvedVc, TaskId, TransientInstance, TransientValue, Value,
+    ValueTypeId, Vc,
 };
 
 /// Trait to implement in order for a type to be accepted as a
@@ -40,9 +51,252 @@ impl_task_input! {
     i32,
     u64,
     usize,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroUsize,
     RcStr,
     TaskId,
-    ValueTypeId
+    ValueTypeId,
+    Box<str>,
+    Box<[u8]>,
+    Arc<str>,
+    Cow<'static, str>,
+    CowStrTaskInput
+}
+
+/// The task input of a `Cow<'static, str>` argument of a
+/// [`#[turbo_tasks::function]`][crate::function].
+///
+/// Task inputs are cloned for every execution of the task. Borrowed strings are kept as-is and
+/// owned strings are shared as an [`RcStr`], so cloning never copies the string. Deserialized
+/// inputs are always [`CowStrTaskInput::Owned`].
+#[derive(Clone, Debug)]
+pub enum CowStrTaskInput {
+    Borrowed(&'static str),
+    Owned(RcStr),
+}
+
+impl CowStrTaskInput {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CowStrTaskInput::Borrowed(value) => value,
+            CowStrTaskInput::Owned(value) => value.as_str(),
+        }
+    }
+}
+
+impl From<Cow<'static, str>> for CowStrTaskInput {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(value) => CowStrTaskInput::Borrowed(value),
+            Cow::Owned(value) => CowStrTaskInput::Owned(value.into()),
+        }
+    }
+}
+
+impl From<CowStrTaskInput> for Cow<'static, str> {
+    fn from(value: CowStrTaskInput) -> Self {
+        match value {
+            CowStrTaskInput::Borrowed(value) => Cow::Borrowed(value),
+            CowStrTaskInput::Owned(value) => Cow::Owned(value.into_owned()),
+        }
+    }
+}
+
+// Borrowed and owned inputs with the same contents are the same task.
+impl PartialEq for CowStrTaskInput {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for CowStrTaskInput {}
+
+impl Hash for CowStrTaskInput {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl Serialize for CowStrTaskInput {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CowStrTaskInput {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(CowStrTaskInput::Owned(RcStr::deserialize(deserializer)?))
+    }
+}
+
+/// The task input of an `#[turbo_tasks(untracked)]` argument of a
+/// [`#[turbo_tasks::function]`][crate::function].
+///
+/// It's not part of the task's cache key: all values are equal and hash the same. Calls that only
+/// differ in an untracked argument share a task, which is executed with the value of the first
+/// call.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UntrackedTaskInput<T>(pub T);
+
+impl<T> PartialEq for UntrackedTaskInput<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for UntrackedTaskInput<T> {}
+
+impl<T> Hash for UntrackedTaskInput<T> {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl<T> Debug for UntrackedTaskInput<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> TaskInput for UntrackedTaskInput<T>
+where
+    T: TaskInput,
+{
+    fn is_resolved(&self) -> bool {
+        self.0.is_resolved()
+    }
+
+    fn is_transient(&self) -> bool {
+        self.0.is_transient()
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        Ok(UntrackedTaskInput(self.0.resolve().await?))
+    }
+}
+
+/// The task input of a `#[turbo_tasks(debug_skip)]` argument of a
+/// [`#[turbo_tasks::function]`][crate::function].
+///
+/// It's formatted as `..` by its `Debug` impl, e.g. for arguments that are large or contain
+/// secrets. It's part of the task's cache key like any other input.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DebugSkippedTaskInput<T>(pub T);
+
+impl<T> Debug for DebugSkippedTaskInput<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("..")
+    }
+}
+
+impl<T> TaskInput for DebugSkippedTaskInput<T>
+where
+    T: TaskInput,
+{
+    fn is_resolved(&self) -> bool {
+        self.0.is_resolved()
+    }
+
+    fn is_transient(&self) -> bool {
+        self.0.is_transient()
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        Ok(DebugSkippedTaskInput(self.0.resolve().await?))
+    }
+}
+
+/// Projects the task input of a `#[turbo_tasks(key(...))]` argument to the part of it that's
+/// relevant for the task's cache key. It's implemented by a marker type that
+/// [`#[turbo_tasks::function]`][crate::function] generates for each such argument.
+pub trait TaskInputKey<T> {
+    fn key(value: &T) -> impl Hash + Eq;
+}
+
+/// The task input of a `#[turbo_tasks(key(...))]` argument of a
+/// [`#[turbo_tasks::function]`][crate::function].
+///
+/// Only the projection `K` of the value is part of the task's cache key: values with equal
+/// projections are equal and hash the same. Calls that only differ in the rest of the value share a
+/// task, which is executed with the value of the first call. The whole value is serialized, and the
+/// projection is computed again when it's deserialized.
+#[derive(Serialize, Deserialize)]
+#[serde(
+    transparent,
+    bound(serialize = "T: Serialize", deserialize = "T: Deserialize<'de>")
+)]
+pub struct KeyedTaskInput<T, K>(pub T, #[serde(skip)] PhantomData<K>);
+
+impl<T, K> KeyedTaskInput<T, K> {
+    pub fn new(value: T) -> Self {
+        KeyedTaskInput(value, PhantomData)
+    }
+}
+
+impl<T, K> Clone for KeyedTaskInput<T, K>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        KeyedTaskInput::new(self.0.clone())
+    }
+}
+
+impl<T, K> PartialEq for KeyedTaskInput<T, K>
+where
+    K: TaskInputKey<T>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        K::key(&self.0) == K::key(&other.0)
+    }
+}
+
+impl<T, K> Eq for KeyedTaskInput<T, K> where K: TaskInputKey<T> {}
+
+impl<T, K> Hash for KeyedTaskInput<T, K>
+where
+    K: TaskInputKey<T>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        K::key(&self.0).hash(state)
+    }
+}
+
+impl<T, K> Debug for KeyedTaskInput<T, K>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T, K> TaskInput for KeyedTaskInput<T, K>
+where
+    T: TaskInput,
+    K: TaskInputKey<T> + Send + Sync,
+{
+    fn is_resolved(&self) -> bool {
+        self.0.is_resolved()
+    }
+
+    fn is_transient(&self) -> bool {
+        self.0.is_transient()
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        Ok(KeyedTaskInput::new(self.0.resolve().await?))
+    }
 }
 
 impl<T> TaskInput for Vec<T>
@@ -66,6 +320,91 @@ where
     }
 }
 
+impl<T, const N: usize> TaskInput for [T; N]
+where
+    T: TaskInput,
+{
+    fn is_resolved(&self) -> bool {
+        self.iter().all(TaskInput::is_resolved)
+    }
+
+    fn is_transient(&self) -> bool {
+        self.iter().any(TaskInput::is_transient)
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        let mut resolved = Vec::with_capacity(N);
+        for value in self {
+            resolved.push(value.resolve().await?);
+        }
+        Ok(resolved
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("an array of {N} elements was resolved")))
+    }
+}
+
+impl<T> TaskInput for Box<T>
+where
+    T: TaskInput,
+{
+    fn is_resolved(&self) -> bool {
+        (**self).is_resolved()
+    }
+
+    fn is_transient(&self) -> bool {
+        (**self).is_transient()
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        Ok(Box::new((**self).resolve().await?))
+    }
+}
+
+/// An `Arc` is hashed and compared by its contents, so tasks called with equal contents are
+/// deduplicated. Cloning the input only bumps the reference count, which makes it suited for large
+/// shared values like configurations.
+impl<T> TaskInput for Arc<T>
+where
+    T: TaskInput,
+{
+    fn is_resolved(&self) -> bool {
+        (**self).is_resolved()
+    }
+
+    fn is_transient(&self) -> bool {
+        (**self).is_transient()
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        if self.is_resolved() {
+            // keeps sharing the contents
+            return Ok(self.clone());
+        }
+        Ok(Arc::new((**self).resolve().await?))
+    }
+}
+
+impl<T> TaskInput for Arc<[T]>
+where
+    T: TaskInput,
+{
+    fn is_resolved(&self) -> bool {
+        self.iter().all(TaskInput::is_resolved)
+    }
+
+    fn is_transient(&self) -> bool {
+        self.iter().any(TaskInput::is_transient)
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        let mut resolved = Vec::with_capacity(self.len());
+        for value in self.iter() {
+            resolved.push(value.resolve().await?);
+        }
+        Ok(resolved.into())
+    }
+}
+
 impl<T> TaskInput for Option<T>
 where
     T: TaskInput,
@@ -92,6 +431,30 @@ where
     }
 }
 
+impl<K, V> TaskInput for BTreeMap<K, V>
+where
+    K: TaskInput + Ord,
+    V: TaskInput,
+{
+    fn is_resolved(&self) -> bool {
+        self.iter()
+            .all(|(key, value)| key.is_resolved() && value.is_resolved())
+    }
+
+    fn is_transient(&self) -> bool {
+        self.iter()
+            .any(|(key, value)| key.is_transient() || value.is_transient())
+    }
+
+    async fn resolve(&self) -> Result<Self> {
+        let mut resolved = BTreeMap::new();
+        for (key, value) in self {
+            resolved.insert(key.resolve().await?, value.resolve().await?);
+        }
+        Ok(resolved)
+    }
+}
+
 impl<T> TaskInput for Vc<T>
 where
     T: Send,
@@ -122,6 +485,17 @@ where
     }
 }
 
+// The output of an operation is never resolved, resolving it would lose track of the operation that
+// created it.
+impl<T> TaskInput for OperationVc<T>
+where
+    T: Send,
+{
+    fn is_transient(&self) -> bool {
+        self.node.node.get_task_id().is_transient()
+    }
+}
+
 impl<T> TaskInput for Value<T>
 where
     T: Any
@@ -330,6 +704,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_generic_variants() -> Result<()> {
+        trait Label {}
+        impl Label for u32 {}
+
+        #[derive(Clone, TaskInput, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+        enum GenericVariants<T: Label, U>
+        where
+            U: Default,
+        {
+            Labeled(T),
+            Many(Vec<U>),
+            Dynamic(Vc<Box<dyn crate::debug::ValueDebug>>),
+        }
+
+        assert_task_input(GenericVariants::<u32, RcStr>::Labeled(42));
+        assert_task_input(GenericVariants::<u32, RcStr>::Many(vec!["42".into()]));
+        Ok(())
+    }
+
     #[derive(Clone, TaskInput, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
     enum OneVariant {
         Variant,
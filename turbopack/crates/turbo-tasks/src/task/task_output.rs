@@ -1,8 +1,33 @@
-use std::marker::PhantomData;
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
 
 use anyhow::Result;
 
-use crate::{RawVc, Vc};
+use crate::{util::SharedError, RawVc, Vc};
+
+/// The error of a [`#[turbo_tasks::function]`][crate::function] that returns a `Result<Vc<T>, E>`.
+/// Any `E: Into<TaskError>` is accepted, which includes custom error types that implement
+/// [`std::error::Error`].
+///
+/// The error is passed on to the readers of the task as-is, so callers can match on a typed error
+/// with [`downcast_task_error`].
+pub type TaskError = anyhow::Error;
+
+/// Finds an error of type `E` that was returned by a task, either directly or by a task that it
+/// read. The errors of failed tasks are shared between all of their readers, and are unwrapped
+/// here.
+pub fn downcast_task_error<E>(err: &anyhow::Error) -> Option<&E>
+where
+    E: Display + Debug + Send + Sync + 'static,
+{
+    if let Some(err) = err.downcast_ref::<E>() {
+        return Some(err);
+    }
+    let shared = err.downcast_ref::<SharedError>()?;
+    downcast_task_error(shared)
+}
 
 /// Trait to implement in order for a type to be accepted as a
 /// `turbo_tasks::function` return type.
@@ -31,6 +56,24 @@ where
     }
 }
 
+impl<T> TaskOutput for Option<Vc<T>>
+where
+    T: Send + 'static,
+{
+    type Return = Vc<Option<Vc<T>>>;
+
+    fn try_from_raw_vc(raw_vc: RawVc) -> Self::Return {
+        Vc {
+            node: raw_vc,
+            _t: PhantomData,
+        }
+    }
+
+    fn try_into_raw_vc(self) -> Result<RawVc> {
+        Ok(Vc::<Option<Vc<T>>>::cell(self).node)
+    }
+}
+
 impl TaskOutput for () {
     type Return = Vc<()>;
 
@@ -44,9 +87,10 @@ impl TaskOutput for () {
     }
 }
 
-impl<T> TaskOutput for Result<T>
+impl<T, E> TaskOutput for Result<T, E>
 where
     T: TaskOutput,
+    E: Into<TaskError>,
 {
     type Return = T::Return;
 
@@ -55,6 +99,6 @@ where
     }
 
     fn try_into_raw_vc(self) -> Result<RawVc> {
-        self?.try_into_raw_vc()
+        self.map_err(Into::into)?.try_into_raw_vc()
     }
 }
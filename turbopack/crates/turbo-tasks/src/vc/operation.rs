@@ -0,0 +1,195 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use auto_hash_map::{AutoMap, AutoSet};
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    trace::{TraceRawVcs, TraceRawVcsContext},
+    vc::{ReadVcFuture, ResolvedVc, Vc},
+    RcStr, VcValueType,
+};
+
+/// The output of a side-effecting task function, created by calling a
+/// [`#[turbo_tasks::function(operation)]`][macro@crate::function].
+///
+/// Unlike a [`Vc`], an `OperationVc` always points to the output of the task that executed the
+/// operation, so it can't be read without making the current task depend on the operation, either
+/// by [connecting][OperationVc::connect] it or by [reading it strongly
+/// consistently][OperationVc::read_strongly_consistent].
+#[derive(Serialize, Deserialize)]
+#[serde(transparent, bound = "")]
+pub struct OperationVc<T>
+where
+    T: ?Sized + Send,
+{
+    pub(crate) node: Vc<T>,
+}
+
+impl<T> OperationVc<T>
+where
+    T: ?Sized + Send,
+{
+    // called by the exposed function generated by `#[turbo_tasks::function(operation)]`, the `Vc`
+    // must be the unresolved output of the operation's task
+    #[doc(hidden)]
+    pub fn new_private(node: Vc<T>) -> Self {
+        Self { node }
+    }
+
+    /// Connects the operation to the current task and returns its output.
+    pub fn connect(self) -> Vc<T> {
+        Vc::connect(self.node);
+        self.node
+    }
+
+    /// Resolves the output of the operation once all of its internal tasks are finished.
+    pub async fn resolve_strongly_consistent(self) -> Result<ResolvedVc<T>> {
+        Ok(ResolvedVc {
+            node: self.node.resolve_strongly_consistent().await?,
+        })
+    }
+}
+
+impl<T> OperationVc<T>
+where
+    T: VcValueType,
+{
+    /// Reads the output of the operation once all of its internal tasks are finished.
+    #[must_use]
+    pub fn read_strongly_consistent(self) -> ReadVcFuture<T> {
+        self.node.strongly_consistent()
+    }
+}
+
+impl<T> Copy for OperationVc<T> where T: ?Sized + Send {}
+
+impl<T> Clone for OperationVc<T>
+where
+    T: ?Sized + Send,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> PartialEq<OperationVc<T>> for OperationVc<T>
+where
+    T: ?Sized + Send,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T> Eq for OperationVc<T> where T: ?Sized + Send {}
+
+impl<T> Hash for OperationVc<T>
+where
+    T: ?Sized + Send,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.hash(state);
+    }
+}
+
+impl<T> std::fmt::Debug for OperationVc<T>
+where
+    T: ?Sized + Send,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OperationVc")
+            .field("node", &self.node.node)
+            .finish()
+    }
+}
+
+impl<T> TraceRawVcs for OperationVc<T>
+where
+    T: ?Sized + Send,
+{
+    fn trace_raw_vcs(&self, trace_context: &mut TraceRawVcsContext) {
+        TraceRawVcs::trace_raw_vcs(&self.node, trace_context);
+    }
+}
+
+/// Indicates that a type is safe to pass as an input to a
+/// [`#[turbo_tasks::function(operation)]`][macro@crate::function]: it doesn't contain any
+/// [`Vc`] or [`ResolvedVc`], whose values could be read without depending on the operation that
+/// created them. It may contain [`OperationVc`].
+///
+/// # Safety
+///
+/// This trait is marked as unsafe. You should not implement it yourself, but instead rely on
+/// [`#[derive(turbo_tasks::OperationValue)]`][macro@OperationValue] to check the fields for you.
+pub unsafe trait OperationValue {}
+
+unsafe impl<T: ?Sized + Send> OperationValue for OperationVc<T> {}
+
+macro_rules! impl_operation {
+    ($ty:ty) => {
+        unsafe impl OperationValue for $ty {}
+    };
+
+    ($ty:ty, $($tys:ty),+) => {
+        impl_operation!($ty);
+        impl_operation!($($tys),+);
+    }
+}
+
+impl_operation!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64, char, bool, usize);
+impl_operation!((), str, String, Duration, anyhow::Error, RcStr);
+impl_operation!(Path, PathBuf);
+impl_operation!(serde_json::Value);
+
+// based on stdlib's internal `tuple_impls!` macro
+macro_rules! impl_operation_tuple {
+    ($T:ident) => {
+        impl_operation_tuple!(@impl $T);
+    };
+    ($T:ident $( $U:ident )+) => {
+        impl_operation_tuple!($( $U )+);
+        impl_operation_tuple!(@impl $T $( $U )+);
+    };
+    (@impl $( $T:ident )+) => {
+        unsafe impl<$($T: OperationValue),+> OperationValue for ($($T,)+) {}
+    };
+}
+
+impl_operation_tuple!(E D C B A Z Y X W V U T);
+
+unsafe impl<T: OperationValue> OperationValue for Option<T> {}
+unsafe impl<T: OperationValue> OperationValue for Vec<T> {}
+unsafe impl<T: OperationValue, const N: usize> OperationValue for [T; N] {}
+unsafe impl<T: OperationValue> OperationValue for [T] {}
+unsafe impl<T: OperationValue, S> OperationValue for HashSet<T, S> {}
+unsafe impl<T: OperationValue, S, const I: usize> OperationValue for AutoSet<T, S, I> {}
+unsafe impl<T: OperationValue> OperationValue for BTreeSet<T> {}
+unsafe impl<T: OperationValue, S> OperationValue for IndexSet<T, S> {}
+unsafe impl<K: OperationValue, V: OperationValue, S> OperationValue for HashMap<K, V, S> {}
+unsafe impl<K: OperationValue, V: OperationValue, S, const I: usize> OperationValue
+    for AutoMap<K, V, S, I>
+{
+}
+unsafe impl<K: OperationValue, V: OperationValue> OperationValue for BTreeMap<K, V> {}
+unsafe impl<K: OperationValue, V: OperationValue, S> OperationValue for IndexMap<K, V, S> {}
+unsafe impl<T: OperationValue + ?Sized> OperationValue for Box<T> {}
+unsafe impl<T: OperationValue + ?Sized> OperationValue for Arc<T> {}
+unsafe impl<T: OperationValue, E: OperationValue> OperationValue for Result<T, E> {}
+unsafe impl<T: OperationValue + ?Sized> OperationValue for Mutex<T> {}
+unsafe impl<T: OperationValue + ?Sized> OperationValue for RefCell<T> {}
+unsafe impl<T: ?Sized> OperationValue for PhantomData<T> {}
+
+unsafe impl<T: OperationValue + ?Sized> OperationValue for &T {}
+unsafe impl<T: OperationValue + ?Sized> OperationValue for &mut T {}
+
+pub use turbo_tasks_macros::OperationValue;